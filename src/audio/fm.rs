@@ -11,14 +11,14 @@ pub(crate) struct Fm {
 
 impl Default for Fm {
     fn default() -> Self {
-        Self::new()
+        Self::new(3_579_545, 44100)
     }
 }
 
 impl Fm {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(master_clock: u32, sample_rate: u32) -> Self {
         Self {
-            ym2413: Ym2413::new(3579545, 44100),
+            ym2413: Ym2413::new(master_clock, sample_rate),
             fm_enable: false,
             user_disabled: false,
         }
@@ -28,12 +28,10 @@ impl Fm {
         match port {
             0xF0 => self.ym2413.write_address(value),
             0xF1 => self.ym2413.write_data(value),
-            0xF2 => {
-                // When user has disabled FM, ignore the game's attempt to enable it.
-                // This makes the game believe FM hardware is absent and use PSG instead.
-                if !self.user_disabled {
-                    self.fm_enable = (value & 0x01) != 0;
-                }
+            // When user has disabled FM, ignore the game's attempt to enable it.
+            // This makes the game believe FM hardware is absent and use PSG instead.
+            0xF2 if !self.user_disabled => {
+                self.fm_enable = (value & 0x01) != 0;
             }
             _ => {}
         }
@@ -61,7 +59,7 @@ mod tests {
 
     #[test]
     fn fm_disabled_by_default() {
-        let fm = Fm::new();
+        let fm = Fm::new(3_579_545, 44100);
         // Port $F2 deve retornar 0 quando FM não está habilitado
         let mut fm = fm;
         assert_eq!(fm.read_data(0xF2), 0, "FM não habilitado → port $F2 = 0");
@@ -69,14 +67,14 @@ mod tests {
 
     #[test]
     fn enabling_fm_via_port_f2() {
-        let mut fm = Fm::new();
+        let mut fm = Fm::new(3_579_545, 44100);
         fm.write_data(0xF2, 0x01); // habilita FM
         assert_eq!(fm.read_data(0xF2), 1, "FM habilitado → port $F2 = 1");
     }
 
     #[test]
     fn user_disabled_hides_fm_from_game() {
-        let mut fm = Fm::new();
+        let mut fm = Fm::new(3_579_545, 44100);
         fm.user_disabled = true;
         fm.write_data(0xF2, 0x01); // jogo tenta habilitar FM
         // Como user_disabled=true, o bit é ignorado
@@ -85,7 +83,7 @@ mod tests {
 
     #[test]
     fn user_disabled_produces_silent_sample() {
-        let mut fm = Fm::new();
+        let mut fm = Fm::new(3_579_545, 44100);
         fm.write_data(0xF2, 0x01); // habilita FM
         fm.user_disabled = true;
         assert_eq!(fm.generate_sample(), 0.0, "user_disabled → sample silencioso");
@@ -93,14 +91,14 @@ mod tests {
 
     #[test]
     fn fm_not_enabled_produces_silent_sample() {
-        let mut fm = Fm::new();
+        let mut fm = Fm::new(3_579_545, 44100);
         // FM não habilitado (port $F2 = 0)
         assert_eq!(fm.generate_sample(), 0.0);
     }
 
     #[test]
     fn disabling_fm_after_enable() {
-        let mut fm = Fm::new();
+        let mut fm = Fm::new(3_579_545, 44100);
         fm.write_data(0xF2, 0x01); // habilita
         fm.write_data(0xF2, 0x00); // desabilita
         assert_eq!(fm.read_data(0xF2), 0, "FM desabilitado via port $F2");
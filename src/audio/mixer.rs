@@ -7,10 +7,10 @@ pub(crate) struct AudioMixer {
 }
 
 impl AudioMixer {
-    pub(crate) fn new(is_gg: bool, sample_rate: f32) -> Self {
+    pub(crate) fn new(is_gg: bool, master_clock: u32, sample_rate: u32) -> Self {
         Self {
-            psg: Psg::new(is_gg, sample_rate),
-            fm: Fm::new(),
+            psg: Psg::new(is_gg, master_clock, sample_rate),
+            fm: Fm::new(master_clock, sample_rate),
         }
     }
 
@@ -36,7 +36,7 @@ mod tests {
 
     #[test]
     fn all_channels_silent_by_default() {
-        let mut mixer = AudioMixer::new(false, 44100.0);
+        let mut mixer = AudioMixer::new(false, 3_579_545, 44100);
         // All PSG volume registers default to 15 (silent); FM disabled by default.
         let (l, r) = mixer.generate_sample();
         assert_eq!(l, 0.0);
@@ -45,20 +45,20 @@ mod tests {
 
     #[test]
     fn output_is_clamped_to_unit_range() {
-        let mut mixer = AudioMixer::new(false, 44100.0);
+        let mut mixer = AudioMixer::new(false, 3_579_545, 44100);
         // Force maximum PSG output: ch0 volume=0 (loudest), tone=1 (fast toggle)
         mixer.psg.write_data(0x81); // latch ch0 tone, data=1
         mixer.psg.write_data(0x90); // latch ch0 volume=0
         for _ in 0..1000 {
             let (l, r) = mixer.generate_sample();
-            assert!(l >= -1.0 && l <= 1.0, "left sample out of range: {l}");
-            assert!(r >= -1.0 && r <= 1.0, "right sample out of range: {r}");
+            assert!((-1.0..=1.0).contains(&l), "left sample out of range: {l}");
+            assert!((-1.0..=1.0).contains(&r), "right sample out of range: {r}");
         }
     }
 
     #[test]
     fn psg_active_produces_nonzero_output() {
-        let mut mixer = AudioMixer::new(false, 44100.0);
+        let mut mixer = AudioMixer::new(false, 3_579_545, 44100);
         // ch0: tone=1 (fast), volume=0 (max)
         mixer.psg.write_data(0x81);
         mixer.psg.write_data(0x90);
@@ -71,7 +71,7 @@ mod tests {
     fn fm_gain_applied_before_mix() {
         // FM output is multiplied by FM_GAIN=4.0 and clamped before mixing.
         // With FM disabled (user_disabled=true), fm_out = 0 regardless of ym2413 state.
-        let mut mixer = AudioMixer::new(false, 44100.0);
+        let mut mixer = AudioMixer::new(false, 3_579_545, 44100);
         mixer.fm.user_disabled = true;
         let (l, r) = mixer.generate_sample();
         assert_eq!(l, 0.0);
@@ -81,7 +81,7 @@ mod tests {
     #[test]
     fn stereo_channels_symmetric_when_no_gg_panning() {
         // SMS mixer produces equal L/R since PSG has no per-channel panning.
-        let mut mixer = AudioMixer::new(false, 44100.0);
+        let mut mixer = AudioMixer::new(false, 3_579_545, 44100);
         mixer.psg.write_data(0x81); // ch0 tone active
         mixer.psg.write_data(0x90); // ch0 vol=0
         for _ in 0..100 {
@@ -9,7 +9,7 @@ const PSG_VOLUME_TABLE: [f32; 16] = [
 
 /// SN76489 PSG emulation using integer decrementing counters.
 ///
-/// The real chip has a master clock (3579545 Hz NTSC) divided by 16 to get
+/// The real chip has a master clock (3579545 Hz NTSC, 3546893 Hz PAL) divided by 16 to get
 /// the internal clock. Each channel has a 10-bit counter that decrements
 /// every internal clock tick. When it reaches zero, it reloads from the
 /// register and the output polarity toggles.
@@ -38,14 +38,13 @@ pub(crate) struct Psg {
 
 impl Default for Psg {
     fn default() -> Self {
-        Self::new(false, 44100.0)
+        Self::new(false, 3_579_545, 44100)
     }
 }
 
 impl Psg {
-    pub(crate) fn new(is_gg: bool, sample_rate: f32) -> Self {
-        let master_clock: f64 = 3579545.0;
-        let psg_clock = master_clock / 16.0;
+    pub(crate) fn new(is_gg: bool, master_clock: u32, sample_rate: u32) -> Self {
+        let psg_clock = master_clock as f64 / 16.0;
 
         Self {
             registers: [
@@ -236,7 +235,7 @@ mod tests {
 
     #[test]
     fn latch_byte_selects_tone0_low_nibble() {
-        let mut psg = Psg::new(false, 44100.0);
+        let mut psg = Psg::new(false, 3_579_545, 44100);
         // Latch byte: bit7=1, bits6-4=000 (reg 0 = tom 0), bits3-0=0b1010
         psg.write_data(0x80 | 0x0A);
         assert_eq!(psg.registers[0] & 0x0F, 0x0A, "nibble baixo do registro 0");
@@ -244,7 +243,7 @@ mod tests {
 
     #[test]
     fn data_byte_updates_upper_6_bits_of_tone() {
-        let mut psg = Psg::new(false, 44100.0);
+        let mut psg = Psg::new(false, 3_579_545, 44100);
         // Primeiro faz latch no reg 0 com nibble baixo = 0x05
         psg.write_data(0x85); // latch reg0, data=5
         // Data byte: bit7=0, bits5-0 = upper 6 bits
@@ -255,7 +254,7 @@ mod tests {
 
     #[test]
     fn latch_byte_selects_volume_register() {
-        let mut psg = Psg::new(false, 44100.0);
+        let mut psg = Psg::new(false, 3_579_545, 44100);
         // Reg 1 (volume do canal 0): latch byte = 1 ccc t dddd → ccc=000, t=1 → reg índice 1
         // byte = 0x80 | (0b001 << 4) | 0x07 = 0x97
         psg.write_data(0x97);
@@ -265,7 +264,7 @@ mod tests {
     #[test]
     fn volume_15_produces_silence() {
         // Volume 15 = silêncio na tabela de volumes do PSG
-        let mut psg = Psg::new(false, 44100.0);
+        let mut psg = Psg::new(false, 3_579_545, 44100);
         // Todos os canais já iniciam com volume 0x0F (silêncio)
         let (l, r) = psg.generate_sample();
         assert_eq!(l, 0.0, "canal silencioso deve produzir sample 0.0");
@@ -275,7 +274,7 @@ mod tests {
     #[test]
     fn volume_0_produces_nonzero_output() {
         // Canal 0 com tom e volume = 0 (máximo)
-        let mut psg = Psg::new(false, 44100.0);
+        let mut psg = Psg::new(false, 3_579_545, 44100);
         // Configura tom 0 = 0x001 (frequência bem alta para garantir saída não-zero)
         psg.write_data(0x81); // latch reg0, data=1 → lower nibble = 1
         psg.write_data(0x00); // data byte: upper 6 bits = 0 → reg[0] = 1
@@ -292,22 +291,22 @@ mod tests {
 
     #[test]
     fn noise_control_write_resets_lfsr() {
-        let mut psg = Psg::new(false, 44100.0);
+        let mut psg = Psg::new(false, 3_579_545, 44100);
         // Avança o LFSR gerando alguns samples
         psg.write_data(0x90); // volume 0 no noise
         for _ in 0..50 { psg.generate_sample(); }
         // Escreve no registro de controle do noise (reg 6)
-        psg.write_data(0x80 | (0b110 << 4) | 0x00); // latch reg6
+        psg.write_data(0x80 | (0b110 << 4)); // latch reg6, data=0
         assert_eq!(psg.noise_lfsr, 0x8000, "escrever no noise control deve resetar o LFSR");
     }
 
     #[test]
     fn stereo_write_only_affects_gg() {
-        let mut psg_sms = Psg::new(false, 44100.0);
+        let mut psg_sms = Psg::new(false, 3_579_545, 44100);
         psg_sms.write_stereo(0x00); // não-GG ignora
         assert_eq!(psg_sms.stereo, 0xFF, "SMS ignora write_stereo");
 
-        let mut psg_gg = Psg::new(true, 44100.0);
+        let mut psg_gg = Psg::new(true, 3_579_545, 44100);
         psg_gg.write_stereo(0x0F);
         assert_eq!(psg_gg.stereo, 0x0F, "GG aplica write_stereo");
     }
@@ -316,7 +315,7 @@ mod tests {
     fn tone0_zero_treated_as_0x400() {
         // Quando registrador de tom = 0, o contador deve recarregar com 0x400
         // Isso é verificado indiretamente: a saída não deve travar (sample gerado sem panic)
-        let mut psg = Psg::new(false, 44100.0);
+        let mut psg = Psg::new(false, 3_579_545, 44100);
         psg.write_data(0x80); // latch reg0, data=0
         psg.write_data(0x00); // upper 6 bits = 0 → reg[0] = 0
         psg.write_data(0x90); // volume 0
@@ -325,10 +324,36 @@ mod tests {
 
     #[test]
     fn all_channels_start_silent() {
-        let psg = Psg::new(false, 44100.0);
+        let psg = Psg::new(false, 3_579_545, 44100);
         assert_eq!(psg.registers[1], 0x0F, "vol canal 0 inicia em 15 (silêncio)");
         assert_eq!(psg.registers[3], 0x0F, "vol canal 1 inicia em 15");
         assert_eq!(psg.registers[5], 0x0F, "vol canal 2 inicia em 15");
         assert_eq!(psg.registers[7], 0x0F, "vol noise inicia em 15");
     }
+
+    #[test]
+    fn pal_clock_runs_fewer_ticks_per_sample() {
+        let ntsc = Psg::new(false, 3_579_545, 44100);
+        let pal  = Psg::new(false, 3_546_893, 44100);
+        assert!(pal.clock_step < ntsc.clock_step, "PAL PSG clock é mais lento que o NTSC");
+    }
+
+    #[test]
+    fn tone_pitch_is_independent_of_output_rate() {
+        // Tom de ~440 Hz (reg = 254): ~881 trocas de polaridade por segundo em qualquer taxa
+        for rate in [44100, 48000, 96000] {
+            let mut psg = Psg::new(false, 3_579_545, rate);
+            psg.write_data(0x8E); // ch0 tone, 4 bits baixos = 0xE
+            psg.write_data(0x0F); // 6 bits altos = 0x0F → reg = 254
+            psg.write_data(0x90); // ch0 volume máximo
+            let mut last = psg.generate_sample().0 > 0.0;
+            let mut edges = 0;
+            for _ in 0..rate {
+                let high = psg.generate_sample().0 > 0.0;
+                if high != last { edges += 1; }
+                last = high;
+            }
+            assert!((870..=890).contains(&edges), "{rate} Hz: {edges} trocas de polaridade");
+        }
+    }
 }
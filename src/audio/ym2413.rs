@@ -572,26 +572,20 @@ impl Ym2413 {
         }
 
         match slot.eg_state {
-            EG_DAMP => {
-                if slot.eg_out >= EG_MAX && (eg_counter & mask) == 0 {
-                    Self::start_envelope(slot);
-                    if (slot.slot_type & 1) != 0 && slot.pg_keep == 0 {
-                        slot.pg_phase = 0;
-                        // Buddy reset happens below outside borrow
-                    }
+            EG_DAMP if slot.eg_out >= EG_MAX && (eg_counter & mask) == 0 => {
+                Self::start_envelope(slot);
+                if (slot.slot_type & 1) != 0 && slot.pg_keep == 0 {
+                    slot.pg_phase = 0;
+                    // Buddy reset happens below outside borrow
                 }
             }
-            EG_ATTACK => {
-                if slot.eg_out == 0 {
-                    slot.eg_state = EG_DECAY;
-                    slot.update_requests |= 4;
-                }
+            EG_ATTACK if slot.eg_out == 0 => {
+                slot.eg_state = EG_DECAY;
+                slot.update_requests |= 4;
             }
-            EG_DECAY => {
-                if (slot.eg_out >> 3) == slot.patch.sl {
-                    slot.eg_state = EG_SUSTAIN;
-                    slot.update_requests |= 4;
-                }
+            EG_DECAY if (slot.eg_out >> 3) == slot.patch.sl => {
+                slot.eg_state = EG_SUSTAIN;
+                slot.update_requests |= 4;
             }
             _ => {}
         }
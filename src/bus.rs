@@ -1,4 +1,5 @@
-use crate::platform::Platform;
+use crate::debugger::{Access, AddressSpace, WatchHit, Watchpoint};
+use crate::platform::{Platform, VideoStandard};
 
 pub(crate) struct Bus {
     pub(crate) mmu:    crate::mmu::Mmu,
@@ -6,31 +7,64 @@ pub(crate) struct Bus {
     pub(crate) joypad: crate::joypad::Joypad,
     pub(crate) mixer:  crate::audio::mixer::AudioMixer,
     pub(crate) platform: Platform,
+    /// Debugger watchpoints, installed only while a debug frame runs.
+    pub(crate) watchpoints: Vec<Watchpoint>,
+    /// First watchpoint hit of the current instruction (`pc` filled in by the debugger).
+    pub(crate) watch_hit: Option<WatchHit>,
 }
 
 impl Bus {
-    pub(crate) fn new(rom: Vec<u8>, platform: Platform, sample_rate: f32) -> Self {
+    pub(crate) fn new(rom: Vec<u8>, platform: Platform, video: VideoStandard, sample_rate: u32) -> Self {
         Self {
             mmu:    crate::mmu::Mmu::new(rom, platform),
             vdp:    crate::vdp::Vdp::new(platform),
             joypad: crate::joypad::Joypad::new(),
-            mixer:  crate::audio::mixer::AudioMixer::new(platform.is_gg(), sample_rate),
+            mixer:  crate::audio::mixer::AudioMixer::new(platform.is_gg(), video.master_clock(), sample_rate),
             platform,
+            watchpoints: Vec::new(),
+            watch_hit: None,
         }
     }
 
+    fn check_watchpoints(&mut self, space: AddressSpace, access: Access, addr: u16, value: u8) {
+        if self.watch_hit.is_none() && self.watchpoints.iter().any(|w| w.matches(space, access, addr)) {
+            self.watch_hit = Some(WatchHit { space, access, addr, value, pc: 0 });
+        }
+    }
+
+    /// Reads memory without side effects and without checking watchpoints
+    /// (debugger, disassembler).
+    pub(crate) fn peek(&self, addr: u16) -> u8 {
+        self.mmu.read(addr)
+    }
+
     /// Read a byte from the memory bus.
     pub(crate) fn read(&mut self, addr: u16) -> u8 {
-        self.mmu.read(addr)
+        let value = self.mmu.read(addr);
+        if !self.watchpoints.is_empty() {
+            self.check_watchpoints(AddressSpace::Memory, Access::Read, addr, value);
+        }
+        value
     }
 
     /// Write a byte to the memory bus.
     pub(crate) fn write(&mut self, addr: u16, value: u8) {
+        if !self.watchpoints.is_empty() {
+            self.check_watchpoints(AddressSpace::Memory, Access::Write, addr, value);
+        }
         self.mmu.write(addr, value);
     }
 
     /// Read from an I/O port ($00–$FF).
     pub(crate) fn read_io(&mut self, port: u8) -> u8 {
+        let value = self.port_in(port);
+        if !self.watchpoints.is_empty() {
+            self.check_watchpoints(AddressSpace::Io, Access::Read, port as u16, value);
+        }
+        value
+    }
+
+    fn port_in(&mut self, port: u8) -> u8 {
         match port {
             // VDP data/control ports: 0x80–0xBF
             0x80..=0xBF => {
@@ -49,11 +83,12 @@ impl Bus {
                 }
             },
             // Game Gear Start button and I/O ports
-            0x00 => if self.platform.is_gg() { self.joypad.read_port_00() } else { 0xFF },
+            0x00 if self.platform.is_gg() => self.joypad.read_port_00(),
             // FM audio detection port ($F0–$F2) — checked before the 0xC0-0xFF joypad mirror
             0xF0..=0xF2 => self.mixer.fm.read_data(port),
             // Joypad ports: 0xC0–0xFF (mirrored throughout this range)
             // Even ports = Port A ($DC equivalent), Odd ports = Port B ($DD equivalent)
+            0xC0..=0xFF if !self.mmu.io_enabled() => 0xFF,
             0xC0..=0xFF => {
                 if port.is_multiple_of(2) {
                     self.joypad.read_port_dc()
@@ -67,6 +102,9 @@ impl Bus {
 
     /// Write to an I/O port ($00–$FF).
     pub(crate) fn write_io(&mut self, port: u8, value: u8) {
+        if !self.watchpoints.is_empty() {
+            self.check_watchpoints(AddressSpace::Io, Access::Write, port as u16, value);
+        }
         match port {
             // VDP data/control ports: 0x80–0xBF
             0x80..=0xBF => {
@@ -77,17 +115,16 @@ impl Bus {
                 }
             },
             // Game Gear stereo panning (port 0x06)
-            0x06 => {
-                if self.platform.is_gg() {
-                    self.mixer.psg.write_stereo(value);
-                }
-            },
+            0x06 if self.platform.is_gg() => self.mixer.psg.write_stereo(value),
             // PSG audio ports: 0x40–0x7F
             0x40..=0x7F => self.mixer.psg.write_data(value),
+            // Game Gear serial / link registers
+            0x00 | 0x02 | 0x04 if self.platform.is_gg() => {},
             // System memory control ($3E/$3F, mirrored 0x00–0x3F)
             0x00..=0x3F => {
                 if port.is_multiple_of(2) {
-                    // Bit 3 (0x08) = Cartridge RAM enable (Memory Control)
+                    // Slot, work RAM and I/O enables (Memory Control)
+                    self.mmu.write_memory_control(value);
                 } else {
                     // Nationalization / Port A/B control (I/O Control)
                     self.joypad.write_port_3f(value);
@@ -112,13 +149,27 @@ impl System {
     }
 }
 
+impl z80::Z80_io for System {
+    fn read_byte(&self, addr: u16) -> u8 {
+        self.bus.borrow_mut().read(addr)
+    }
+    fn write_byte(&mut self, addr: u16, value: u8) {
+        self.bus.borrow_mut().write(addr, value);
+    }
+    fn port_in(&self, addr: u16) -> u8 {
+        self.bus.borrow_mut().read_io((addr & 0xFF) as u8)
+    }
+    fn port_out(&mut self, addr: u16, value: u8) {
+        self.bus.borrow_mut().write_io((addr & 0xFF) as u8, value);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::platform::Platform;
 
     fn make_sms_bus() -> Bus {
-        Bus::new(vec![0u8; 0xC000], Platform::MasterSystem, 44100.0)
+        Bus::new(vec![0u8; 0xC000], Platform::MasterSystem, VideoStandard::Ntsc, 44100)
     }
 
     // ── V/H counter reads (ports 0x40–0x7F) ──────────────────────────────────
@@ -175,6 +226,25 @@ mod tests {
         assert_eq!(bus.read_io(0xFF), 0xFF);
     }
 
+    // ── Nationalization (port 0x3F → 0xDD) ───────────────────────────────────
+
+    #[test]
+    fn export_region_echoes_port_3f_th_levels_on_dd() {
+        let mut bus = make_sms_bus();
+        bus.write_io(0x3F, 0x55);
+        assert_eq!(bus.read_io(0xDD) & 0xC0, 0x00);
+        bus.write_io(0x3F, 0xF5);
+        assert_eq!(bus.read_io(0xDD) & 0xC0, 0xC0);
+    }
+
+    #[test]
+    fn japan_region_ignores_port_3f_th_levels() {
+        let mut bus = make_sms_bus();
+        bus.joypad.region = crate::platform::ConsoleRegion::Japan;
+        bus.write_io(0x3F, 0x55);
+        assert_eq!(bus.read_io(0xDD) & 0xC0, 0xC0);
+    }
+
     // ── Port 0x00 (GG Start) ──────────────────────────────────────────────────
 
     #[test]
@@ -185,7 +255,7 @@ mod tests {
 
     #[test]
     fn read_port_00_on_gg_returns_joypad_byte() {
-        let mut bus = Bus::new(vec![0u8; 0xC000], Platform::GameGear, 44100.0);
+        let mut bus = Bus::new(vec![0u8; 0xC000], Platform::GameGear, VideoStandard::Ntsc, 44100);
         // Start not pressed → bit 7 high
         assert_eq!(bus.read_io(0x00) & 0x80, 0x80);
     }
@@ -217,7 +287,7 @@ mod tests {
 
     #[test]
     fn write_port_06_sets_gg_stereo() {
-        let mut bus = Bus::new(vec![0u8; 0xC000], Platform::GameGear, 44100.0);
+        let mut bus = Bus::new(vec![0u8; 0xC000], Platform::GameGear, VideoStandard::Ntsc, 44100);
         bus.write_io(0x06, 0xFF);
         assert_eq!(bus.mixer.psg.stereo, 0xFF);
     }
@@ -255,6 +325,29 @@ mod tests {
         assert_eq!(bus.read_io(0xF2), 0x00);
     }
 
+    // ── Watchpoints ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn watchpoint_records_first_matching_access() {
+        let mut bus = make_sms_bus();
+        bus.watchpoints.push(Watchpoint { space: AddressSpace::Memory, start: 0xC000, end: 0xC00F, read: false, write: true });
+        bus.read(0xC000);
+        assert_eq!(bus.watch_hit, None, "leitura não observada");
+        bus.write(0xC005, 0x42);
+        bus.write(0xC006, 0x43);
+        let hit = bus.watch_hit.unwrap();
+        assert_eq!((hit.addr, hit.value, hit.access), (0xC005, 0x42, Access::Write));
+    }
+
+    #[test]
+    fn io_watchpoint_sees_port_value() {
+        let mut bus = make_sms_bus();
+        bus.watchpoints.push(Watchpoint { space: AddressSpace::Io, start: 0xDC, end: 0xDC, read: true, write: false });
+        bus.read_io(0xDC);
+        let hit = bus.watch_hit.unwrap();
+        assert_eq!((hit.space, hit.addr, hit.value), (AddressSpace::Io, 0xDC, 0xFF));
+    }
+
     // ── Unmapped ports ────────────────────────────────────────────────────────
 
     #[test]
@@ -264,18 +357,3 @@ mod tests {
         assert_eq!(bus.read_io(0x38), 0xFF);
     }
 }
-
-impl z80::Z80_io for System {
-    fn read_byte(&self, addr: u16) -> u8 {
-        self.bus.borrow_mut().read(addr)
-    }
-    fn write_byte(&mut self, addr: u16, value: u8) {
-        self.bus.borrow_mut().write(addr, value);
-    }
-    fn port_in(&self, addr: u16) -> u8 {
-        self.bus.borrow_mut().read_io((addr & 0xFF) as u8)
-    }
-    fn port_out(&mut self, addr: u16, value: u8) {
-        self.bus.borrow_mut().write_io((addr & 0xFF) as u8, value);
-    }
-}
@@ -0,0 +1,205 @@
+//! Cheat codes. Game Genie codes patch ROM reads (optionally only when the
+//! original byte matches, so banked ROM is patched in the right bank);
+//! Pro Action Replay codes rewrite a RAM byte every frame.
+
+use std::path::Path;
+
+/// What an enabled cheat does to the machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheatEffect {
+    /// Reads of `addr` (while ROM is mapped there) return `value`, if the
+    /// ROM byte equals `compare`.
+    RomPatch { addr: u16, value: u8, compare: Option<u8> },
+    /// `value` is written to RAM at `addr` at the start of every frame.
+    RamWrite { addr: u16, value: u8 },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cheat {
+    pub name:    String,
+    pub enabled: bool,
+    code:        String,
+    effect:      CheatEffect,
+}
+
+impl Cheat {
+    /// Parses a Game Genie (`XXX-XXX` or `XXX-XXX-XXX`) or Pro Action
+    /// Replay (`00XX-XXYY`) code. Dashes, spaces and case are ignored.
+    pub fn parse(code: &str, name: &str) -> Result<Self, String> {
+        let hex: String = code.chars().filter(|c| *c != '-' && !c.is_whitespace()).collect::<String>().to_ascii_uppercase();
+        let digits: Vec<u8> = hex.chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect::<Option<_>>()
+            .ok_or_else(|| format!("Invalid cheat code: {code}"))?;
+        let byte = |i: usize| digits[i] << 4 | digits[i + 1];
+
+        let (code, effect) = match digits.len() {
+            6 | 9 => {
+                let value = byte(0);
+                let addr = ((digits[5] ^ 0xF) as u16) << 12
+                    | (digits[2] as u16) << 8 | (digits[3] as u16) << 4 | digits[4] as u16;
+                let compare = (digits.len() == 9).then(|| (digits[6] << 4 | digits[8]).rotate_right(2) ^ 0xBA);
+                if addr >= 0xC000 {
+                    return Err(format!("Game Genie code {code} does not patch ROM"));
+                }
+                let code = if digits.len() == 9 {
+                    format!("{}-{}-{}", &hex[..3], &hex[3..6], &hex[6..])
+                } else {
+                    format!("{}-{}", &hex[..3], &hex[3..])
+                };
+                (code, CheatEffect::RomPatch { addr, value, compare })
+            }
+            8 if byte(0) == 0 => {
+                let addr = (byte(2) as u16) << 8 | byte(4) as u16;
+                if addr < 0xC000 {
+                    return Err(format!("Action Replay code {code} must target RAM ($C000-$FFFF)"));
+                }
+                (format!("{}-{}", &hex[..4], &hex[4..]), CheatEffect::RamWrite { addr, value: byte(6) })
+            }
+            _ => return Err(format!("Not a Game Genie or Action Replay code: {code}")),
+        };
+        Ok(Self { name: name.trim().to_string(), enabled: true, code, effect })
+    }
+
+    /// The code in canonical form (upper case, with dashes).
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn effect(&self) -> CheatEffect {
+        self.effect
+    }
+}
+
+/// Serialises a cheat list as one `+ CODE name` (or `- CODE name` when
+/// disabled) line per cheat.
+pub fn cheats_to_text(cheats: &[Cheat]) -> String {
+    cheats.iter()
+        .map(|c| format!("{} {} {}\n", if c.enabled { '+' } else { '-' }, c.code, c.name))
+        .collect()
+}
+
+/// Parses [`cheats_to_text`] output. Blank lines and `#` comments are
+/// skipped; a bare code is taken as enabled.
+pub fn cheats_from_text(text: &str) -> Result<Vec<Cheat>, String> {
+    let mut cheats = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') { continue; }
+        let (enabled, rest) = if let Some(rest) = line.strip_prefix('+') {
+            (true, rest.trim_start())
+        } else if let Some(rest) = line.strip_prefix('-') {
+            (false, rest.trim_start())
+        } else {
+            (true, line)
+        };
+        let (code, name) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let mut cheat = Cheat::parse(code, name).map_err(|e| format!("line {}: {e}", n + 1))?;
+        cheat.enabled = enabled;
+        cheats.push(cheat);
+    }
+    Ok(cheats)
+}
+
+/// Loads the cheat list kept beside a ROM; a missing file is an empty list.
+pub fn load_cheats(path: &Path) -> Result<Vec<Cheat>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => cheats_from_text(&text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("{}: {e}", path.display())),
+    }
+}
+
+/// Writes the cheat list, removing the file when the list is empty.
+pub fn save_cheats(path: &Path, cheats: &[Cheat]) -> std::io::Result<()> {
+    if cheats.is_empty() {
+        return match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    std::fs::write(path, cheats_to_text(cheats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Emulator, Platform, VideoStandard};
+    use crate::test_util::with_large_stack;
+
+    #[test]
+    fn decodes_game_genie_codes() {
+        let c = Cheat::parse("ab2-34e", "Vidas").unwrap();
+        assert_eq!(c.code(), "AB2-34E");
+        assert_eq!(c.effect(), CheatEffect::RomPatch { addr: 0x1234, value: 0xAB, compare: None });
+
+        // Byte de comparação 0x5A codificado como G=8, I=3 (H é ignorado)
+        let c = Cheat::parse("AB2 34E 8F3", "").unwrap();
+        assert_eq!(c.code(), "AB2-34E-8F3");
+        assert_eq!(c.effect(), CheatEffect::RomPatch { addr: 0x1234, value: 0xAB, compare: Some(0x5A) });
+
+        assert!(Cheat::parse("AB2-340", "").is_err(), "endereço $F234 não é ROM");
+    }
+
+    #[test]
+    fn decodes_action_replay_codes() {
+        let c = Cheat::parse("00c0-a003", "").unwrap();
+        assert_eq!(c.code(), "00C0-A003");
+        assert_eq!(c.effect(), CheatEffect::RamWrite { addr: 0xC0A0, value: 0x03 });
+        assert!(Cheat::parse("0012-3403", "").is_err(), "PAR só escreve na RAM");
+        assert!(Cheat::parse("01C0A003", "").is_err());
+        assert!(Cheat::parse("XYZ-123", "").is_err());
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let mut off = Cheat::parse("AB2-34E", "Pulo alto").unwrap();
+        off.enabled = false;
+        let cheats = vec![Cheat::parse("00C0A003", "Vidas infinitas").unwrap(), off];
+        let text = cheats_to_text(&cheats);
+        assert_eq!(text, "+ 00C0-A003 Vidas infinitas\n- AB2-34E Pulo alto\n");
+        assert_eq!(cheats_from_text(&format!("# comentário\n\n{text}")).unwrap(), cheats);
+        assert!(cheats_from_text("+ ZZZ").unwrap_err().starts_with("line 1"));
+    }
+
+    #[test]
+    fn non_ascii_first_character_is_an_error_not_a_panic() {
+        let err = cheats_from_text("00C0-A003 Vidas\né ruim").unwrap_err();
+        assert!(err.starts_with("line 2"), "{err}");
+    }
+
+    #[test]
+    fn patches_rom_reads_and_rewrites_ram() {
+        with_large_stack(|| {
+            // Bancos de 16KB preenchidos com o número do banco; 0000: JR $0000
+            let mut rom: Vec<u8> = (0..4u8).flat_map(|b| vec![b; 0x4000]).collect();
+            rom[..2].copy_from_slice(&[0x18, 0xFE]);
+            let mut emu = Emulator::new(rom, Platform::MasterSystem, VideoStandard::Ntsc, 44100.0);
+
+            // $8000 com comparação 0x02: só vale quando o banco 2 está no slot 2
+            let patch = (0x99u8, 0x8000u16, 0x02u8);
+            let stored = (patch.2 ^ 0xBA).rotate_left(2);
+            let code = format!("{:02X}{:X}{:02X}{:X}{:X}0{:X}", patch.0, (patch.1 >> 8) & 0xF, patch.1 & 0xFF,
+                               (patch.1 >> 12) ^ 0xF, stored >> 4, stored & 0xF);
+            let cheats = vec![Cheat::parse(&code, "").unwrap(), Cheat::parse("00C1-0042", "").unwrap()];
+            emu.set_cheats(cheats.clone());
+            assert_eq!(emu.cheats(), &cheats[..]);
+
+            assert_eq!(emu.peek(0x8000), 0x99, "banco 2 mapeado: byte bate com a comparação");
+            emu.poke(0xFFFF, 3);
+            assert_eq!(emu.peek(0x8000), 0x03, "outro banco: sem patch");
+
+            emu.step_frame();
+            assert_eq!(emu.peek(0xC100), 0x42, "PAR aplicado a cada frame");
+            emu.poke(0xC100, 0);
+            emu.step_frame();
+            assert_eq!(emu.peek(0xC100), 0x42);
+
+            let mut disabled = cheats;
+            disabled.iter_mut().for_each(|c| c.enabled = false);
+            emu.set_cheats(disabled);
+            emu.poke(0xFFFF, 2);
+            assert_eq!(emu.peek(0x8000), 0x02, "cheat desativado não altera a ROM");
+        });
+    }
+}
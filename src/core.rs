@@ -1,60 +1,141 @@
 use crate::bus::{Bus, System};
-use crate::platform::Platform;
+use crate::cheats::{Cheat, CheatEffect};
+use crate::debugger::{DebugBreak, Debugger, Registers};
+use crate::disasm::Instruction;
+use crate::gamedb::{self, GameInfo};
+use crate::mapper::MapperKind;
+use crate::mmu::RomPatch;
+use crate::movie::FrameInput;
+use crate::platform::{ConsoleRegion, MediaSlot, Platform, VideoStandard};
+use crate::rom_header::RomInfo;
+use crate::trace::{InterruptProbe, Tracer};
 use z80::Z80;
 
-pub(crate) struct Emulator {
+/// A complete Sega 8-bit machine: Z80, VDP, PSG/FM, mapper and input ports.
+///
+/// The emulator is driven one video frame at a time with [`Emulator::step_frame`];
+/// inputs are latched with the `set_*` methods before each frame.
+pub struct Emulator {
     pub(crate) cpu: Z80<System>,
+    /// CPU cycles run so far in the current frame (a debugger break can stop mid-frame).
     frame_cycles: u32,
     vcounter: u16,
     cycles_accumulator: i32,
     line_interrupt_counter: u8,
+    /// Output sample rate in Hz.
+    sample_rate: u32,
+    /// Cycle × rate accumulator: one sample is due every `master_clock / sample_rate`
+    /// CPU cycles. Carried across frames so the long-term rate is exact.
+    sample_clock: u64,
     pub(crate) platform: Platform,
+    pub(crate) video: VideoStandard,
+    tracer: Option<Box<Tracer>>,
+    cheats: Vec<Cheat>,
+    game: Option<GameInfo>,
 }
 
 impl Emulator {
-    pub(crate) fn new(rom_data: Vec<u8>, platform: Platform, sample_rate: f32) -> Self {
-        let bus = Bus::new(rom_data, platform, sample_rate);
+    /// Builds a powered-on machine with `rom_data` inserted in the cartridge slot.
+    /// `sample_rate` is the audio output rate in Hz. Games in the bundled
+    /// database get their mapper and save memory without further setup.
+    pub fn new(rom_data: Vec<u8>, platform: Platform, video: VideoStandard, sample_rate: f32) -> Self {
+        let sample_rate = sample_rate.round().max(1.0) as u32;
+        let bus = Bus::new(rom_data, platform, video, sample_rate);
         let system = System::new(bus);
         let mut cpu = Z80::new(system);
         cpu.init();
 
-        Self {
+        let mut emu = Self {
             cpu,
             frame_cycles: 0,
             vcounter: 0,
             cycles_accumulator: 0,
             line_interrupt_counter: 0,
+            sample_rate,
+            sample_clock: 0,
             platform,
+            video,
+            tracer: None,
+            cheats: Vec::new(),
+            game: None,
+        };
+        if let Some(game) = gamedb::builtin_entry(emu.rom_crc32()) {
+            emu.set_game_info(game.clone());
+        }
+        emu
+    }
+
+    /// Runs one full frame. Returns whether VBlank was reached and the
+    /// interleaved stereo (L, R) samples generated during the frame.
+    pub fn step_frame(&mut self) -> (bool, Vec<f32>) {
+        let (frame_ready, audio, _) = self.run_frame(None);
+        (frame_ready, audio)
+    }
+
+    /// Like [`step_frame`](Self::step_frame), but checks `dbg`'s breakpoints,
+    /// watchpoints and pending step. On a break the frame is left unfinished and
+    /// the next call resumes it; the third value says why execution stopped.
+    pub fn step_frame_debug(&mut self, dbg: &mut Debugger) -> (bool, Vec<f32>, Option<DebugBreak>) {
+        {
+            let mut bus = self.cpu.io.bus.borrow_mut();
+            bus.watchpoints = std::mem::take(&mut dbg.watchpoints);
+            bus.watch_hit = None;
         }
+        let out = self.run_frame(Some(dbg));
+        dbg.watchpoints = std::mem::take(&mut self.cpu.io.bus.borrow_mut().watchpoints);
+        out
     }
 
-    pub(crate) fn step_frame(&mut self) -> (bool, Vec<f32>) {
+    fn run_frame(&mut self, mut dbg: Option<&mut Debugger>) -> (bool, Vec<f32>, Option<DebugBreak>) {
         let cycles_per_line = 228;
-        let lines_per_frame = 262;
-        let total_frame_cycles = cycles_per_line * lines_per_frame;
-        let mut frame_cycles = 0;
-        
-        let mut audio_buffer = Vec::new();
-        // At 60Hz and 44100Hz audio, there are 735 samples per frame
-        let samples_per_frame = 735;
-        let cycles_per_sample = total_frame_cycles / samples_per_frame;
-        let mut sample_cycles_accumulator = 0;
+        let lines_per_frame = self.video.lines_per_frame();
+        let total_frame_cycles = cycles_per_line * lines_per_frame as u32;
+
+        // ~735 stereo samples per NTSC frame at 44.1 kHz, ~800 at 48 kHz
+        let master_clock = self.video.master_clock() as u64;
+        let sample_rate = self.sample_rate as u64;
+        let mut audio_buffer = Vec::with_capacity(
+            2 * (sample_rate * total_frame_cycles as u64 / master_clock + 2) as usize);
         
         // Retorna true se um frame (vblank) for emitido
         let mut frame_ready = false;
+        let mut stop = None;
+        let mut tracer = self.tracer.take();
+
+        if self.frame_cycles == 0 {
+            self.apply_ram_cheats();
+        }
+
+        while self.frame_cycles < total_frame_cycles {
+            let pc = self.cpu.pc;
+            if let Some(d) = dbg.as_deref_mut() {
+                if let Some(reason) = d.before_instruction(self) {
+                    d.stopped(self, reason);
+                    stop = Some(reason);
+                    break;
+                }
+            }
+
+            let probe = tracer.as_deref_mut().map(|t| {
+                t.before_instruction(self);
+                InterruptProbe::capture(self)
+            });
 
-        while frame_cycles < total_frame_cycles {
             let mut cycles_run = self.cpu.step();
             if cycles_run == 0 {
                 cycles_run = 4; // NOP (Halt state)
             }
+
+            if let (Some(t), Some(probe)) = (tracer.as_deref_mut(), probe) {
+                t.after_instruction(self, cycles_run, probe);
+            }
             
-            frame_cycles += cycles_run;
+            self.frame_cycles += cycles_run;
             self.cycles_accumulator += cycles_run as i32;
 
-            sample_cycles_accumulator += cycles_run;
-            while sample_cycles_accumulator >= cycles_per_sample {
-                sample_cycles_accumulator -= cycles_per_sample;
+            self.sample_clock += cycles_run as u64 * sample_rate;
+            while self.sample_clock >= master_clock {
+                self.sample_clock -= master_clock;
                 let (sample_l, sample_r) = self.cpu.io.bus.borrow_mut().mixer.generate_sample();
                 // O cpal espera interleaved stereo: Left, Right
                 audio_buffer.push(sample_l);
@@ -86,18 +167,13 @@ impl Emulator {
                 {
                     let mut bus = self.cpu.io.bus.borrow_mut();
 
-                    if self.vcounter >= 262 {
+                    if self.vcounter >= lines_per_frame {
                         self.vcounter = 0;
                         bus.joypad.th_pin_low = false;
                         bus.vdp.h_latched = false;
                     }
 
-                    let hw_vcounter = if self.vcounter <= 218 {
-                        self.vcounter
-                    } else {
-                        self.vcounter - 6
-                    };
-                    bus.vdp.v_counter = hw_vcounter as u8;
+                    bus.vdp.v_counter = self.video.hw_vcounter(self.vcounter);
 
                     // H counter: maps pixel positions (0-341) to counter values.
                     // Z80 cycles × 3/2 ≈ pixel position.
@@ -148,11 +224,25 @@ impl Emulator {
             } else {
                 self.cpu.clr_irq();
             }
+
+            if let Some(d) = dbg.as_deref_mut() {
+                if let Some(reason) = d.after_instruction(self, pc) {
+                    d.stopped(self, reason);
+                    stop = Some(reason);
+                    break;
+                }
+            }
+        }
+        if stop.is_none() {
+            self.frame_cycles = 0;
+            if let Some(t) = tracer.as_deref_mut() { t.end_frame(); }
         }
-        (frame_ready, audio_buffer)
+        self.tracer = tracer;
+        (frame_ready, audio_buffer, stop)
     }
 
-    pub(crate) fn get_framebuffer(&self) -> [u32; 256 * 192] {
+    /// Returns the 256×192 ARGB framebuffer (Game Gear games occupy the centred 160×144).
+    pub fn get_framebuffer(&self) -> [u32; 256 * 192] {
         let mut fb = self.cpu.io.bus.borrow().vdp.frame_buffer;
         // Strip the internal priority encoding bit before output
         for pixel in fb.iter_mut() {
@@ -162,8 +252,9 @@ impl Emulator {
     }
 
     // Proxy commands to joypad
+    /// Sets the player 1 pad (port A) and the Pause/Start button.
     #[allow(clippy::too_many_arguments)]
-    pub(crate) fn set_input(&mut self, up: bool, down: bool, left: bool, right: bool, b1: bool, b2: bool, start: bool) {
+    pub fn set_input(&mut self, up: bool, down: bool, left: bool, right: bool, b1: bool, b2: bool, start: bool) {
         let mut bus = self.cpu.io.bus.borrow_mut();
         // Detect rising edge of Start/Pause button
         // SMS: Pause button triggers NMI
@@ -185,18 +276,258 @@ impl Emulator {
         }
     }
 
-    pub(crate) fn set_lightgun(&mut self, active: bool, x: u16, y: u16) {
+    /// Sets the player 2 pad (port B).
+    pub fn set_input_p2(&mut self, up: bool, down: bool, left: bool, right: bool, b1: bool, b2: bool) {
+        let mut bus = self.cpu.io.bus.borrow_mut();
+        bus.joypad.p2_up = up;
+        bus.joypad.p2_down = down;
+        bus.joypad.p2_left = left;
+        bus.joypad.p2_right = right;
+        bus.joypad.p2_b1 = b1;
+        bus.joypad.p2_b2 = b2;
+    }
+
+    /// Sets the Light Phaser state on port A: trigger and aim position in screen pixels.
+    pub fn set_lightgun(&mut self, active: bool, x: u16, y: u16) {
         let mut bus = self.cpu.io.bus.borrow_mut();
         bus.joypad.lightgun_active = active;
         bus.joypad.mouse_x = x;
         bus.joypad.mouse_y = y;
     }
 
-    pub(crate) fn set_fm_disabled(&self, disabled: bool) {
-        self.cpu.io.bus.borrow_mut().mixer.fm.user_disabled = disabled;
+    /// Latches a complete frame of input; a `reset` request power-cycles first.
+    pub fn apply_input(&mut self, input: &FrameInput) {
+        if input.reset { self.power_cycle(); }
+        let (p1, p2) = (&input.p1, &input.p2);
+        self.set_input(p1[0], p1[1], p1[2], p1[3], p1[4] || input.gun.is_some(), p1[5], input.start);
+        self.set_input_p2(p2[0], p2[1], p2[2], p2[3], p2[4], p2[5]);
+        let (gx, gy) = input.gun.unwrap_or((0, 0));
+        self.set_lightgun(input.gun.is_some(), gx.min(255), gy.min(191));
+    }
+
+    /// Power-cycles the machine: same cartridge and settings, battery-backed RAM kept.
+    pub fn power_cycle(&mut self) {
+        self.rebuild(true);
+    }
+
+    /// Rebuilds the machine from its ROM as if just switched on.
+    /// Without `keep_battery_ram`, SRAM and EEPROM start blank (deterministic movies).
+    pub(crate) fn rebuild(&mut self, keep_battery_ram: bool) {
+        let (rom, cart_ram, fm_disabled, region) = {
+            let bus = self.cpu.io.bus.borrow();
+            (bus.mmu.rom[..bus.mmu.rom_size].to_vec(), bus.mmu.cart_ram, bus.mixer.fm.user_disabled, bus.joypad.region)
+        };
+        let eeprom = self.get_eeprom_data();
+        let tracer = self.tracer.take();
+        let cheats = std::mem::take(&mut self.cheats);
+        let game = self.game.take();
+        let bios = self.cpu.io.bus.borrow_mut().mmu.bios.take().map(|b| b.rom);
+        let media = self.media_slot();
+
+        *self = Emulator::new(rom, self.platform, self.video, self.sample_rate as f32);
+        self.set_tracer(tracer);
+        self.set_cheats(cheats);
+        self.set_media_slot(media);
+        if let Some(game) = game { self.set_game_info(game); }
+        if let Some(bios) = bios { self.set_bios(bios); }
+        let mut bus = self.cpu.io.bus.borrow_mut();
+        if eeprom.is_some() && bus.mmu.eeprom.is_none() {
+            bus.mmu.eeprom = Some(crate::eeprom::Eeprom93C46::new());
+        }
+        bus.mixer.fm.user_disabled = fm_disabled;
+        bus.joypad.region = region;
+        drop(bus);
+        if keep_battery_ram {
+            self.load_cart_ram(&cart_ram);
+            if let Some(data) = eeprom { self.load_eeprom_data(&data); }
+        }
+    }
+
+    /// Attaches (or with `None`, detaches) an execution trace; see [`Tracer`].
+    pub fn set_tracer(&mut self, tracer: Option<Box<Tracer>>) {
+        let vdp_writes = tracer.as_ref().is_some_and(|t| t.options().vdp_writes);
+        self.cpu.io.bus.borrow_mut().vdp.reg_write_log = vdp_writes.then(Vec::new);
+        self.tracer = tracer;
+    }
+
+    /// Replaces the cheat list. Enabled Game Genie codes patch ROM reads from
+    /// now on; enabled Action Replay codes are applied at each frame start.
+    pub fn set_cheats(&mut self, cheats: Vec<Cheat>) {
+        self.cpu.io.bus.borrow_mut().mmu.rom_patches = cheats.iter()
+            .filter(|c| c.enabled)
+            .filter_map(|c| match c.effect() {
+                CheatEffect::RomPatch { addr, value, compare } => Some(RomPatch { addr, value, compare }),
+                CheatEffect::RamWrite { .. } => None,
+            })
+            .collect();
+        self.cheats = cheats;
+    }
+
+    pub fn cheats(&self) -> &[Cheat] {
+        &self.cheats
+    }
+
+    fn apply_ram_cheats(&mut self) {
+        let mut bus = self.cpu.io.bus.borrow_mut();
+        for cheat in self.cheats.iter().filter(|c| c.enabled) {
+            if let CheatEffect::RamWrite { addr, value } = cheat.effect() {
+                bus.mmu.write_ram(addr, value);
+            }
+        }
+    }
+
+    /// Detaches the trace so it can be finished or moved to another emulator.
+    pub fn take_tracer(&mut self) -> Option<Box<Tracer>> {
+        self.cpu.io.bus.borrow_mut().vdp.reg_write_log = None;
+        self.tracer.take()
+    }
+
+    /// Current Z80 register file.
+    pub fn registers(&self) -> Registers {
+        let c = &self.cpu;
+        Registers {
+            af: c.get_af(), bc: c.get_bc(), de: c.get_de(), hl: c.get_hl(),
+            af_alt: c.get_af_alt(), bc_alt: c.get_bc_alt(), de_alt: c.get_de_alt(), hl_alt: c.get_hl_alt(),
+            ix: c.ix, iy: c.iy, sp: c.sp, pc: c.pc,
+            i: c.i, r: c.r,
+            iff1: c.iff1, iff2: c.iff2,
+            interrupt_mode: c.interrupt_mode,
+            halted: c.halted,
+        }
+    }
+
+    /// Loads the register file (the interrupt mode and halt state included).
+    pub fn set_registers(&mut self, r: &Registers) {
+        let c = &mut self.cpu;
+        c.set_af(r.af); c.set_bc(r.bc); c.set_de(r.de); c.set_hl(r.hl);
+        c.set_af_alt(r.af_alt); c.set_bc_alt(r.bc_alt); c.set_de_alt(r.de_alt); c.set_hl_alt(r.hl_alt);
+        c.ix = r.ix; c.iy = r.iy; c.sp = r.sp; c.pc = r.pc;
+        c.i = r.i; c.r = r.r;
+        c.iff1 = r.iff1; c.iff2 = r.iff2;
+        c.interrupt_mode = r.interrupt_mode;
+        c.halted = r.halted;
+    }
+
+    /// Reads memory as the CPU would see it, without side effects or watchpoints.
+    pub fn peek(&self, addr: u16) -> u8 {
+        self.cpu.io.bus.borrow().peek(addr)
+    }
+
+    /// Writes memory as the CPU would (mapper registers included), bypassing watchpoints.
+    pub fn poke(&mut self, addr: u16, value: u8) {
+        self.cpu.io.bus.borrow_mut().mmu.write(addr, value);
+    }
+
+    /// Disassembles the instruction at `addr` as currently mapped.
+    pub fn disassemble(&self, addr: u16) -> Instruction {
+        crate::disasm::disassemble(&self.cpu.io.bus.borrow(), addr)
     }
 
-    pub(crate) fn save_state(&self) -> crate::savestate::SaveState {
+    /// Copy of VRAM, CRAM and the VDP registers for the graphics viewers.
+    pub fn vdp_snapshot(&self) -> crate::vdp_view::VdpSnapshot {
+        crate::vdp_view::VdpSnapshot::capture(&self.cpu.io.bus.borrow().vdp)
+    }
+
+    /// Cartridge mapper in use.
+    pub fn mapper(&self) -> MapperKind {
+        self.cpu.io.bus.borrow().mmu.mapper_kind()
+    }
+
+    /// ROM bank mapped at `addr`, or `None` for RAM, cart RAM and EEPROM.
+    pub fn rom_bank_at(&self, addr: u16) -> Option<u16> {
+        self.cpu.io.bus.borrow().mmu.rom_bank_at(addr)
+    }
+
+    /// CRC32 of the cartridge ROM as loaded.
+    pub fn rom_crc32(&self) -> u32 {
+        self.cpu.io.bus.borrow().mmu.rom_crc
+    }
+
+    /// Header, checksum and CRC32 of the loaded ROM image (padding excluded).
+    pub fn rom_info(&self) -> RomInfo {
+        let bus = self.cpu.io.bus.borrow();
+        RomInfo::new(&bus.mmu.rom[..bus.mmu.rom_size])
+    }
+
+    /// Hides the YM2413 from the game (port $F2 reads as absent).
+    /// Games the database marks `fm=off` never see it.
+    pub fn set_fm_disabled(&self, disabled: bool) {
+        let forced = self.game.as_ref().is_some_and(|g| g.fm == Some(false));
+        self.cpu.io.bus.borrow_mut().mixer.fm.user_disabled = disabled || forced;
+    }
+
+    pub fn is_fm_disabled(&self) -> bool {
+        self.cpu.io.bus.borrow().mixer.fm.user_disabled
+    }
+
+    /// Applies a game database entry to a freshly built machine: mapper,
+    /// save memory, sprite limit and FM. Platform, video standard and
+    /// region are chosen before construction (see [`GameInfo`]). The bundled
+    /// entry is already applied by [`new`](Self::new); this is for user entries.
+    pub fn set_game_info(&mut self, game: GameInfo) {
+        {
+            let mut bus = self.cpu.io.bus.borrow_mut();
+            bus.mmu.apply_game(&game);
+            if let Some(limit) = game.sprite_limit { bus.vdp.sprite_limit = limit; }
+            if game.fm == Some(false) { bus.mixer.fm.user_disabled = true; }
+        }
+        self.game = Some(game);
+    }
+
+    /// Installs a console BIOS (SMS, Mark III or Game Gear boot ROM) on a
+    /// freshly built machine, which then boots from it: the BIOS checks the
+    /// cartridge and hands over through port $3E like the real console.
+    pub fn set_bios(&mut self, bios: Vec<u8>) {
+        if bios.is_empty() || self.platform.is_sg_family() { return; }
+        self.cpu.io.bus.borrow_mut().mmu.set_bios(bios);
+    }
+
+    pub fn has_bios(&self) -> bool {
+        self.cpu.io.bus.borrow().mmu.bios.is_some()
+    }
+
+    /// Plugs the game into the card or expansion slot of a freshly built
+    /// Master System instead of the cartridge slot. Other platforms have
+    /// only the cartridge slot.
+    pub fn set_media_slot(&mut self, media: MediaSlot) {
+        if self.platform == Platform::MasterSystem {
+            self.cpu.io.bus.borrow_mut().mmu.set_media(media);
+        }
+    }
+
+    pub fn media_slot(&self) -> MediaSlot {
+        self.cpu.io.bus.borrow().mmu.media
+    }
+
+    /// Database entry the game was loaded with, if it had one.
+    pub fn game_info(&self) -> Option<&GameInfo> {
+        self.game.as_ref()
+    }
+
+    /// Selects Japanese or export nationalization for ports $3F/$DD (and GG port $00).
+    pub fn set_console_region(&self, region: ConsoleRegion) {
+        self.cpu.io.bus.borrow_mut().joypad.region = region;
+    }
+
+    pub fn console_region(&self) -> ConsoleRegion {
+        self.cpu.io.bus.borrow().joypad.region
+    }
+
+    pub fn platform(&self) -> Platform {
+        self.platform
+    }
+
+    pub fn video_standard(&self) -> VideoStandard {
+        self.video
+    }
+
+    /// Audio output rate in Hz that `step_frame` generates samples at.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Captures the full machine state; see [`crate::SaveState::serialize`].
+    pub fn save_state(&self) -> crate::savestate::SaveState {
         use crate::savestate::*;
         let bus = self.cpu.io.bus.borrow();
 
@@ -217,10 +548,9 @@ impl Emulator {
         let mmu = MmuState {
             ram:        bus.mmu.ram,
             cart_ram:   bus.mmu.cart_ram,
-            ram_control: bus.mmu.ram_control,
-            rom_bank_0: bus.mmu.rom_bank_0,
-            rom_bank_1: bus.mmu.rom_bank_1,
-            rom_bank_2: bus.mmu.rom_bank_2,
+            mapper:     bus.mmu.mapper.registers(),
+            memory_control: bus.mmu.memory_control,
+            bios_mapper: bus.mmu.bios.as_ref().map_or(Vec::new(), |b| b.mapper.registers()),
         };
 
         let vdp = bus.vdp.get_state();
@@ -236,7 +566,7 @@ impl Emulator {
         SaveState { cpu, mmu, vdp, psg, timing }
     }
 
-    pub(crate) fn load_state(&mut self, state: crate::savestate::SaveState) {
+    pub fn load_state(&mut self, state: crate::savestate::SaveState) {
         // CPU
         let c = &state.cpu;
         self.cpu.set_af(c.af); self.cpu.set_bc(c.bc);
@@ -256,10 +586,11 @@ impl Emulator {
         let m = &state.mmu;
         bus.mmu.ram        = m.ram;
         bus.mmu.cart_ram   = m.cart_ram;
-        bus.mmu.ram_control = m.ram_control;
-        bus.mmu.rom_bank_0 = m.rom_bank_0;
-        bus.mmu.rom_bank_1 = m.rom_bank_1;
-        bus.mmu.rom_bank_2 = m.rom_bank_2;
+        bus.mmu.mapper.set_registers(&m.mapper);
+        if let Some(ref mut bios) = bus.mmu.bios {
+            bios.mapper.set_registers(&m.bios_mapper);
+            bus.mmu.memory_control = m.memory_control;
+        }
 
         bus.vdp.load_state(&state.vdp);
         bus.mixer.psg.load_state(&state.psg);
@@ -272,25 +603,25 @@ impl Emulator {
         self.frame_cycles          = t.frame_cycles;
     }
 
-    pub(crate) fn has_eeprom(&self) -> bool {
+    pub fn has_eeprom(&self) -> bool {
         self.cpu.io.bus.borrow().mmu.eeprom.is_some()
     }
 
-    pub(crate) fn is_eeprom_dirty(&self) -> bool {
+    pub fn is_eeprom_dirty(&self) -> bool {
         self.cpu.io.bus.borrow().mmu.eeprom.as_ref().map(|e| e.dirty).unwrap_or(false)
     }
 
-    pub(crate) fn clear_eeprom_dirty(&self) {
+    pub fn clear_eeprom_dirty(&self) {
         if let Some(ref mut eeprom) = self.cpu.io.bus.borrow_mut().mmu.eeprom {
             eeprom.dirty = false;
         }
     }
 
-    pub(crate) fn get_eeprom_data(&self) -> Option<Vec<u8>> {
+    pub fn get_eeprom_data(&self) -> Option<Vec<u8>> {
         self.cpu.io.bus.borrow().mmu.eeprom.as_ref().map(|e| e.data.to_vec())
     }
 
-    pub(crate) fn load_eeprom_data(&self, data: &[u8]) {
+    pub fn load_eeprom_data(&self, data: &[u8]) {
         if let Some(ref mut eeprom) = self.cpu.io.bus.borrow_mut().mmu.eeprom {
             let len = data.len().min(eeprom.data.len());
             eeprom.data[..len].copy_from_slice(&data[..len]);
@@ -298,23 +629,24 @@ impl Emulator {
         }
     }
 
-    pub(crate) fn is_sram_dirty(&self) -> bool {
+    pub fn is_sram_dirty(&self) -> bool {
         self.cpu.io.bus.borrow().mmu.sram_dirty
     }
 
-    pub(crate) fn clear_sram_dirty(&self) {
+    pub fn clear_sram_dirty(&self) {
         self.cpu.io.bus.borrow_mut().mmu.sram_dirty = false;
     }
 
-    /// Returns a copy of the 16KB cart RAM.
-    pub(crate) fn get_cart_ram(&self) -> Vec<u8> {
-        self.cpu.io.bus.borrow().mmu.cart_ram.to_vec()
+    /// Returns a copy of the cart RAM (16KB unless the game database says otherwise).
+    pub fn get_cart_ram(&self) -> Vec<u8> {
+        let bus = self.cpu.io.bus.borrow();
+        bus.mmu.cart_ram[..bus.mmu.cart_ram_size].to_vec()
     }
 
     /// Overwrites cart RAM with the given data (used when loading a .sav file).
-    pub(crate) fn load_cart_ram(&self, data: &[u8]) {
+    pub fn load_cart_ram(&self, data: &[u8]) {
         let mut bus = self.cpu.io.bus.borrow_mut();
-        let len = data.len().min(bus.mmu.cart_ram.len());
+        let len = data.len().min(bus.mmu.cart_ram_size);
         bus.mmu.cart_ram[..len].copy_from_slice(&data[..len]);
         bus.mmu.sram_dirty = false;
     }
@@ -323,28 +655,13 @@ impl Emulator {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::platform::Platform;
+    use crate::test_util::with_large_stack;
 
     /// A 48 KB ROM filled with 0x00 (NOP). The Z80 spins through NOPs indefinitely.
     fn nop_rom() -> Vec<u8> { vec![0u8; 0xC000] }
 
     fn make_emu() -> Emulator {
-        Emulator::new(nop_rom(), Platform::MasterSystem, 44100.0)
-    }
-
-    /// Runs `f` on a thread with a 32 MB stack to avoid overflow in debug builds.
-    /// `step_frame` triggers deep Z80 dispatch which exceeds the default 2 MB test stack.
-    fn with_large_stack<F, R>(f: F) -> R
-    where
-        F: FnOnce() -> R + Send + 'static,
-        R: Send + 'static,
-    {
-        std::thread::Builder::new()
-            .stack_size(32 * 1024 * 1024)
-            .spawn(f)
-            .unwrap()
-            .join()
-            .unwrap()
+        Emulator::new(nop_rom(), Platform::MasterSystem, VideoStandard::Ntsc, 44100.0)
     }
 
     // ── step_frame audio ──────────────────────────────────────────────────────
@@ -413,8 +730,49 @@ mod tests {
         });
     }
 
+    #[test]
+    fn pal_frame_produces_more_audio_than_ntsc() {
+        with_large_stack(|| {
+            let mut emu = Emulator::new(nop_rom(), Platform::MasterSystem, VideoStandard::Pal, 44100.0);
+            let (frame_ready, audio) = emu.step_frame();
+            assert!(frame_ready);
+            assert!(
+                audio.len() >= 1700 && audio.len() <= 1850,
+                "expected ~1774 samples (887 pairs) per PAL frame, got {}",
+                audio.len()
+            );
+            assert_eq!(emu.vcounter, 0);
+        });
+    }
+
+    #[test]
+    fn audio_follows_output_sample_rate() {
+        with_large_stack(|| {
+            let mut emu = Emulator::new(nop_rom(), Platform::MasterSystem, VideoStandard::Ntsc, 48000.0);
+            let mut pairs = 0;
+            for _ in 0..60 {
+                pairs += emu.step_frame().1.len() / 2;
+            }
+            // 48000 × 59736 / 3579545 ≈ 801.0 pares por frame NTSC
+            let expected = 48000 * 60 * 59736 / 3_579_545;
+            assert!(pairs.abs_diff(expected) <= 1, "esperado ~{expected} pares em 60 frames, veio {pairs}");
+            assert_eq!(emu.sample_rate(), 48000);
+        });
+    }
+
     // ── set_input / NMI ───────────────────────────────────────────────────────
 
+    #[test]
+    fn set_input_p2_updates_port_b() {
+        with_large_stack(|| {
+            let mut emu = make_emu();
+            emu.set_input_p2(true, false, false, true, false, false);
+            let mut bus = emu.cpu.io.bus.borrow_mut();
+            assert_eq!(bus.read_io(0xDC) & 0xC0, 0x80, "P2 up → bit 6 de $DC");
+            assert_eq!(bus.read_io(0xDD) & 0x0F, 0x0D, "P2 right → bit 1 de $DD");
+        });
+    }
+
     #[test]
     fn set_input_does_not_panic() {
         with_large_stack(|| {
@@ -437,7 +795,7 @@ mod tests {
     #[test]
     fn gg_start_button_does_not_trigger_nmi() {
         with_large_stack(|| {
-            let mut emu = Emulator::new(nop_rom(), Platform::GameGear, 44100.0);
+            let mut emu = Emulator::new(nop_rom(), Platform::GameGear, VideoStandard::Ntsc, 44100.0);
             emu.set_input(false, false, false, false, false, false, true);
             assert_eq!(emu.cpu.nmi_pending, 0);
         });
@@ -516,4 +874,100 @@ mod tests {
             assert_eq!(emu.cpu.pc, 0x1234);
         });
     }
+
+    // ── set_game_info ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn game_info_sets_mapper_save_and_sprite_limit() {
+        with_large_stack(|| {
+            let mut emu = Emulator::new(nop_rom(), Platform::GameGear, VideoStandard::Ntsc, 44100.0);
+            let mut game = GameInfo::new(emu.rom_crc32(), "Teste");
+            game.mapper = Some(MapperKind::Korean);
+            game.save = Some(crate::gamedb::SaveMemory::Eeprom);
+            game.sprite_limit = Some(false);
+            game.fm = Some(false);
+            emu.set_game_info(game);
+            assert_eq!(emu.mapper(), MapperKind::Korean);
+            assert!(emu.has_eeprom());
+            assert!(!emu.cpu.io.bus.borrow().vdp.sprite_limit);
+            emu.set_fm_disabled(false);
+            assert!(emu.cpu.io.bus.borrow().mixer.fm.user_disabled, "fm=off vence a escolha do usuário");
+
+            emu.power_cycle();
+            assert_eq!(emu.mapper(), MapperKind::Korean, "power cycle mantém o mapper do banco de dados");
+            assert!(emu.has_eeprom());
+            assert_eq!(emu.game_info().map(|g| g.title.as_str()), Some("Teste"));
+        });
+    }
+
+    /// Acrescenta 4 bytes a `rom` para que o CRC32 da imagem seja `target`
+    /// (percorre a tabela do CRC de trás para frente).
+    fn rom_with_crc(mut rom: Vec<u8>, target: u32) -> Vec<u8> {
+        let table: Vec<u32> = (0..256u32).map(|i| (0..8).fold(i, |c, _| if c & 1 != 0 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 })).collect();
+        let mut r = !target;
+        for _ in 0..4 {
+            let j = (0..256).find(|&j| table[j] >> 24 == r >> 24).unwrap();
+            r = ((r ^ table[j]) << 8) | j as u32;
+        }
+        rom.extend_from_slice(&(r ^ !crate::mmu::crc32(&rom)).to_le_bytes());
+        assert_eq!(crate::mmu::crc32(&rom), target);
+        rom
+    }
+
+    #[test]
+    fn bundled_database_fits_eeprom_and_mapper_without_setup() {
+        with_large_stack(|| {
+            // Majors Pro Baseball (GG, EEPROM 93C46)
+            let emu = Emulator::new(rom_with_crc(vec![0; 0x7FFC], 0x36EB_CD6D), Platform::GameGear, VideoStandard::Ntsc, 44100.0);
+            assert!(emu.has_eeprom());
+            assert_eq!(emu.game_info().map(|g| g.title.as_str()), Some("Majors Pro Baseball"));
+
+            // 4 Pak All Action (mapper próprio)
+            let emu = Emulator::new(rom_with_crc(vec![0; 0x1FFFC], 0xA67F_2A5C), Platform::MasterSystem, VideoStandard::Ntsc, 44100.0);
+            assert_eq!(emu.mapper(), MapperKind::FourPak);
+            assert!(!emu.has_eeprom());
+
+            assert!(!make_emu().has_eeprom() && make_emu().game_info().is_none(), "ROM desconhecida");
+        });
+    }
+
+    #[test]
+    fn game_info_limits_cart_ram_size() {
+        with_large_stack(|| {
+            let mut emu = make_emu();
+            assert_eq!(emu.get_cart_ram().len(), 16384);
+            let mut game = GameInfo::new(0, "");
+            game.save = Some(crate::gamedb::SaveMemory::Sram(8192));
+            emu.set_game_info(game);
+            assert_eq!(emu.get_cart_ram().len(), 8192);
+            let mut bus = emu.cpu.io.bus.borrow_mut();
+            bus.mmu.write(0xFFFC, 0x08); // RAM em $8000
+            bus.mmu.write(0xA000, 0x5A);
+            assert_eq!(bus.mmu.read(0x8000), 0x5A, "8KB espelhados em $8000–$BFFF");
+        });
+    }
+
+    // ── set_bios ──────────────────────────────────────────────────────────────
+
+    #[test]
+    fn bios_hands_over_through_port_3e() {
+        with_large_stack(|| {
+            // Cartucho: JP $0004 em $0004
+            let mut rom = nop_rom();
+            rom[4..7].copy_from_slice(&[0xC3, 0x04, 0x00]);
+            let mut emu = Emulator::new(rom, Platform::MasterSystem, VideoStandard::Ntsc, 44100.0);
+            // BIOS: LD A,$AB; OUT ($3E),A — e o cartucho assume em $0004
+            let mut bios = vec![0u8; 0x2000];
+            bios[..4].copy_from_slice(&[0x3E, 0xAB, 0xD3, 0x3E]);
+            emu.set_bios(bios);
+            assert!(emu.has_bios());
+            assert_eq!(emu.peek(0x0000), 0x3E);
+            emu.step_frame();
+            assert!((4..7).contains(&emu.cpu.pc), "PC preso no laço do cartucho: {:04X}", emu.cpu.pc);
+
+            emu.power_cycle();
+            assert!(emu.has_bios(), "power cycle mantém a BIOS");
+            assert_eq!(emu.peek(0x0000), 0x3E, "e volta a ligar por ela");
+        });
+    }
 }
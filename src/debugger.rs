@@ -0,0 +1,356 @@
+//! Z80 debugger: PC breakpoints, memory/IO watchpoints and stepping.
+//!
+//! The frontend owns a [`Debugger`] and runs frames through
+//! [`Emulator::step_frame_debug`], which checks breakpoints before every
+//! instruction. Watchpoints are handed to the bus for the duration of the
+//! call, so every CPU access through `Bus::read`/`write`/`read_io`/`write_io`
+//! is checked; the instruction that triggered one completes, then execution
+//! stops. Opcode fetches go through `Bus::read` too and count as reads.
+
+use crate::Emulator;
+
+/// Stops when the CPU is about to execute `addr`. The Sega mapper swaps ROM
+/// banks in and out of each slot, so a breakpoint can be limited to one bank.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub addr: u16,
+    /// ROM bank that must be mapped at `addr`; `None` matches whatever is there.
+    pub bank: Option<u16>,
+}
+
+impl Breakpoint {
+    /// Parses `"4A20"` or `"bank:addr"` (`"3:8A20"`), hexadecimal, optional `$`.
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().split_once(':') {
+            Some((bank, addr)) => Some(Self { addr: parse_hex(addr)?, bank: Some(parse_hex(bank)?) }),
+            None => Some(Self { addr: parse_hex(text)?, bank: None }),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressSpace {
+    Memory,
+    Io,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// Stops after an instruction reads or writes an address (or I/O port) in `start..=end`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub space: AddressSpace,
+    pub start: u16,
+    pub end:   u16,
+    pub read:  bool,
+    pub write: bool,
+}
+
+impl Watchpoint {
+    /// Parses `"C000"` or a range `"C000-C0FF"`, hexadecimal, optional `$`.
+    pub fn parse(text: &str, space: AddressSpace, read: bool, write: bool) -> Option<Self> {
+        let (start, end) = match text.trim().split_once('-') {
+            Some((a, b)) => (parse_hex(a)?, parse_hex(b)?),
+            None => { let a = parse_hex(text)?; (a, a) }
+        };
+        if space == AddressSpace::Io && end > 0xFF { return None; }
+        Some(Self { space, start: start.min(end), end: start.max(end), read, write })
+    }
+
+    pub(crate) fn matches(&self, space: AddressSpace, access: Access, addr: u16) -> bool {
+        let wanted = match access { Access::Read => self.read, Access::Write => self.write };
+        wanted && self.space == space && (self.start..=self.end).contains(&addr)
+    }
+}
+
+fn parse_hex(text: &str) -> Option<u16> {
+    let t = text.trim();
+    let t = t.strip_prefix('$').or_else(|| t.strip_prefix("0x")).unwrap_or(t);
+    u16::from_str_radix(t, 16).ok()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WatchHit {
+    pub space:  AddressSpace,
+    pub access: Access,
+    pub addr:   u16,
+    pub value:  u8,
+    /// Address of the instruction that made the access.
+    pub pc:     u16,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepMode {
+    /// Execute one instruction.
+    Into,
+    /// Like `Into`, but runs CALL/RST and repeating block instructions to completion.
+    Over,
+    /// Run until the current subroutine returns.
+    Out,
+}
+
+/// Why [`Emulator::step_frame_debug`] stopped before the end of the frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugBreak {
+    Breakpoint { pc: u16, bank: Option<u16> },
+    Watchpoint(WatchHit),
+    Step,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Step {
+    Requested(StepMode),
+    Into,
+    /// Stop back at `pc` with the stack no deeper than `sp`.
+    Over { pc: u16, sp: u16 },
+    /// Stop after a return that pops the stack above `sp`.
+    Out { sp: u16 },
+}
+
+#[derive(Default)]
+pub struct Debugger {
+    pub breakpoints: Vec<Breakpoint>,
+    pub watchpoints: Vec<Watchpoint>,
+    pub last_break:  Option<DebugBreak>,
+    step: Option<Step>,
+    /// Where execution last stopped; a breakpoint there does not fire again on resume.
+    resume_pc: Option<u16>,
+    /// The instruction being executed is a RET/RETI/RETN (for step-out).
+    executing_ret: bool,
+}
+
+impl Debugger {
+    /// Whether frames need to run through [`Emulator::step_frame_debug`].
+    pub fn is_active(&self) -> bool {
+        !self.breakpoints.is_empty() || !self.watchpoints.is_empty() || self.step.is_some()
+    }
+
+    pub fn is_stepping(&self) -> bool {
+        self.step.is_some()
+    }
+
+    /// Starts a step; it completes during the next `step_frame_debug` call(s).
+    pub fn step(&mut self, mode: StepMode) {
+        self.step = Some(Step::Requested(mode));
+    }
+
+    pub fn cancel_step(&mut self) {
+        self.step = None;
+    }
+
+    /// Called before each instruction; returns a breakpoint hit.
+    pub(crate) fn before_instruction(&mut self, emu: &Emulator) -> Option<DebugBreak> {
+        let pc = emu.cpu.pc;
+        let resumed_here = self.resume_pc.take() == Some(pc);
+
+        if let Some(Step::Requested(mode)) = self.step {
+            self.step = Some(match mode {
+                StepMode::Into => Step::Into,
+                StepMode::Over => match instruction_to_skip(emu, pc) {
+                    Some(len) => Step::Over { pc: pc.wrapping_add(len), sp: emu.cpu.sp },
+                    None => Step::Into,
+                },
+                StepMode::Out => Step::Out { sp: emu.cpu.sp },
+            });
+        }
+        self.executing_ret = is_return(emu.peek(pc), emu.peek(pc.wrapping_add(1)));
+
+        // While halted the CPU sits on the next instruction without executing it.
+        if resumed_here || emu.cpu.halted { return None; }
+        let bank = emu.rom_bank_at(pc);
+        self.breakpoints.iter()
+            .any(|b| b.addr == pc && (b.bank.is_none() || b.bank == bank))
+            .then_some(DebugBreak::Breakpoint { pc, bank })
+    }
+
+    /// Called after each instruction that started at `pc`; returns a watchpoint
+    /// hit or a completed step.
+    pub(crate) fn after_instruction(&mut self, emu: &Emulator, pc: u16) -> Option<DebugBreak> {
+        let hit = emu.cpu.io.bus.borrow_mut().watch_hit.take();
+        if let Some(hit) = hit {
+            return Some(DebugBreak::Watchpoint(WatchHit { pc, ..hit }));
+        }
+        let done = match self.step? {
+            Step::Requested(_) => false,
+            Step::Into => true,
+            Step::Over { pc, sp } => emu.cpu.pc == pc && emu.cpu.sp >= sp,
+            Step::Out { sp } => self.executing_ret && emu.cpu.sp > sp,
+        };
+        done.then_some(DebugBreak::Step)
+    }
+
+    /// Records a stop at the current PC.
+    pub(crate) fn stopped(&mut self, emu: &Emulator, reason: DebugBreak) {
+        self.step = None;
+        self.resume_pc = Some(emu.cpu.pc);
+        self.last_break = Some(reason);
+    }
+}
+
+/// Length of a CALL, RST or repeating block instruction at `pc` — the ones
+/// step-over runs to completion.
+fn instruction_to_skip(emu: &Emulator, pc: u16) -> Option<u16> {
+    match emu.peek(pc) {
+        0xCD | 0xC4 | 0xCC | 0xD4 | 0xDC | 0xE4 | 0xEC | 0xF4 | 0xFC => Some(3),
+        op if op & 0xC7 == 0xC7 => Some(1),
+        0xED if matches!(emu.peek(pc.wrapping_add(1)), 0xB0..=0xB3 | 0xB8..=0xBB) => Some(2),
+        _ => None,
+    }
+}
+
+/// RET, RET cc, RETI and RETN (including the undocumented ED mirrors).
+fn is_return(op: u8, next: u8) -> bool {
+    op == 0xC9 || op & 0xC7 == 0xC0 || (op == 0xED && next & 0xC7 == 0x45)
+}
+
+/// Snapshot of the Z80 registers for display.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Registers {
+    pub af: u16, pub bc: u16, pub de: u16, pub hl: u16,
+    pub af_alt: u16, pub bc_alt: u16, pub de_alt: u16, pub hl_alt: u16,
+    pub ix: u16, pub iy: u16, pub sp: u16, pub pc: u16,
+    pub i: u8, pub r: u8,
+    pub iff1: bool, pub iff2: bool,
+    pub interrupt_mode: u8,
+    pub halted: bool,
+}
+
+impl Registers {
+    /// The F register as `SZYHXPNC`, cleared flags shown as `-`.
+    pub fn flags(&self) -> String {
+        "SZYHXPNC".chars().enumerate()
+            .map(|(i, c)| if self.af & (0x80 >> i) != 0 { c } else { '-' })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Platform, VideoStandard};
+    use crate::test_util::with_large_stack;
+
+    /// 0000 DI / LD SP,$DFF0 / 0004 CALL $0010 / 0007 LD ($C000),A / 000A JR $0004
+    /// 0010 INC A / 0011 OUT ($BE),A / 0013 RET
+    fn test_emu() -> Emulator {
+        let mut rom = vec![0u8; 0xC000];
+        rom[..0x0C].copy_from_slice(&[
+            0xF3, 0x31, 0xF0, 0xDF, 0xCD, 0x10, 0x00, 0x32, 0x00, 0xC0, 0x18, 0xF8,
+        ]);
+        rom[0x10..0x14].copy_from_slice(&[0x3C, 0xD3, 0xBE, 0xC9]);
+        Emulator::new(rom, Platform::MasterSystem, VideoStandard::Ntsc, 44100.0)
+    }
+
+    fn run_until_break(emu: &mut Emulator, dbg: &mut Debugger) -> Option<DebugBreak> {
+        (0..3).find_map(|_| emu.step_frame_debug(dbg).2)
+    }
+
+    #[test]
+    fn parses_breakpoints_and_watchpoints() {
+        assert_eq!(Breakpoint::parse("$4A20"), Some(Breakpoint { addr: 0x4A20, bank: None }));
+        assert_eq!(Breakpoint::parse("3:8a20"), Some(Breakpoint { addr: 0x8A20, bank: Some(3) }));
+        assert_eq!(Breakpoint::parse("xyz"), None);
+        let w = Watchpoint::parse("C0FF-C000", AddressSpace::Memory, false, true).unwrap();
+        assert_eq!((w.start, w.end), (0xC000, 0xC0FF));
+        assert!(Watchpoint::parse("100", AddressSpace::Io, true, true).is_none(), "porta > $FF");
+    }
+
+    #[test]
+    fn flags_string_shows_set_bits() {
+        let regs = Registers { af: 0x00C1, ..Default::default() };
+        assert_eq!(regs.flags(), "SZ-----C");
+    }
+
+    #[test]
+    fn breakpoint_stops_before_instruction_and_resume_skips_it() {
+        with_large_stack(|| {
+            let mut emu = test_emu();
+            let mut dbg = Debugger { breakpoints: vec![Breakpoint { addr: 0x0010, bank: None }], ..Default::default() };
+            assert_eq!(run_until_break(&mut emu, &mut dbg), Some(DebugBreak::Breakpoint { pc: 0x0010, bank: Some(0) }));
+            assert_eq!(emu.registers().pc, 0x0010);
+            let a = (emu.registers().af >> 8) as u8;
+            // Retomar não dispara de novo no mesmo PC; para na próxima volta do laço
+            assert!(matches!(run_until_break(&mut emu, &mut dbg), Some(DebugBreak::Breakpoint { .. })));
+            assert_eq!((emu.registers().af >> 8) as u8, a.wrapping_add(1));
+        });
+    }
+
+    #[test]
+    fn breakpoint_in_other_bank_does_not_fire() {
+        with_large_stack(|| {
+            let mut emu = test_emu();
+            let mut dbg = Debugger { breakpoints: vec![Breakpoint { addr: 0x0010, bank: Some(1) }], ..Default::default() };
+            assert_eq!(emu.step_frame_debug(&mut dbg).2, None);
+        });
+    }
+
+    #[test]
+    fn memory_write_watchpoint_reports_value_and_pc() {
+        with_large_stack(|| {
+            let mut emu = test_emu();
+            let mut dbg = Debugger::default();
+            dbg.watchpoints.push(Watchpoint::parse("C000", AddressSpace::Memory, false, true).unwrap());
+            let Some(DebugBreak::Watchpoint(hit)) = run_until_break(&mut emu, &mut dbg) else { panic!("sem parada") };
+            assert_eq!((hit.access, hit.addr, hit.pc), (Access::Write, 0xC000, 0x0007));
+            assert_eq!(hit.value as u16, emu.registers().af >> 8);
+            assert_eq!(emu.registers().pc, 0x000A, "para depois da instrução");
+            assert!(!dbg.watchpoints.is_empty(), "watchpoints devolvidos ao depurador");
+        });
+    }
+
+    #[test]
+    fn io_watchpoint_catches_port_write() {
+        with_large_stack(|| {
+            let mut emu = test_emu();
+            let mut dbg = Debugger::default();
+            dbg.watchpoints.push(Watchpoint::parse("BE", AddressSpace::Io, false, true).unwrap());
+            let Some(DebugBreak::Watchpoint(hit)) = run_until_break(&mut emu, &mut dbg) else { panic!("sem parada") };
+            assert_eq!((hit.space, hit.addr, hit.pc), (AddressSpace::Io, 0xBE, 0x0011));
+        });
+    }
+
+    fn stopped_at_call() -> (Emulator, Debugger) {
+        let mut emu = test_emu();
+        let mut dbg = Debugger { breakpoints: vec![Breakpoint { addr: 0x0004, bank: None }], ..Default::default() };
+        run_until_break(&mut emu, &mut dbg).unwrap();
+        dbg.breakpoints.clear();
+        (emu, dbg)
+    }
+
+    #[test]
+    fn step_into_enters_call() {
+        with_large_stack(|| {
+            let (mut emu, mut dbg) = stopped_at_call();
+            dbg.step(StepMode::Into);
+            assert_eq!(run_until_break(&mut emu, &mut dbg), Some(DebugBreak::Step));
+            assert_eq!(emu.registers().pc, 0x0010);
+            assert!(!dbg.is_stepping());
+        });
+    }
+
+    #[test]
+    fn step_over_runs_call_to_completion() {
+        with_large_stack(|| {
+            let (mut emu, mut dbg) = stopped_at_call();
+            dbg.step(StepMode::Over);
+            assert_eq!(run_until_break(&mut emu, &mut dbg), Some(DebugBreak::Step));
+            assert_eq!(emu.registers().pc, 0x0007);
+        });
+    }
+
+    #[test]
+    fn step_out_returns_to_caller() {
+        with_large_stack(|| {
+            let (mut emu, mut dbg) = stopped_at_call();
+            dbg.step(StepMode::Into);
+            run_until_break(&mut emu, &mut dbg);
+            dbg.step(StepMode::Out);
+            assert_eq!(run_until_break(&mut emu, &mut dbg), Some(DebugBreak::Step));
+            assert_eq!(emu.registers().pc, 0x0007);
+        });
+    }
+}
@@ -0,0 +1,431 @@
+//! Z80 disassembler.
+//!
+//! Decodes every opcode, including the CB/DD/ED/FD/DDCB/FDCB prefixes and the
+//! undocumented forms (IXH/IXL, SLL, `IN (C)`, `OUT (C),0`, DDCB results copied
+//! to a register, NEG/RETN/IM mirrors). Bytes are fetched through
+//! `Bus::peek`, so disassembling never disturbs the VDP, EEPROM or watchpoints.
+//! SMS I/O ports are shown by name and branch targets in mapper slots get the
+//! bank they currently resolve to.
+
+use std::fmt;
+
+use crate::bus::Bus;
+
+const R:   [&str; 8] = ["B", "C", "D", "E", "H", "L", "(HL)", "A"];
+const RP:  [&str; 4] = ["BC", "DE", "HL", "SP"];
+const RP2: [&str; 4] = ["BC", "DE", "HL", "AF"];
+const CC:  [&str; 8] = ["NZ", "Z", "NC", "C", "PO", "PE", "P", "M"];
+const ALU: [&str; 8] = ["ADD A,", "ADC A,", "SUB ", "SBC A,", "AND ", "XOR ", "OR ", "CP "];
+const ROT: [&str; 8] = ["RLC", "RRC", "RL", "RR", "SLA", "SRA", "SLL", "SRL"];
+const IM:  [&str; 8] = ["0", "0/1", "1", "2", "0", "0/1", "1", "2"];
+const BLOCK: [[&str; 4]; 4] = [
+    ["LDI",  "CPI",  "INI",  "OUTI"],
+    ["LDD",  "CPD",  "IND",  "OUTD"],
+    ["LDIR", "CPIR", "INIR", "OTIR"],
+    ["LDDR", "CPDR", "INDR", "OTDR"],
+];
+
+/// Symbolic name of an SMS/GG I/O port, as seen by an `IN` (`write = false`) or `OUT`.
+pub fn port_name(port: u8, write: bool) -> Option<&'static str> {
+    Some(match (port, write) {
+        (0xBE, _)     => "VDP_DATA",
+        (0xBF, _)     => "VDP_CTRL",
+        (0x7E, false) => "VCOUNT",
+        (0x7F, false) => "HCOUNT",
+        (0x7F, true)  => "PSG",
+        (0xDC, _)     => "JOY1",
+        (0xDD, _)     => "JOY2",
+        (0xF0, _)     => "FM_ADDR",
+        (0xF1, _)     => "FM_DATA",
+        (0xF2, _)     => "FM_CTRL",
+        _ => return None,
+    })
+}
+
+/// One decoded instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub addr:  u16,
+    /// ROM bank mapped at `addr`, `None` in RAM.
+    pub bank:  Option<u16>,
+    pub bytes: Vec<u8>,
+    pub text:  String,
+}
+
+impl Instruction {
+    pub fn len(&self) -> u16 {
+        self.bytes.len() as u16
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Address of the following instruction.
+    pub fn next_addr(&self) -> u16 {
+        self.addr.wrapping_add(self.len())
+    }
+}
+
+/// `03:8A20  CD 10 00     CALL $0010`
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bank = self.bank.map(|b| format!("{b:02X}")).unwrap_or_else(|| "--".into());
+        let bytes: Vec<String> = self.bytes.iter().map(|b| format!("{b:02X}")).collect();
+        write!(f, "{bank}:{:04X}  {:<12} {}", self.addr, bytes.join(" "), self.text)
+    }
+}
+
+/// Disassembles the instruction at `addr` as the CPU currently sees memory.
+pub(crate) fn disassemble(bus: &Bus, addr: u16) -> Instruction {
+    let mut d = Decoder { bus, pc: addr, index: None };
+    let text = d.instruction();
+    let bytes = (0..d.pc.wrapping_sub(addr)).map(|i| bus.peek(addr.wrapping_add(i))).collect();
+    Instruction { addr, bank: bus.mmu.rom_bank_at(addr), bytes, text }
+}
+
+struct Decoder<'a> {
+    bus:   &'a Bus,
+    /// Next byte to fetch.
+    pc:    u16,
+    /// `"IX"`/`"IY"` after a DD/FD prefix.
+    index: Option<&'static str>,
+}
+
+impl Decoder<'_> {
+    fn byte(&mut self) -> u8 {
+        let b = self.bus.peek(self.pc);
+        self.pc = self.pc.wrapping_add(1);
+        b
+    }
+
+    fn word(&mut self) -> u16 {
+        let lo = self.byte() as u16;
+        lo | (self.byte() as u16) << 8
+    }
+
+    fn n(&mut self) -> String {
+        format!("${:02X}", self.byte())
+    }
+
+    fn nn(&mut self) -> String {
+        format!("${:04X}", self.word())
+    }
+
+    /// HL, or the index register after a prefix.
+    fn hl(&self) -> &'static str {
+        self.index.unwrap_or("HL")
+    }
+
+    fn displacement(index: &str, d: i8) -> String {
+        let sign = if d < 0 { '-' } else { '+' };
+        format!("({index}{sign}${:02X})", d.unsigned_abs())
+    }
+
+    /// `r[i]` with index substitution: (HL) → (IX+d), H/L → IXH/IXL unless
+    /// the same instruction also addresses memory (`plain_hl`).
+    fn reg(&mut self, i: u8, plain_hl: bool) -> String {
+        match (i, self.index) {
+            (6, Some(ix)) => { let d = self.byte() as i8; Self::displacement(ix, d) }
+            (4, Some(ix)) if !plain_hl => format!("{ix}H"),
+            (5, Some(ix)) if !plain_hl => format!("{ix}L"),
+            _ => R[i as usize].to_string(),
+        }
+    }
+
+    fn rp(&self, p: u8) -> &'static str {
+        if p == 2 { self.hl() } else { RP[p as usize] }
+    }
+
+    fn rp2(&self, p: u8) -> &'static str {
+        if p == 2 { self.hl() } else { RP2[p as usize] }
+    }
+
+    /// Branch target, annotated with its bank when it lies in a mapper slot.
+    fn target(&self, addr: u16) -> String {
+        match self.bus.mmu.rom_bank_at(addr) {
+            Some(bank) if addr >= 0x0400 => format!("${addr:04X} ; {bank:02X}:{addr:04X}"),
+            _ => format!("${addr:04X}"),
+        }
+    }
+
+    fn relative(&mut self) -> String {
+        let d = self.byte() as i8;
+        self.target(self.pc.wrapping_add(d as u16))
+    }
+
+    fn port(&mut self, write: bool) -> String {
+        let p = self.byte();
+        port_name(p, write).map(str::to_string).unwrap_or_else(|| format!("${p:02X}"))
+    }
+
+    fn instruction(&mut self) -> String {
+        let op = self.byte();
+        match op {
+            0xCB => self.cb(),
+            0xED => self.ed(),
+            0xDD | 0xFD => {
+                // A prefix followed by another prefix (or ED) acts as a lone NOP.
+                if matches!(self.bus.peek(self.pc), 0xDD | 0xED | 0xFD) {
+                    return "NOP*".into();
+                }
+                self.index = Some(if op == 0xDD { "IX" } else { "IY" });
+                match self.byte() {
+                    0xCB => self.index_cb(),
+                    op => self.main(op),
+                }
+            }
+            _ => self.main(op),
+        }
+    }
+
+    fn main(&mut self, op: u8) -> String {
+        let (x, y, z) = (op >> 6, (op >> 3) & 7, op & 7);
+        let (p, q) = (y >> 1, y & 1);
+        match (x, z) {
+            (0, 0) => match y {
+                0 => "NOP".into(),
+                1 => "EX AF,AF'".into(),
+                2 => format!("DJNZ {}", self.relative()),
+                3 => format!("JR {}", self.relative()),
+                _ => format!("JR {},{}", CC[y as usize - 4], self.relative()),
+            },
+            (0, 1) if q == 0 => format!("LD {},{}", self.rp(p), self.nn()),
+            (0, 1) => format!("ADD {},{}", self.hl(), self.rp(p)),
+            (0, 2) => match (q, p) {
+                (0, 0) => "LD (BC),A".into(),
+                (0, 1) => "LD (DE),A".into(),
+                (0, 2) => format!("LD ({}),{}", self.nn(), self.hl()),
+                (0, _) => format!("LD ({}),A", self.nn()),
+                (_, 0) => "LD A,(BC)".into(),
+                (_, 1) => "LD A,(DE)".into(),
+                (_, 2) => format!("LD {},({})", self.hl(), self.nn()),
+                _ => format!("LD A,({})", self.nn()),
+            },
+            (0, 3) => format!("{} {}", if q == 0 { "INC" } else { "DEC" }, self.rp(p)),
+            (0, 4) => format!("INC {}", self.reg(y, false)),
+            (0, 5) => format!("DEC {}", self.reg(y, false)),
+            (0, 6) => { let r = self.reg(y, false); format!("LD {r},{}", self.n()) }
+            (0, _) => ["RLCA", "RRCA", "RLA", "RRA", "DAA", "CPL", "SCF", "CCF"][y as usize].into(),
+            (1, 6) if y == 6 => "HALT".into(),
+            (1, _) => {
+                let mem = y == 6 || z == 6;
+                let dst = self.reg(y, mem);
+                format!("LD {dst},{}", self.reg(z, mem))
+            }
+            (2, _) => format!("{}{}", ALU[y as usize], self.reg(z, false)),
+            (_, 0) => format!("RET {}", CC[y as usize]),
+            (_, 1) => match (q, p) {
+                (0, _) => format!("POP {}", self.rp2(p)),
+                (_, 0) => "RET".into(),
+                (_, 1) => "EXX".into(),
+                (_, 2) => format!("JP ({})", self.hl()),
+                _ => format!("LD SP,{}", self.hl()),
+            },
+            (_, 2) => { let t = self.word(); format!("JP {},{}", CC[y as usize], self.target(t)) }
+            (_, 3) => match y {
+                0 => { let t = self.word(); format!("JP {}", self.target(t)) }
+                1 => self.cb(), // unreachable: handled as a prefix
+                2 => format!("OUT ({}),A", self.port(true)),
+                3 => format!("IN A,({})", self.port(false)),
+                4 => format!("EX (SP),{}", self.hl()),
+                5 => "EX DE,HL".into(),
+                6 => "DI".into(),
+                _ => "EI".into(),
+            },
+            (_, 4) => { let t = self.word(); format!("CALL {},{}", CC[y as usize], self.target(t)) }
+            (_, 5) if q == 0 => format!("PUSH {}", self.rp2(p)),
+            (_, 5) => { let t = self.word(); format!("CALL {}", self.target(t)) }
+            (_, 6) => format!("{}{}", ALU[y as usize], self.n()),
+            _ => format!("RST ${:02X}", y * 8),
+        }
+    }
+
+    fn cb(&mut self) -> String {
+        let op = self.byte();
+        let (x, y, z) = (op >> 6, (op >> 3) & 7, (op & 7) as usize);
+        match x {
+            0 => format!("{} {}", ROT[y as usize], R[z]),
+            1 => format!("BIT {y},{}", R[z]),
+            2 => format!("RES {y},{}", R[z]),
+            _ => format!("SET {y},{}", R[z]),
+        }
+    }
+
+    /// DDCB/FDCB: displacement first, then the opcode. Except for BIT, a
+    /// register operand other than (HL) also receives the result (undocumented).
+    fn index_cb(&mut self) -> String {
+        let d = self.byte() as i8;
+        let op = self.byte();
+        let mem = Self::displacement(self.index.unwrap_or("IX"), d);
+        let (x, y, z) = (op >> 6, (op >> 3) & 7, (op & 7) as usize);
+        let copy = if z == 6 { String::new() } else { format!(",{}", R[z]) };
+        match x {
+            0 => format!("{} {mem}{copy}", ROT[y as usize]),
+            1 => format!("BIT {y},{mem}"),
+            2 => format!("RES {y},{mem}{copy}"),
+            _ => format!("SET {y},{mem}{copy}"),
+        }
+    }
+
+    fn ed(&mut self) -> String {
+        let op = self.byte();
+        let (x, y, z) = (op >> 6, (op >> 3) & 7, op & 7);
+        let (p, q) = (y >> 1, y & 1);
+        match (x, z) {
+            (1, 0) if y == 6 => "IN (C)".into(),
+            (1, 0) => format!("IN {},(C)", R[y as usize]),
+            (1, 1) if y == 6 => "OUT (C),0".into(),
+            (1, 1) => format!("OUT (C),{}", R[y as usize]),
+            (1, 2) => format!("{} HL,{}", if q == 0 { "SBC" } else { "ADC" }, RP[p as usize]),
+            (1, 3) if q == 0 => format!("LD ({}),{}", self.nn(), RP[p as usize]),
+            (1, 3) => format!("LD {},({})", RP[p as usize], self.nn()),
+            (1, 4) => "NEG".into(),
+            (1, 5) => if y == 1 { "RETI".into() } else { "RETN".into() },
+            (1, 6) => format!("IM {}", IM[y as usize]),
+            (1, _) => ["LD I,A", "LD R,A", "LD A,I", "LD A,R", "RRD", "RLD", "NOP*", "NOP*"][y as usize].into(),
+            (2, 0..=3) if y >= 4 => BLOCK[y as usize - 4][z as usize].into(),
+            _ => "NOP*".into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::{Platform, VideoStandard};
+
+    fn bus_with(code: &[u8], at: usize) -> Bus {
+        let mut rom = vec![0u8; 0x20000];
+        rom[at..at + code.len()].copy_from_slice(code);
+        Bus::new(rom, Platform::MasterSystem, VideoStandard::Ntsc, 44100)
+    }
+
+    fn dis(code: &[u8]) -> (String, u16) {
+        let i = disassemble(&bus_with(code, 0x100), 0x100);
+        (i.text.clone(), i.len())
+    }
+
+    #[test]
+    fn unprefixed_instructions() {
+        assert_eq!(dis(&[0x00]), ("NOP".into(), 1));
+        assert_eq!(dis(&[0x3E, 0x12]), ("LD A,$12".into(), 2));
+        assert_eq!(dis(&[0x21, 0x34, 0x12]), ("LD HL,$1234".into(), 3));
+        assert_eq!(dis(&[0x32, 0x00, 0xC0]), ("LD ($C000),A".into(), 3));
+        assert_eq!(dis(&[0x76]), ("HALT".into(), 1));
+        assert_eq!(dis(&[0x78]), ("LD A,B".into(), 1));
+        assert_eq!(dis(&[0xFE, 0x10]), ("CP $10".into(), 2));
+        assert_eq!(dis(&[0xF5]), ("PUSH AF".into(), 1));
+        assert_eq!(dis(&[0xFF]), ("RST $38".into(), 1));
+        assert_eq!(dis(&[0x08]), ("EX AF,AF'".into(), 1));
+    }
+
+    #[test]
+    fn relative_jumps_resolve_target() {
+        assert_eq!(dis(&[0x18, 0xFE]), ("JR $0100".into(), 2));
+        assert_eq!(dis(&[0x10, 0x02]), ("DJNZ $0104".into(), 2));
+        assert_eq!(dis(&[0x38, 0x00]), ("JR C,$0102".into(), 2));
+    }
+
+    #[test]
+    fn branch_targets_in_mapper_slots_show_bank() {
+        let mut bus = bus_with(&[0xCD, 0x10, 0x40], 0x100);
+        bus.mmu.write(0xFFFE, 5);
+        assert_eq!(disassemble(&bus, 0x100).text, "CALL $4010 ; 05:4010");
+        let ram = bus_with(&[0xC3, 0x00, 0xC0], 0x100);
+        assert_eq!(disassemble(&ram, 0x100).text, "JP $C000", "RAM não tem banco");
+    }
+
+    #[test]
+    fn sms_ports_are_symbolic() {
+        assert_eq!(dis(&[0xD3, 0xBE]).0, "OUT (VDP_DATA),A");
+        assert_eq!(dis(&[0xD3, 0xBF]).0, "OUT (VDP_CTRL),A");
+        assert_eq!(dis(&[0xD3, 0x7F]).0, "OUT (PSG),A");
+        assert_eq!(dis(&[0xDB, 0xDC]).0, "IN A,(JOY1)");
+        assert_eq!(dis(&[0xDB, 0xDD]).0, "IN A,(JOY2)");
+        assert_eq!(dis(&[0xD3, 0xF2]).0, "OUT (FM_CTRL),A");
+        assert_eq!(dis(&[0xD3, 0x3E]).0, "OUT ($3E),A");
+    }
+
+    #[test]
+    fn cb_prefix_including_sll() {
+        assert_eq!(dis(&[0xCB, 0x00]), ("RLC B".into(), 2));
+        assert_eq!(dis(&[0xCB, 0x37]), ("SLL A".into(), 2));
+        assert_eq!(dis(&[0xCB, 0x7E]), ("BIT 7,(HL)".into(), 2));
+        assert_eq!(dis(&[0xCB, 0xC1]), ("SET 0,C".into(), 2));
+    }
+
+    #[test]
+    fn index_prefixes() {
+        assert_eq!(dis(&[0xDD, 0x7E, 0x05]), ("LD A,(IX+$05)".into(), 3));
+        assert_eq!(dis(&[0xFD, 0x36, 0xFE, 0x42]), ("LD (IY-$02),$42".into(), 4));
+        assert_eq!(dis(&[0xDD, 0x21, 0x00, 0xC0]), ("LD IX,$C000".into(), 4));
+        assert_eq!(dis(&[0xDD, 0xE9]), ("JP (IX)".into(), 2));
+        assert_eq!(dis(&[0xDD, 0x66, 0x01]), ("LD H,(IX+$01)".into(), 3), "H não vira IXH com (IX+d)");
+        assert_eq!(dis(&[0xFD, 0x09]), ("ADD IY,BC".into(), 2));
+        assert_eq!(dis(&[0xDD, 0xEB]), ("EX DE,HL".into(), 2));
+    }
+
+    #[test]
+    fn undocumented_index_halves() {
+        assert_eq!(dis(&[0xDD, 0x26, 0x10]), ("LD IXH,$10".into(), 3));
+        assert_eq!(dis(&[0xFD, 0x6C]), ("LD IYL,IYH".into(), 2));
+        assert_eq!(dis(&[0xDD, 0x84]), ("ADD A,IXH".into(), 2));
+    }
+
+    #[test]
+    fn index_cb_forms() {
+        assert_eq!(dis(&[0xDD, 0xCB, 0x03, 0x06]), ("RLC (IX+$03)".into(), 4));
+        assert_eq!(dis(&[0xFD, 0xCB, 0xFF, 0x80]), ("RES 0,(IY-$01),B".into(), 4));
+        assert_eq!(dis(&[0xDD, 0xCB, 0x00, 0x46]), ("BIT 0,(IX+$00)".into(), 4));
+        assert_eq!(dis(&[0xDD, 0xCB, 0x00, 0x41]), ("BIT 0,(IX+$00)".into(), 4));
+    }
+
+    #[test]
+    fn ed_prefix_including_undocumented() {
+        assert_eq!(dis(&[0xED, 0xB0]), ("LDIR".into(), 2));
+        assert_eq!(dis(&[0xED, 0xB3]), ("OTIR".into(), 2));
+        assert_eq!(dis(&[0xED, 0x70]), ("IN (C)".into(), 2));
+        assert_eq!(dis(&[0xED, 0x71]), ("OUT (C),0".into(), 2));
+        assert_eq!(dis(&[0xED, 0x54]), ("NEG".into(), 2));
+        assert_eq!(dis(&[0xED, 0x4D]), ("RETI".into(), 2));
+        assert_eq!(dis(&[0xED, 0x55]), ("RETN".into(), 2));
+        assert_eq!(dis(&[0xED, 0x56]), ("IM 1".into(), 2));
+        assert_eq!(dis(&[0xED, 0x43, 0x00, 0xC0]), ("LD ($C000),BC".into(), 4));
+        assert_eq!(dis(&[0xED, 0x00]), ("NOP*".into(), 2));
+    }
+
+    #[test]
+    fn chained_prefix_is_a_lone_nop() {
+        assert_eq!(dis(&[0xDD, 0xFD, 0x21, 0, 0]), ("NOP*".into(), 1));
+    }
+
+    #[test]
+    fn every_opcode_decodes_within_four_bytes() {
+        // Um só barramento; só os bytes em $0100 mudam a cada opcode.
+        let mut bus = bus_with(&[], 0x100);
+        for a in 0..=255u8 {
+            for b in [0x00, 0xCB, 0x36, 0x7E, 0xB0, 0xFF] {
+                bus.mmu.rom[0x100..0x104].copy_from_slice(&[a, b, 0x12, 0x34]);
+                let i = disassemble(&bus, 0x100);
+                assert!((1..=4).contains(&i.len()) && !i.text.is_empty(), "{a:02X} {b:02X}");
+            }
+        }
+    }
+
+    #[test]
+    fn display_shows_bank_bytes_and_text() {
+        let i = disassemble(&bus_with(&[0x3E, 0x12], 0x100), 0x100);
+        assert_eq!(i.to_string(), "00:0100  3E 12        LD A,$12");
+    }
+
+    #[test]
+    fn disassembling_does_not_trigger_watchpoints() {
+        let mut bus = bus_with(&[0x00], 0x100);
+        bus.watchpoints.push(crate::debugger::Watchpoint {
+            space: crate::debugger::AddressSpace::Memory, start: 0, end: 0xFFFF, read: true, write: true,
+        });
+        disassemble(&bus, 0x100);
+        assert_eq!(bus.watch_hit, None);
+    }
+}
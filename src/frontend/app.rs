@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 use std::num::NonZeroU32;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use std::time::Instant;
 use std::path::{Path, PathBuf};
 
@@ -18,20 +18,48 @@ use winit::keyboard::{KeyCode, PhysicalKey};
 use winit::window::{Window, WindowAttributes, WindowId};
 use winit::raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 
-use cpal::Stream;
 use gilrs::{Button, Event as GilrsEvent, Gilrs};
 
+use crate::cheats::{load_cheats, save_cheats};
 use crate::core::Emulator;
-use crate::platform::{Platform, SMS_W, SMS_H, GG_W, GG_H};
+use crate::debugger::Debugger;
+use crate::gamedb::GameDb;
+use crate::gdb::GdbStub;
+use crate::movie::{FrameInput, Movie, MoviePlayer, MovieRecorder};
+use crate::patch::{apply_patch, find_patch};
+use crate::rewind::RewindBuffer;
+use crate::trace::Tracer;
+use crate::platform::{ConsoleRegion, MediaSlot, Platform, VideoStandard, SMS_W, SMS_H, GG_W, GG_H};
+use crate::frontend::audio::AudioOutput;
+use crate::frontend::debug_ui::DebugView;
+use crate::frontend::cheat_ui::CheatView;
+use crate::frontend::memory_ui::MemoryView;
+use crate::frontend::rom_info_ui::RomInfoView;
+use crate::frontend::ram_search_ui::RamSearchView;
+use crate::frontend::vdp_ui::VdpView;
 use crate::frontend::egui_ui::{DialogState, EguiState};
 use crate::frontend::input::{KeyConfig, PadState};
 use crate::frontend::menu::{AppMenu, MenuAction};
 use crate::frontend::renderer::Renderer;
-
-const SMS_FRAME_US: i64 = 16_683;
+use crate::frontend::speed::SpeedControl;
 
 fn sram_path(p: &Path) -> PathBuf { p.with_extension("sav") }
 fn eeprom_path(p: &Path) -> PathBuf { p.with_extension("eep") }
+fn cheats_path(p: &Path) -> PathBuf { p.with_extension("cht") }
+
+/// Path the save files of a game are named after: the ROM itself, or for a
+/// patched game the patch plus the ROM's extension (`game.ips.sms`), so the
+/// patched and original games keep separate saves.
+fn save_base(rom: &Path, patch: Option<&Path>) -> PathBuf {
+    match patch {
+        None => rom.to_path_buf(),
+        Some(p) => {
+            let name = p.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+            let ext  = rom.extension().and_then(|e| e.to_str()).unwrap_or("sms");
+            p.with_file_name(format!("{name}.{ext}"))
+        }
+    }
+}
 
 fn save_sram(emu: &Emulator, rom_path: &Path) {
     let data = emu.get_cart_ram();
@@ -76,6 +104,15 @@ fn save_state_to_slot(emu: &Emulator, rom_path: &Path, slot: usize) {
     }
 }
 
+/// Memory budget for rewind deltas (roughly a few minutes of gameplay).
+const REWIND_BUDGET: usize = 32 * 1024 * 1024;
+
+fn movie_path(rom_path: &Path) -> PathBuf {
+    let stem = rom_path.file_stem().and_then(|s| s.to_str()).unwrap_or("game");
+    let ext  = rom_path.extension().and_then(|s| s.to_str()).unwrap_or("sms");
+    rom_path.with_file_name(format!("{}.{}.vsm", stem, ext))
+}
+
 fn load_state_from_slot(emu: &mut Emulator, rom_path: &Path, slot: usize) {
     let path = savestate_path(rom_path, slot);
     match std::fs::read(&path) {
@@ -87,27 +124,30 @@ fn load_state_from_slot(emu: &mut Emulator, rom_path: &Path, slot: usize) {
     }
 }
 
-pub fn load_rom(path: &Path, sample_rate: f32, fm_disabled: bool) -> Option<Emulator> {
-    match std::fs::read(path) {
-        Ok(data) => {
-            let platform = match path.extension()
-                .and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref()
-            {
-                Some("gg") => Platform::GameGear,
-                Some("sg") => Platform::Sg1000,
-                Some("sc") => Platform::Sc3000,
-                _          => Platform::MasterSystem,
-            };
-            let emu = Emulator::new(data, platform, sample_rate);
-            emu.set_fm_disabled(platform != Platform::MasterSystem || fm_disabled);
-            load_sram_into(&emu, path);
-            load_eeprom_into(&emu, path);
-            println!("Loaded ROM: {} ({:?})",
-                path.file_stem().and_then(|n| n.to_str()).unwrap_or("?"), platform);
-            Some(emu)
-        }
-        Err(e) => { eprintln!("Failed to load ROM: {e}"); None }
+fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe().ok()?.parent().map(Path::to_path_buf)
+}
+
+/// Bundled game database plus `gamedb.txt` next to the executable, if present.
+fn load_game_db() -> GameDb {
+    let mut db = GameDb::builtin();
+    if let Some(path) = exe_dir().map(|d| d.join("gamedb.txt")).filter(|p| p.exists()) {
+        match GameDb::load(&path) {
+            Ok(extra) => db.extend(extra),
+            Err(e) => eprintln!("Failed to load game database: {e}"),
+        }
     }
+    db
+}
+
+/// Console boot ROM next to the executable: `bios.sms` or `bios.gg`.
+fn bios_path(platform: Platform) -> Option<PathBuf> {
+    let ext = match platform {
+        Platform::MasterSystem => "sms",
+        Platform::GameGear => "gg",
+        _ => return None,
+    };
+    exe_dir().map(|d| d.join("bios").with_extension(ext))
 }
 
 struct GlState {
@@ -186,9 +226,7 @@ fn init_gl(window: &Window) -> GlState {
 pub struct VibeApp {
     // Pre-init
     initial_rom: Option<String>,
-    audio_buf:   Arc<Mutex<Vec<f32>>>,
-    sample_rate: f32,
-    _stream:     Option<Stream>,
+    audio:       AudioOutput,
     gilrs:       Gilrs,
     menu:        AppMenu,
     proxy:       EventLoopProxy<MenuAction>,
@@ -201,12 +239,31 @@ pub struct VibeApp {
 
     // Emulation
     emu:             Option<Emulator>,
+    gamedb:          GameDb,
+    /// `--trace` log while no ROM is running; moves into each loaded machine.
+    tracer:          Option<Box<Tracer>>,
+    /// `--gdb` remote stub; while a client is attached it owns pause/resume.
+    gdb:             Option<GdbStub>,
     rom_path:        Option<PathBuf>,
+    /// Slot `rom_path` was opened into, kept for Reset.
+    rom_media:       MediaSlot,
+    /// `--patch` given for `rom_path`, kept for Reset; otherwise a
+    /// same-named patch beside the ROM is picked up on every load.
+    rom_patch:       Option<PathBuf>,
+    /// What save files, states, movies and cheats are named after; see `save_base`.
+    save_path:       Option<PathBuf>,
     fb:              Vec<u32>,
     pad:             PadState,
     pressed_keys:    HashSet<KeyCode>,
     mx: u16, my: u16,
     trigger_frames:  u8,
+    /// A Reset requested while recording, applied as part of the next recorded frame.
+    pending_reset:   bool,
+    recorder:        Option<MovieRecorder>,
+    player:          Option<MoviePlayer>,
+    rewind:          RewindBuffer,
+    /// Audio of the last emulated frame, repeated during slow motion.
+    last_audio:      Vec<f32>,
     last_frame:      Instant,
     time_debt_us:    i64,
     sram_save_timer: u32,
@@ -216,20 +273,23 @@ pub struct VibeApp {
 }
 
 impl VibeApp {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         initial_rom: Option<String>,
-        audio_buf: Arc<Mutex<Vec<f32>>>,
-        sample_rate: f32,
-        stream: Option<Stream>,
+        tracer: Option<Tracer>,
+        gdb: Option<GdbStub>,
+        video_standard: VideoStandard,
+        console_region: ConsoleRegion,
+        platform: Option<Platform>,
+        patch: Option<PathBuf>,
+        audio: AudioOutput,
         gilrs: Gilrs,
         menu: AppMenu,
         proxy: EventLoopProxy<MenuAction>,
     ) -> Self {
         Self {
             initial_rom,
-            audio_buf,
-            sample_rate,
-            _stream: stream,
+            audio,
             gilrs,
             menu,
             proxy,
@@ -238,12 +298,23 @@ impl VibeApp {
             renderer: None,
             egui_state: None,
             emu: None,
+            gamedb: load_game_db(),
+            tracer: tracer.map(Box::new),
+            gdb,
             rom_path: None,
+            rom_media: MediaSlot::Cartridge,
+            rom_patch: patch,
+            save_path: None,
             fb: vec![0u32; SMS_W * SMS_H],
             pad: PadState::default(),
             pressed_keys: HashSet::new(),
             mx: 0, my: 0,
             trigger_frames: 0,
+            pending_reset: false,
+            recorder: None,
+            player: None,
+            rewind: RewindBuffer::new(REWIND_BUDGET, 1),
+            last_audio: Vec::new(),
             last_frame: Instant::now(),
             time_debt_us: 0,
             sram_save_timer: 0,
@@ -255,29 +326,146 @@ impl VibeApp {
                 save_slot:       1,
                 binding:         None,
                 key_config:      KeyConfig::default(),
-                fm_disabled:      false,
+                fm:               None,
+                bios_enabled:     true,
+                sync_to_vsync:    false,
+                rewind_enabled:   true,
+                video_standard,
+                console_region,
+                platform,
+                reset_notice:     None,
                 rom_loaded:       false,
+                movie_hud:        None,
+                speed:            SpeedControl::default(),
+                debugger:         Debugger::default(),
+                debug_view:       DebugView::default(),
+                vdp_view:         VdpView::default(),
+                memory_view:      MemoryView::default(),
+                cheat_view:       CheatView::default(),
+                rom_info:         RomInfoView::default(),
+                ram_search:       RamSearchView::default(),
                 menu_bar_height:  0.0,
             },
         }
     }
 
+    /// Builds a machine for the ROM at `path`, plugged into `media`. A game
+    /// database entry overrides the platform detected from the extension and
+    /// ROM contents (unless one is forced), and the chosen video standard and
+    /// region. Cards always go into a Master System (SG-1000 cards
+    /// included). With BIOS boot on, the console's BIOS runs first if one is
+    /// found. `patch` (or else a same-named `.ips`/`.bps`/`.ups`) is applied
+    /// first; returns the machine and the path its saves are named after.
+    fn load_rom(&self, path: &Path, media: MediaSlot, patch: Option<&Path>) -> Option<(Emulator, PathBuf)> {
+        let mut data = match std::fs::read(path) {
+            Ok(data) => data,
+            Err(e) => { eprintln!("Failed to load ROM: {e}"); return None; }
+        };
+        let patch = patch.map(Path::to_path_buf).or_else(|| find_patch(path));
+        if let Some(ref p) = patch {
+            match std::fs::read(p).map_err(|e| e.to_string()).and_then(|bytes| apply_patch(&data, &bytes)) {
+                Ok(patched) => { data = patched; println!("Patch applied: {}", p.display()); }
+                Err(e) => { eprintln!("Failed to apply patch {}: {e}", p.display()); return None; }
+            }
+        }
+        let save = save_base(path, patch.as_deref());
+        let d = &self.dialog;
+        let game = self.gamedb.lookup_rom(&data).cloned();
+        let platform = if media != MediaSlot::Cartridge {
+            Platform::MasterSystem
+        } else {
+            d.platform.or(game.as_ref().and_then(|g| g.platform)).unwrap_or_else(|| Platform::detect(
+                path.extension().and_then(|e| e.to_str()).unwrap_or(""), &data))
+        };
+        let video = game.as_ref().and_then(|g| g.video).unwrap_or(d.video_standard);
+        let region = game.as_ref().and_then(|g| g.region).unwrap_or(d.console_region);
+        let mut emu = Emulator::new(data, platform, video, self.audio.sample_rate);
+        emu.set_console_region(region);
+        emu.set_media_slot(media);
+        if let Some(game) = game {
+            println!("Game database: {} ({})", game.title, game.describe());
+            emu.set_game_info(game);
+        }
+        if let Some(bios_file) = bios_path(platform).filter(|p| d.bios_enabled && p.exists()) {
+            match std::fs::read(&bios_file) {
+                Ok(data) => emu.set_bios(data),
+                Err(e) => eprintln!("Failed to load BIOS: {e}"),
+            }
+        }
+        emu.set_fm_disabled(region.fm_disabled(platform, d.fm));
+        load_sram_into(&emu, &save);
+        load_eeprom_into(&emu, &save);
+        match load_cheats(&cheats_path(&save)) {
+            Ok(cheats) => emu.set_cheats(cheats),
+            Err(e) => eprintln!("Failed to load cheats: {e}"),
+        }
+        println!("Loaded ROM: {} ({:?}, {:?}, {:?}, {:?})",
+            path.file_stem().and_then(|n| n.to_str()).unwrap_or("?"), platform, media, video, region);
+        Some((emu, save))
+    }
+
+    /// Replaces the running machine, carrying the trace log over to the new one.
+    fn replace_emu(&mut self, mut emu: Option<Emulator>) {
+        let tracer = self.emu.as_mut().and_then(Emulator::take_tracer).or_else(|| self.tracer.take());
+        match emu.as_mut() {
+            Some(e) => e.set_tracer(tracer),
+            None => self.tracer = tracer,
+        }
+        self.emu = emu;
+    }
+
     fn flush_saves(&self) {
-        if let (Some(ref e), Some(ref p)) = (&self.emu, &self.rom_path) {
+        // Movies run on blank battery RAM; never let them overwrite the player's saves.
+        if self.recorder.is_some() || self.player.is_some() { return; }
+        if let (Some(ref e), Some(ref p)) = (&self.emu, &self.save_path) {
             if e.is_sram_dirty()   { save_sram(e, p); }
             if e.is_eeprom_dirty() { save_eeprom(e, p); }
         }
     }
 
+    /// Ends recording (writing the movie beside the ROM) or playback, and
+    /// puts back the battery RAM the movie ran without.
+    fn stop_movie(&mut self) {
+        if let Some(ref e) = self.emu {
+            if let Some(ref rec) = self.recorder { rec.restore_battery(e); }
+            if let Some(ref pl) = self.player { pl.restore_battery(e); }
+        }
+        if let Some(rec) = self.recorder.take() {
+            if let Some(ref rom) = self.save_path {
+                let path = movie_path(rom);
+                match std::fs::write(&path, rec.finish().serialize()) {
+                    Ok(_) => println!("Movie saved: {}", path.display()),
+                    Err(e) => eprintln!("Failed to write movie: {e}"),
+                }
+            }
+        }
+        self.player = None;
+        self.pending_reset = false;
+        self.dialog.movie_hud = None;
+    }
+
     fn render(&mut self) {
         let window = match self.window.as_ref() { Some(w) => w.clone(), None => return };
         let gl = match self.gl_state.as_ref().map(|s| s.gl.clone()) { Some(g) => g, None => return };
 
-        // Timing
+        // Timing — paced by the audio device's consumption (dynamic rate control
+        // absorbs clock drift), by the display refresh when syncing to vsync, or
+        // by the wall clock at the machine's 60 Hz NTSC / 50 Hz PAL when there is no audio device.
+        let frame_us = self.emu.as_ref().map(|e| e.video).unwrap_or(self.dialog.video_standard).frame_us();
         let now = Instant::now();
         let elapsed = now.duration_since(self.last_frame).as_micros().min(50_000) as i64;
         self.last_frame = now;
-        self.time_debt_us = (self.time_debt_us + elapsed).min(SMS_FRAME_US * 2);
+        self.time_debt_us = (self.time_debt_us + elapsed).min(frame_us * 2);
+        let frames_to_run = if self.dialog.sync_to_vsync {
+            1
+        } else if self.audio.has_device() {
+            self.audio.frames_wanted(frame_us)
+        } else if self.time_debt_us >= frame_us {
+            self.time_debt_us -= frame_us;
+            1
+        } else {
+            0
+        };
 
         // Gamepad input
         while let Some(GilrsEvent { id, .. }) = self.gilrs.next_event() {
@@ -293,6 +481,8 @@ impl VibeApp {
 
         let is_gg = self.emu.as_ref().map(|e| e.platform.is_gg()).unwrap_or(false);
         let is_sg = self.emu.as_ref().map(|e| e.platform.is_sg_family()).unwrap_or(false);
+        // Hold Tab to fast-forward
+        self.dialog.speed.ff_held = self.pressed_keys.contains(&KeyCode::Tab);
         let pk = &self.pressed_keys;
         let kc = &self.dialog.key_config;
         let p  = &self.pad;
@@ -303,33 +493,113 @@ impl VibeApp {
         let kb1    = pk.contains(&kc.p1.b1)    || p.b1;
         let kb2    = pk.contains(&kc.p1.b2)    || p.b2;
         let kstart = pk.contains(&kc.p1.start) || p.start;
+        let p2 = [kc.p2.up, kc.p2.down, kc.p2.left, kc.p2.right, kc.p2.b1, kc.p2.b2].map(|k| pk.contains(&k));
+        // Hold Backspace to rewind (not while a movie is recording or playing)
+        let rewinding = self.dialog.rewind_enabled && pk.contains(&KeyCode::Backspace)
+            && self.recorder.is_none() && self.player.is_none();
+        if let (Some(stub), Some(e)) = (self.gdb.as_mut(), self.emu.as_mut()) {
+            if let Some(running) = stub.poll(e, &mut self.dialog.debugger) {
+                self.dialog.speed.paused = !running;
+            }
+        }
+        // Breakpoints, watchpoints and stepping apply to normal play only
+        let debugging = self.dialog.debugger.is_active() && !rewinding
+            && self.recorder.is_none() && self.player.is_none();
+
+        // Step emulation — each tick is one frame of output time; the speed
+        // controls decide how many emulated frames fill it.
+        for _ in 0..frames_to_run {
+            let Some(ref mut e) = self.emu else {
+                self.fb.iter_mut().for_each(|p| *p = 0);
+                continue;
+            };
+            e.set_fm_disabled(e.console_region().fm_disabled(e.platform(), self.dialog.fm));
 
-        // Step emulation
-        if self.time_debt_us >= SMS_FRAME_US {
-            self.time_debt_us -= SMS_FRAME_US;
-            let trigger_active = self.trigger_frames > 0;
-            if self.trigger_frames > 0 { self.trigger_frames -= 1; }
-
-            if let Some(ref mut e) = self.emu {
-                e.set_fm_disabled(is_sg || is_gg || self.dialog.fm_disabled);
-                e.set_input(ku, kd, kl, kr, kb1 || trigger_active, kb2, kstart);
-                e.set_lightgun(trigger_active, self.mx.min(255), self.my.min(191));
-
-                let (_, mut samples) = e.step_frame();
-                if let Ok(mut buf) = self.audio_buf.try_lock() {
-                    buf.append(&mut samples);
-                    if buf.len() > 8192 { let excess = buf.len() - 8192; buf.drain(0..excess); }
-                }
+            // A debugger step runs even while paused, until it completes.
+            let frames = if debugging && self.dialog.speed.paused && self.dialog.debugger.is_stepping() {
+                1
+            } else {
+                self.dialog.speed.frames_for_tick()
+            };
+            let mut tick_samples = None;
+            for _ in 0..frames {
+                let trigger_active = self.trigger_frames > 0;
+                if self.trigger_frames > 0 { self.trigger_frames -= 1; }
+
+                let input = FrameInput {
+                    p1: [ku, kd, kl, kr, kb1, kb2],
+                    p2,
+                    start: kstart,
+                    gun: trigger_active.then_some((self.mx.min(255), self.my.min(191))),
+                    reset: std::mem::take(&mut self.pending_reset),
+                };
+
+                let (_, samples) = if rewinding {
+                    if self.rewind.rewind(e) {
+                        // Re-run the restored frame to redraw it; play its audio backwards.
+                        let (vblank, mut s) = e.step_frame();
+                        s.reverse();
+                        s.chunks_exact_mut(2).for_each(|lr| lr.swap(0, 1));
+                        (vblank, s)
+                    } else {
+                        (false, Vec::new())
+                    }
+                } else if let Some(ref mut pl) = self.player {
+                    let out = pl.step(e).unwrap_or_default();
+                    let desync = pl.desync_frame().map(|f| format!("  desync @ {f}")).unwrap_or_default();
+                    self.dialog.movie_hud = Some(format!("▶ {}/{}{}", pl.frame(), pl.len(), desync));
+                    if pl.is_finished() {
+                        match pl.desync_frame() {
+                            Some(f) => eprintln!("Movie desynced at frame {f}"),
+                            None => println!("Movie finished ({} frames)", pl.len()),
+                        }
+                        pl.restore_battery(e);
+                        self.player = None;
+                        self.dialog.movie_hud = None;
+                    }
+                    out
+                } else if let Some(ref mut rec) = self.recorder {
+                    let out = rec.step(e, input);
+                    self.dialog.movie_hud = Some(format!("● REC {}", rec.len()));
+                    out
+                } else {
+                    e.apply_input(&input);
+                    let out = if debugging {
+                        let (vblank, samples, stop) = e.step_frame_debug(&mut self.dialog.debugger);
+                        if let Some(reason) = stop {
+                            self.dialog.speed.paused = true;
+                            self.dialog.debug_view.open = true;
+                            if let Some(ref mut stub) = self.gdb { stub.stopped(&reason, &self.dialog.debugger); }
+                        }
+                        (vblank, samples)
+                    } else {
+                        e.step_frame()
+                    };
+                    if self.dialog.rewind_enabled { self.rewind.push(e); }
+                    out
+                };
+                tick_samples = Some(samples);
 
                 self.sram_save_timer += 1;
-                if self.sram_save_timer >= 300 {
+                let movie_active = self.recorder.is_some() || self.player.is_some();
+                if self.sram_save_timer >= 300 && !movie_active {
                     self.sram_save_timer = 0;
-                    if let Some(ref p) = self.rom_path {
+                    if let Some(ref p) = self.save_path {
                         if e.is_sram_dirty()   { save_sram(e, p); }
                         if e.is_eeprom_dirty() { save_eeprom(e, p); }
                     }
                 }
+                // A debugger break ends the batch mid-frame
+                if self.dialog.speed.paused { break; }
+            }
+
+            let ran = tick_samples.is_some();
+            let samples = self.dialog.speed.tick_audio(tick_samples, &mut self.last_audio);
+            self.audio.queue(&samples);
 
+            // Only the last frame of a fast-forward batch is presented. The VDP
+            // still renders every frame: sprite collision/overflow depend on it.
+            if ran {
                 let frame = e.get_framebuffer();
                 let (rw, rh, xo, yo) = if is_gg { (GG_W, GG_H, 48, 24) } else { (SMS_W, SMS_H, 0, 0) };
                 let (bx, by) = if is_gg { ((SMS_W - GG_W) / 2, (SMS_H - GG_H) / 2) } else { (0, 0) };
@@ -340,8 +610,28 @@ impl VibeApp {
                         self.fb[(by + y) * SMS_W + (bx + x)] = px & 0x00FF_FFFF;
                     }
                 }
-            } else {
-                self.fb.iter_mut().for_each(|p| *p = 0);
+            }
+        }
+
+        if self.dialog.debug_view.open {
+            self.dialog.debug_view.update(self.emu.as_ref());
+        }
+        if self.dialog.vdp_view.is_open() {
+            self.dialog.vdp_view.update(self.emu.as_ref());
+        }
+        if self.dialog.memory_view.open {
+            self.dialog.memory_view.update(self.emu.as_mut());
+        }
+        if self.dialog.rom_info.open {
+            self.dialog.rom_info.update(self.emu.as_ref());
+        }
+        let search_cheats = self.dialog.ram_search.open && self.dialog.ram_search.update(self.emu.as_mut());
+        let dialog_cheats = self.dialog.cheat_view.open && self.dialog.cheat_view.update(self.emu.as_mut());
+        if search_cheats || dialog_cheats {
+            if let (Some(ref e), Some(ref p)) = (&self.emu, &self.save_path) {
+                if let Err(err) = save_cheats(&cheats_path(p), e.cheats()) {
+                    eprintln!("Failed to save cheats: {err}");
+                }
             }
         }
 
@@ -373,7 +663,7 @@ impl VibeApp {
 
     fn handle_menu_action(&mut self, action: MenuAction, elwt: &ActiveEventLoop) {
         match action {
-            MenuAction::OpenRom => {
+            MenuAction::OpenRom(media) => {
                 self.flush_saves();
                 // GTK is single-threaded: spawn the async dialog on the glib main
                 // context (main thread).  about_to_wait() pumps that context each
@@ -387,7 +677,7 @@ impl VibeApp {
                             .pick_file()
                             .await
                         {
-                            let _ = proxy.send_event(MenuAction::RomSelected(handle.path().to_path_buf()));
+                            let _ = proxy.send_event(MenuAction::RomSelected(handle.path().to_path_buf(), media));
                         }
                     });
                 }
@@ -405,58 +695,155 @@ impl VibeApp {
                                 .pick_file(),
                         );
                         if let Some(h) = handle {
-                            let _ = proxy.send_event(MenuAction::RomSelected(h.path().to_path_buf()));
+                            let _ = proxy.send_event(MenuAction::RomSelected(h.path().to_path_buf(), media));
                         }
                     });
                 }
             }
-            MenuAction::RomSelected(p) => {
-                if let Some(e) = load_rom(&p, self.sample_rate, self.dialog.fm_disabled) {
+            MenuAction::RomSelected(p, media) => {
+                self.stop_movie();
+                self.rewind.clear();
+                if let Some((e, save)) = self.load_rom(&p, media, None) {
                     self.rom_path = Some(p);
-                    self.emu = Some(e);
+                    self.rom_media = media;
+                    self.rom_patch = None;
+                    self.save_path = Some(save);
+                    self.replace_emu(Some(e));
                     self.sram_save_timer = 0;
                 }
             }
+            MenuAction::Reset if self.recorder.is_some() => {
+                // Recorded as a power cycle so playback resets on the same frame.
+                self.pending_reset = true;
+            }
             MenuAction::Reset => {
                 self.flush_saves();
+                self.stop_movie();
+                self.rewind.clear();
                 if let Some(ref p) = self.rom_path.clone() {
-                    self.emu = load_rom(p, self.sample_rate, self.dialog.fm_disabled);
+                    let loaded = self.load_rom(p, self.rom_media, self.rom_patch.as_deref());
+                    self.save_path = loaded.as_ref().map(|(_, save)| save.clone());
+                    self.replace_emu(loaded.map(|(e, _)| e));
                     self.sram_save_timer = 0;
                 }
             }
             MenuAction::Stop => {
                 self.flush_saves();
-                self.emu = None;
+                self.stop_movie();
+                self.rewind.clear();
+                self.replace_emu(None);
                 self.rom_path = None;
+                self.rom_patch = None;
+                self.save_path = None;
                 self.fb.iter_mut().for_each(|p| *p = 0);
             }
             MenuAction::Quit => {
                 self.flush_saves();
+                self.stop_movie();
                 self.shutdown_gl();
                 elwt.exit();
             }
             MenuAction::SaveState => {
-                if let (Some(ref e), Some(ref p)) = (&self.emu, &self.rom_path) {
+                if let (Some(ref e), Some(ref p)) = (&self.emu, &self.save_path) {
                     save_state_to_slot(e, p, self.dialog.save_slot);
                     self.dialog.show_slot_hud = 90;
                 }
             }
             MenuAction::LoadState => {
+                self.stop_movie();
                 let slot = self.dialog.save_slot;
-                let rom_path = self.rom_path.clone();
-                if let (Some(ref mut e), Some(ref p)) = (&mut self.emu, &rom_path) {
+                let save_path = self.save_path.clone();
+                if let (Some(ref mut e), Some(ref p)) = (&mut self.emu, &save_path) {
                     load_state_from_slot(e, p, slot);
                     self.dialog.show_slot_hud = 90;
                 }
             }
+            MenuAction::RecordMovie { from_power_on } => {
+                self.stop_movie();
+                self.flush_saves();
+                self.rewind.clear();
+                if let Some(ref mut e) = self.emu {
+                    self.recorder = Some(MovieRecorder::start(e, from_power_on));
+                    self.dialog.movie_hud = Some("● REC 0".into());
+                }
+            }
+            MenuAction::PlayMovie => {
+                self.stop_movie();
+                self.flush_saves();
+                self.rewind.clear();
+                if let (Some(ref mut e), Some(ref rom)) = (&mut self.emu, &self.save_path) {
+                    let path = movie_path(rom);
+                    let started = std::fs::read(&path)
+                        .map_err(|e| format!("No movie at {}: {e}", path.display()))
+                        .and_then(|data| Movie::deserialize(&data).ok_or_else(|| "Movie file is invalid".to_string()))
+                        .and_then(|movie| MoviePlayer::start(movie, e));
+                    match started {
+                        Ok(pl) => self.player = Some(pl),
+                        Err(e) => eprintln!("{e}"),
+                    }
+                }
+            }
+            MenuAction::StopMovie => self.stop_movie(),
+            MenuAction::TogglePause => {
+                self.dialog.speed.paused = !self.dialog.speed.paused;
+            }
+            MenuAction::FrameAdvance => self.dialog.speed.request_frame_advance(),
+            MenuAction::ToggleFastForward => {
+                self.dialog.speed.fast_forward = !self.dialog.speed.fast_forward;
+            }
+            MenuAction::SetFastForwardSpeed(m) => self.dialog.speed.ff_multiplier = m,
+            MenuAction::ToggleSlowMotion => {
+                self.dialog.speed.slow_motion = !self.dialog.speed.slow_motion;
+            }
+            MenuAction::SetSlowMotionSpeed(d) => self.dialog.speed.slow_divisor = d,
+            MenuAction::SetOffSpeedAudio(mode) => self.dialog.speed.audio = mode,
             MenuAction::SetSlot(slot) => {
                 self.dialog.save_slot = slot;
                 self.dialog.show_slot_hud = 90;
             }
             MenuAction::ToggleFm => {
-                self.dialog.fm_disabled = !self.dialog.fm_disabled;
+                let on = match self.emu {
+                    Some(ref e) => !e.is_fm_disabled(),
+                    None => self.dialog.fm.unwrap_or(self.dialog.console_region.has_builtin_fm()),
+                };
+                self.dialog.fm = Some(!on);
                 self.dialog.show_fm_notice = true;
             }
+            MenuAction::ToggleRewind => {
+                self.dialog.rewind_enabled = !self.dialog.rewind_enabled;
+                if !self.dialog.rewind_enabled { self.rewind.clear(); }
+            }
+            MenuAction::ToggleBios => {
+                self.dialog.bios_enabled = !self.dialog.bios_enabled;
+                if self.rom_path.is_some() { self.dialog.reset_notice = Some("BIOS boot"); }
+            }
+            MenuAction::ToggleVsync => {
+                self.dialog.sync_to_vsync = !self.dialog.sync_to_vsync;
+            }
+            MenuAction::SetVideoStandard(video) => {
+                if self.dialog.video_standard != video {
+                    self.dialog.video_standard = video;
+                    if self.rom_path.is_some() { self.dialog.reset_notice = Some("Video standard"); }
+                }
+            }
+            MenuAction::SetPlatform(platform) => {
+                if self.dialog.platform != platform {
+                    self.dialog.platform = platform;
+                    if self.rom_path.is_some() { self.dialog.reset_notice = Some("Platform"); }
+                }
+            }
+            MenuAction::SetConsoleRegion(region) => {
+                if self.dialog.console_region != region {
+                    self.dialog.console_region = region;
+                    if self.rom_path.is_some() { self.dialog.reset_notice = Some("Console region"); }
+                }
+            }
+            MenuAction::ShowDebugger => { self.dialog.debug_view.open = true; }
+            MenuAction::ShowCheats => { self.dialog.cheat_view.open = true; }
+            MenuAction::ShowRomInfo => { self.dialog.rom_info.open = true; }
+            MenuAction::ShowRamSearch => { self.dialog.ram_search.open = true; }
+            MenuAction::ShowMemoryViewer => { self.dialog.memory_view.open = true; }
+            MenuAction::ShowVdpViewer(v) => self.dialog.vdp_view.open(v),
             MenuAction::ShowControls => { self.dialog.show_key_config = true; }
             MenuAction::ShowAbout    => { self.dialog.show_about = true; }
         }
@@ -514,9 +901,11 @@ impl ApplicationHandler<MenuAction> for VibeApp {
 
         if let Some(path_str) = self.initial_rom.take() {
             let p = PathBuf::from(path_str);
-            if let Some(e) = load_rom(&p, self.sample_rate, self.dialog.fm_disabled) {
+            if let Some((e, save)) = self.load_rom(&p, MediaSlot::Cartridge, self.rom_patch.as_deref()) {
                 self.rom_path = Some(p);
-                self.emu = Some(e);
+                self.save_path = Some(save);
+                self.rom_media = MediaSlot::Cartridge;
+                self.replace_emu(Some(e));
             }
         }
 
@@ -536,6 +925,7 @@ impl ApplicationHandler<MenuAction> for VibeApp {
         match &event {
             WindowEvent::CloseRequested => {
                 self.flush_saves();
+                self.stop_movie();
                 self.shutdown_gl();
                 event_loop.exit();
             }
@@ -567,18 +957,22 @@ impl ApplicationHandler<MenuAction> for VibeApp {
                         return;
                     }
 
-                    // Save-state hotkeys (only when not binding)
+                    // Save-state and speed hotkeys (only when not binding)
                     match code {
+                        KeyCode::F8  => self.dialog.speed.slow_motion = !self.dialog.speed.slow_motion,
+                        KeyCode::F9  => self.dialog.speed.paused = !self.dialog.speed.paused,
+                        KeyCode::F10 => self.dialog.speed.request_frame_advance(),
                         KeyCode::F7 => {
-                            if let (Some(ref e), Some(ref p)) = (&self.emu, &self.rom_path) {
+                            if let (Some(ref e), Some(ref p)) = (&self.emu, &self.save_path) {
                                 save_state_to_slot(e, p, self.dialog.save_slot);
                                 self.dialog.show_slot_hud = 90;
                             }
                         }
                         KeyCode::F5 => {
+                            self.stop_movie();
                             let slot = self.dialog.save_slot;
-                            let rom_path = self.rom_path.clone();
-                            if let (Some(ref mut e), Some(ref p)) = (&mut self.emu, &rom_path) {
+                            let save_path = self.save_path.clone();
+                            if let (Some(ref mut e), Some(ref p)) = (&mut self.emu, &save_path) {
                                 load_state_from_slot(e, p, slot);
                                 self.dialog.show_slot_hud = 90;
                             }
@@ -614,8 +1008,10 @@ impl ApplicationHandler<MenuAction> for VibeApp {
                     if (0.0..=1.0).contains(&ry) { self.my = (ry * emu_h) as u16; }
                 }
             }
-            WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. } if !consumed => {
-                if self.trigger_frames == 0 { self.trigger_frames = 6; }
+            WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. }
+                if !consumed && self.trigger_frames == 0 =>
+            {
+                self.trigger_frames = 6;
             }
             _ => {}
         }
@@ -646,6 +1042,10 @@ impl ApplicationHandler<MenuAction> for VibeApp {
         // Safety net: if shutdown_gl() wasn't called before exit() (e.g. via
         // OS kill), free GPU resources here while the context is still valid.
         self.shutdown_gl();
+        let tracer = self.emu.as_mut().and_then(Emulator::take_tracer).or_else(|| self.tracer.take());
+        if let Some(mut t) = tracer {
+            if let Err(e) = t.finish() { eprintln!("{e}"); }
+        }
     }
 }
 
@@ -0,0 +1,301 @@
+//! Audio output: a lock-free ring buffer between the emulation loop and the
+//! cpal callback, plus dynamic rate control that keeps the buffer half full.
+//!
+//! The console and the sound card run on unrelated clocks, so feeding the
+//! device exactly what the core produces slowly drifts into underruns or an
+//! ever-growing queue. Each frame of samples is resampled by at most ±0.5%
+//! according to the current fill level, which is inaudible but enough to
+//! absorb the difference.
+
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::Stream;
+
+/// Queued audio the frontend aims for; the ring holds four times as much.
+const LATENCY_MS: usize = 64;
+
+/// Largest resampling correction applied by [`RateControl`].
+const MAX_RATE_DELTA: f64 = 0.005;
+
+// ── Ring buffer ───────────────────────────────────────────────────────────────
+
+struct RingInner {
+    buf:  Box<[AtomicU32]>,
+    /// Total samples read (consumer side).
+    head: AtomicUsize,
+    /// Total samples written (producer side).
+    tail: AtomicUsize,
+}
+
+/// Single-producer / single-consumer queue of interleaved stereo f32 samples.
+///
+/// The emulation loop is the only producer and the cpal callback the only
+/// consumer; neither side ever blocks. Samples move in whole L/R pairs.
+#[derive(Clone)]
+pub(crate) struct AudioRing {
+    inner: Arc<RingInner>,
+}
+
+impl AudioRing {
+    pub(crate) fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(2) & !1;
+        Self {
+            inner: Arc::new(RingInner {
+                buf:  (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+                head: AtomicUsize::new(0),
+                tail: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.inner.buf.len()
+    }
+
+    /// Samples currently queued.
+    pub(crate) fn len(&self) -> usize {
+        let head = self.inner.head.load(Ordering::Acquire);
+        let tail = self.inner.tail.load(Ordering::Acquire);
+        tail.saturating_sub(head)
+    }
+
+    /// Appends as many samples as fit; returns how many were written.
+    pub(crate) fn push(&self, samples: &[f32]) -> usize {
+        let r = &self.inner;
+        let tail = r.tail.load(Ordering::Relaxed);
+        let head = r.head.load(Ordering::Acquire);
+        let free = self.capacity() - (tail - head);
+        let n = samples.len().min(free) & !1;
+        for (i, &s) in samples[..n].iter().enumerate() {
+            r.buf[(tail + i) % self.capacity()].store(s.to_bits(), Ordering::Relaxed);
+        }
+        r.tail.store(tail + n, Ordering::Release);
+        n
+    }
+
+    /// Fills `out` from the front of the queue; returns how many samples were read.
+    pub(crate) fn pop(&self, out: &mut [f32]) -> usize {
+        let r = &self.inner;
+        let head = r.head.load(Ordering::Relaxed);
+        let tail = r.tail.load(Ordering::Acquire);
+        let n = out.len().min(tail - head) & !1;
+        for (i, s) in out[..n].iter_mut().enumerate() {
+            *s = f32::from_bits(r.buf[(head + i) % self.capacity()].load(Ordering::Relaxed));
+        }
+        r.head.store(head + n, Ordering::Release);
+        n
+    }
+}
+
+// ── Dynamic rate control ──────────────────────────────────────────────────────
+
+/// Linear-interpolating resampler whose ratio follows the ring fill level:
+/// an emptier buffer stretches audio slightly, a fuller one squeezes it.
+#[derive(Default)]
+pub(crate) struct RateControl {
+    /// Fractional read position into the current block, in stereo frames.
+    pos:  f64,
+    /// Last frame of the previous block (interpolation start point).
+    last: (f32, f32),
+}
+
+impl RateControl {
+    /// Output/input ratio for a buffer holding `fill` samples when aiming at `target`.
+    pub(crate) fn ratio(fill: usize, target: usize) -> f64 {
+        let error = (target as f64 - fill as f64) / target.max(1) as f64;
+        1.0 + MAX_RATE_DELTA * error.clamp(-1.0, 1.0)
+    }
+
+    /// Resamples interleaved stereo `input` by `ratio`, appending to `out`.
+    pub(crate) fn process(&mut self, input: &[f32], ratio: f64, out: &mut Vec<f32>) {
+        let frames = input.len() / 2;
+        if frames == 0 { return; }
+        let frame = |i: usize| (input[2 * i], input[2 * i + 1]);
+        let step = 1.0 / ratio;
+
+        while self.pos < frames as f64 {
+            let i = self.pos as usize;
+            let t = (self.pos - i as f64) as f32;
+            let a = if i == 0 { self.last } else { frame(i - 1) };
+            let b = frame(i);
+            out.push(a.0 + (b.0 - a.0) * t);
+            out.push(a.1 + (b.1 - a.1) * t);
+            self.pos += step;
+        }
+        self.pos -= frames as f64;
+        self.last = frame(frames - 1);
+    }
+}
+
+// ── Device output ─────────────────────────────────────────────────────────────
+
+pub(crate) struct AudioOutput {
+    pub(crate) sample_rate: f32,
+    ring:         AudioRing,
+    rate_control: RateControl,
+    scratch:      Vec<f32>,
+    stream:       Option<Stream>,
+}
+
+impl AudioOutput {
+    /// Opens the default output device; without one, samples are discarded.
+    pub(crate) fn open() -> Self {
+        let mut sample_rate = 44100.0f32;
+        let mut ring = AudioRing::new(2);
+
+        let stream = cpal::default_host()
+            .default_output_device()
+            .and_then(|dev| {
+                let config = dev.default_output_config().ok()?;
+                sample_rate = config.sample_rate().0 as f32;
+                ring = AudioRing::new(target_fill(sample_rate) * 4);
+                let consumer = ring.clone();
+                let channels = config.channels() as usize;
+                let mut stereo = Vec::new();
+                let stream = dev.build_output_stream(
+                    &config.into(),
+                    move |data: &mut [f32], _| {
+                        // The core produces interleaved stereo; map each L/R pair
+                        // onto the device's channel layout (mono, stereo or more).
+                        stereo.resize(data.len() / channels * 2, 0.0);
+                        let got = consumer.pop(&mut stereo);
+                        stereo[got..].iter_mut().for_each(|s| *s = 0.0);
+                        for (frame, lr) in data.chunks_mut(channels).zip(stereo.chunks(2)) {
+                            match frame {
+                                [m] => *m = (lr[0] + lr[1]) * 0.5,
+                                [fl, fr, rest @ ..] => {
+                                    *fl = lr[0];
+                                    *fr = lr[1];
+                                    rest.iter_mut().for_each(|s| *s = 0.0);
+                                }
+                                [] => {}
+                            }
+                        }
+                    },
+                    |e| eprintln!("Audio error: {e}"),
+                    None,
+                ).ok()?;
+                let _ = stream.play();
+                Some(stream)
+            });
+
+        Self { sample_rate, ring, rate_control: RateControl::default(), scratch: Vec::new(), stream }
+    }
+
+    pub(crate) fn has_device(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// Number of whole frames (of `frame_us` each) needed to refill the buffer
+    /// to its target level. Capped so a stall never triggers a long catch-up burst.
+    pub(crate) fn frames_wanted(&self, frame_us: i64) -> u32 {
+        let target = target_fill(self.sample_rate);
+        let queued = self.ring.len();
+        if queued >= target { return 0; }
+        let per_frame = ((self.sample_rate as f64 * frame_us as f64 / 1e6) as usize * 2).max(2);
+        (target - queued).div_ceil(per_frame).min(3) as u32
+    }
+
+    /// Queues one frame of emulator audio, resampled towards the target fill level.
+    pub(crate) fn queue(&mut self, samples: &[f32]) {
+        if !self.has_device() { return; }
+        let ratio = RateControl::ratio(self.ring.len(), target_fill(self.sample_rate));
+        self.scratch.clear();
+        self.rate_control.process(samples, ratio, &mut self.scratch);
+        self.ring.push(&self.scratch);
+    }
+}
+
+/// Target queue length in samples (stereo, so twice the frame count).
+fn target_fill(sample_rate: f32) -> usize {
+    (sample_rate as usize * LATENCY_MS / 1000) * 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── AudioRing ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn ring_preserves_order_across_wraparound() {
+        let ring = AudioRing::new(8);
+        let mut out = [0.0f32; 6];
+        for round in 0..5 {
+            let base = round as f32 * 10.0;
+            assert_eq!(ring.push(&[base, base + 1.0, base + 2.0, base + 3.0, base + 4.0, base + 5.0]), 6);
+            assert_eq!(ring.pop(&mut out), 6);
+            assert_eq!(out, [base, base + 1.0, base + 2.0, base + 3.0, base + 4.0, base + 5.0]);
+        }
+    }
+
+    #[test]
+    fn ring_push_stops_when_full() {
+        let ring = AudioRing::new(4);
+        assert_eq!(ring.push(&[1.0; 6]), 4, "só cabem 4 amostras");
+        assert_eq!(ring.len(), 4);
+        assert_eq!(ring.push(&[1.0; 2]), 0);
+    }
+
+    #[test]
+    fn ring_moves_whole_stereo_pairs() {
+        let ring = AudioRing::new(8);
+        assert_eq!(ring.push(&[1.0, 2.0, 3.0]), 2, "amostra ímpar não é enfileirada");
+        let mut out = [0.0f32; 3];
+        assert_eq!(ring.pop(&mut out), 2);
+    }
+
+    #[test]
+    fn ring_works_across_threads() {
+        let ring = AudioRing::new(64);
+        let consumer = ring.clone();
+        let reader = std::thread::spawn(move || {
+            let mut got = Vec::new();
+            let mut buf = [0.0f32; 16];
+            while got.len() < 2000 {
+                let n = consumer.pop(&mut buf);
+                got.extend_from_slice(&buf[..n]);
+            }
+            got
+        });
+        let data: Vec<f32> = (0..2000).map(|i| i as f32).collect();
+        let mut sent = 0;
+        while sent < data.len() {
+            sent += ring.push(&data[sent..(sent + 10).min(data.len())]);
+        }
+        assert_eq!(reader.join().unwrap(), data);
+    }
+
+    // ── RateControl ───────────────────────────────────────────────────────────
+
+    #[test]
+    fn ratio_is_bounded_to_half_a_percent() {
+        assert_eq!(RateControl::ratio(1000, 1000), 1.0);
+        assert!((RateControl::ratio(0, 1000) - 1.005).abs() < 1e-9, "buffer vazio → estica");
+        assert!((RateControl::ratio(5000, 1000) - 0.995).abs() < 1e-9, "buffer cheio → comprime");
+    }
+
+    #[test]
+    fn unit_ratio_keeps_sample_count() {
+        let mut rc = RateControl::default();
+        let input = vec![0.5f32; 1600];
+        let mut out = Vec::new();
+        rc.process(&input, 1.0, &mut out);
+        assert_eq!(out.len(), input.len());
+    }
+
+    #[test]
+    fn stretched_output_tracks_ratio_over_many_frames() {
+        let mut rc = RateControl::default();
+        let input = vec![0.25f32; 1600];
+        let mut out = Vec::new();
+        for _ in 0..100 {
+            rc.process(&input, 1.005, &mut out);
+        }
+        let expected = 1600.0 * 100.0 * 1.005;
+        assert!((out.len() as f64 - expected).abs() <= 4.0, "esperado ~{expected}, veio {}", out.len());
+        assert!(out[4..].iter().all(|&s| (s - 0.25).abs() < 1e-6), "sinal constante é preservado");
+    }
+}
@@ -0,0 +1,108 @@
+//! Cheats dialog: add, name, toggle and delete Game Genie / Action Replay
+//! codes for the running game.
+
+use crate::{Cheat, CheatEffect, Emulator};
+
+#[derive(Default)]
+pub struct CheatView {
+    pub open:  bool,
+    cheats:    Vec<Cheat>,
+    /// The emulator's list as last seen, to notice a new game being loaded.
+    synced:    Vec<Cheat>,
+    new_code:  String,
+    new_name:  String,
+    error:     Option<String>,
+    /// The list was edited in the dialog and must go back to the emulator.
+    dirty:     bool,
+}
+
+impl CheatView {
+    /// Pushes edits into the emulator, or refreshes the list from it.
+    /// Returns true when the list changed and should be saved.
+    pub fn update(&mut self, emu: Option<&mut Emulator>) -> bool {
+        let Some(emu) = emu else {
+            self.cheats.clear();
+            self.synced.clear();
+            self.dirty = false;
+            return false;
+        };
+        if std::mem::take(&mut self.dirty) {
+            emu.set_cheats(self.cheats.clone());
+            self.synced = self.cheats.clone();
+            true
+        } else {
+            if emu.cheats() != self.synced.as_slice() {
+                self.synced = emu.cheats().to_vec();
+                self.cheats = self.synced.clone();
+            }
+            false
+        }
+    }
+
+    fn add(&mut self) {
+        match Cheat::parse(&self.new_code, &self.new_name) {
+            Ok(cheat) => {
+                self.cheats.push(cheat);
+                self.new_code.clear();
+                self.new_name.clear();
+                self.error = None;
+                self.dirty = true;
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+}
+
+fn describe(effect: CheatEffect) -> String {
+    match effect {
+        CheatEffect::RomPatch { addr, value, compare: Some(c) } => format!("ROM {addr:04X} = {value:02X} if {c:02X}"),
+        CheatEffect::RomPatch { addr, value, compare: None } => format!("ROM {addr:04X} = {value:02X}"),
+        CheatEffect::RamWrite { addr, value } => format!("RAM {addr:04X} = {value:02X}"),
+    }
+}
+
+pub fn draw_cheats(ctx: &egui::Context, view: &mut CheatView, rom_loaded: bool) {
+    let mut open = view.open;
+    egui::Window::new("Cheats")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            if !rom_loaded {
+                ui.label("No ROM loaded.");
+                return;
+            }
+            let mut remove = None;
+            if view.cheats.is_empty() {
+                ui.label("No cheats for this game.");
+            } else {
+                egui::Grid::new("cheat_list").num_columns(4).striped(true).show(ui, |ui| {
+                    for (i, cheat) in view.cheats.iter_mut().enumerate() {
+                        if ui.checkbox(&mut cheat.enabled, "").changed() { view.dirty = true; }
+                        let resp = ui.add(egui::TextEdit::singleline(&mut cheat.name).desired_width(160.0).hint_text("Name"));
+                        if resp.lost_focus() { view.dirty = true; }
+                        ui.monospace(cheat.code()).on_hover_text(describe(cheat.effect()));
+                        if ui.small_button("🗑").on_hover_text("Delete").clicked() { remove = Some(i); }
+                        ui.end_row();
+                    }
+                });
+            }
+            if let Some(i) = remove {
+                view.cheats.remove(i);
+                view.dirty = true;
+            }
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                let code = ui.add(egui::TextEdit::singleline(&mut view.new_code)
+                    .desired_width(110.0).font(egui::TextStyle::Monospace).hint_text("XXX-XXX-XXX"));
+                ui.add(egui::TextEdit::singleline(&mut view.new_name).desired_width(160.0).hint_text("Name"));
+                let enter = code.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button("Add").clicked() || enter { view.add(); }
+            });
+            ui.label(egui::RichText::new("Game Genie: XXX-XXX or XXX-XXX-XXX · Action Replay: 00XX-XXYY").small().weak());
+            if let Some(ref e) = view.error {
+                ui.colored_label(egui::Color32::from_rgb(255, 96, 96), e);
+            }
+        });
+    view.open = open;
+}
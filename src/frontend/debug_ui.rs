@@ -0,0 +1,190 @@
+//! Debugger window: run control, registers/flags, breakpoints and watchpoints.
+
+use crate::debugger::{Access, AddressSpace, Breakpoint, DebugBreak, Debugger, Registers, StepMode, Watchpoint};
+use crate::frontend::speed::SpeedControl;
+use crate::{Emulator, Instruction};
+
+/// Instructions listed from PC onwards.
+const CODE_LINES: usize = 12;
+
+/// Window state plus a snapshot of the CPU taken after each emulated tick.
+pub struct DebugView {
+    pub open:      bool,
+    registers:     Option<Registers>,
+    code:          Vec<Instruction>,
+    bp_input:      String,
+    watch_input:   String,
+    watch_space:   AddressSpace,
+    watch_read:    bool,
+    watch_write:   bool,
+    input_error:   bool,
+}
+
+impl Default for DebugView {
+    fn default() -> Self {
+        Self {
+            open: false,
+            registers: None,
+            code: Vec::new(),
+            bp_input: String::new(),
+            watch_input: String::new(),
+            watch_space: AddressSpace::Memory,
+            watch_read: false,
+            watch_write: true,
+            input_error: false,
+        }
+    }
+}
+
+impl DebugView {
+    pub fn update(&mut self, emu: Option<&Emulator>) {
+        let Some(emu) = emu else { self.registers = None; return; };
+        let regs = emu.registers();
+        self.code.clear();
+        let mut addr = regs.pc;
+        for _ in 0..CODE_LINES {
+            let ins = emu.disassemble(addr);
+            addr = ins.next_addr();
+            self.code.push(ins);
+        }
+        self.registers = Some(regs);
+    }
+}
+
+fn bank_label(bank: Option<u16>) -> String {
+    bank.map(|b| format!("{b:02X}")).unwrap_or_else(|| "--".into())
+}
+
+fn break_label(reason: &DebugBreak) -> String {
+    match reason {
+        DebugBreak::Breakpoint { pc, bank } => format!("breakpoint at {}:{pc:04X}", bank_label(*bank)),
+        DebugBreak::Watchpoint(hit) => {
+            let access = match hit.access { Access::Read => "read", Access::Write => "write" };
+            let addr = match hit.space {
+                AddressSpace::Memory => format!("${:04X}", hit.addr),
+                AddressSpace::Io => format!("port ${:02X}", hit.addr),
+            };
+            format!("{access} {addr} = ${:02X} by {:04X}", hit.value, hit.pc)
+        }
+        DebugBreak::Step => "step".into(),
+    }
+}
+
+pub fn draw_debugger(ctx: &egui::Context, view: &mut DebugView, dbg: &mut Debugger, speed: &mut SpeedControl) {
+    let mut open = view.open;
+    egui::Window::new("Debugger")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            // Run control
+            ui.horizontal(|ui| {
+                if ui.add_enabled(speed.paused, egui::Button::new("Continue")).clicked() {
+                    dbg.cancel_step();
+                    speed.paused = false;
+                }
+                if ui.add_enabled(!speed.paused, egui::Button::new("Break")).clicked() {
+                    speed.paused = true;
+                }
+                ui.separator();
+                for (mode, name) in [(StepMode::Into, "Step Into"), (StepMode::Over, "Step Over"), (StepMode::Out, "Step Out")] {
+                    if ui.add_enabled(speed.paused && view.registers.is_some(), egui::Button::new(name)).clicked() {
+                        dbg.step(mode);
+                    }
+                }
+            });
+            let status = match (speed.paused, &dbg.last_break) {
+                (false, _) => "Running".to_string(),
+                (true, Some(reason)) => format!("Stopped: {}", break_label(reason)),
+                (true, None) => "Paused".to_string(),
+            };
+            ui.label(status);
+            ui.separator();
+
+            // Registers
+            if let Some(r) = view.registers {
+                egui::Grid::new("dbg_regs").num_columns(4).show(ui, |ui| {
+                    for row in [
+                        [("AF", r.af), ("BC", r.bc), ("DE", r.de), ("HL", r.hl)],
+                        [("AF'", r.af_alt), ("BC'", r.bc_alt), ("DE'", r.de_alt), ("HL'", r.hl_alt)],
+                        [("IX", r.ix), ("IY", r.iy), ("SP", r.sp), ("PC", r.pc)],
+                    ] {
+                        for (name, value) in row {
+                            ui.monospace(format!("{name:<3} {value:04X}"));
+                        }
+                        ui.end_row();
+                    }
+                    ui.monospace(format!("I   {:02X}", r.i));
+                    ui.monospace(format!("R   {:02X}", r.r));
+                    ui.monospace(format!("IM  {}", r.interrupt_mode));
+                    ui.monospace(format!("IFF {}{}", r.iff1 as u8, r.iff2 as u8));
+                    ui.end_row();
+                });
+                ui.monospace(format!("Flags  {}{}", r.flags(), if r.halted { "   HALT" } else { "" }));
+                ui.separator();
+                for (i, ins) in view.code.iter().enumerate() {
+                    let line = format!("{} {ins}", if i == 0 { "▶" } else { " " });
+                    if dbg.breakpoints.iter().any(|b| b.addr == ins.addr) {
+                        ui.colored_label(egui::Color32::from_rgb(255, 80, 80), egui::RichText::new(line).monospace());
+                    } else {
+                        ui.monospace(line);
+                    }
+                }
+            } else {
+                ui.label("No ROM loaded.");
+            }
+            ui.separator();
+
+            // Breakpoints
+            ui.strong("Breakpoints");
+            let mut remove = None;
+            for (i, bp) in dbg.breakpoints.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.small_button("✕").clicked() { remove = Some(i); }
+                    let bank = bp.bank.map(|b| format!("{b:02X}:")).unwrap_or_default();
+                    ui.monospace(format!("{bank}{:04X}", bp.addr));
+                });
+            }
+            if let Some(i) = remove { dbg.breakpoints.remove(i); }
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut view.bp_input).hint_text("[bank:]addr").desired_width(90.0));
+                if ui.button("Add").clicked() {
+                    match Breakpoint::parse(&view.bp_input) {
+                        Some(bp) => { dbg.breakpoints.push(bp); view.bp_input.clear(); view.input_error = false; }
+                        None => view.input_error = true,
+                    }
+                }
+            });
+            ui.separator();
+
+            // Watchpoints
+            ui.strong("Watchpoints");
+            let mut remove = None;
+            for (i, w) in dbg.watchpoints.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.small_button("✕").clicked() { remove = Some(i); }
+                    let space = match w.space { AddressSpace::Memory => "mem", AddressSpace::Io => "io " };
+                    let rw = format!("{}{}", if w.read { "R" } else { "-" }, if w.write { "W" } else { "-" });
+                    ui.monospace(format!("{space} {rw} {:04X}-{:04X}", w.start, w.end));
+                });
+            }
+            if let Some(i) = remove { dbg.watchpoints.remove(i); }
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut view.watch_input).hint_text("addr[-end]").desired_width(90.0));
+                ui.radio_value(&mut view.watch_space, AddressSpace::Memory, "Memory");
+                ui.radio_value(&mut view.watch_space, AddressSpace::Io, "I/O");
+                ui.checkbox(&mut view.watch_read, "R");
+                ui.checkbox(&mut view.watch_write, "W");
+                if ui.button("Add").clicked() {
+                    let (read, write) = (view.watch_read, view.watch_write);
+                    match Watchpoint::parse(&view.watch_input, view.watch_space, read, write).filter(|_| read || write) {
+                        Some(w) => { dbg.watchpoints.push(w); view.watch_input.clear(); view.input_error = false; }
+                        None => view.input_error = true,
+                    }
+                }
+            });
+            if view.input_error {
+                ui.colored_label(egui::Color32::from_rgb(255, 80, 80), "Invalid address (hexadecimal).");
+            }
+        });
+    view.open = open;
+}
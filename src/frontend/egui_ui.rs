@@ -5,8 +5,20 @@ use egui_glow::Painter;
 use winit::window::Window;
 use winit::event::WindowEvent;
 
+use crate::debugger::Debugger;
+use crate::frontend::debug_ui::{draw_debugger, DebugView};
+use crate::frontend::cheat_ui::{draw_cheats, CheatView};
+use crate::frontend::memory_ui::{draw_memory_viewer, MemoryView};
+use crate::frontend::rom_info_ui::{draw_rom_info, RomInfoView};
+use crate::frontend::ram_search_ui::{draw_ram_search, RamSearchView};
+use crate::frontend::vdp_ui::{draw_vdp_viewers, VdpView, VdpViewer};
 use crate::frontend::input::{KeyConfig, key_label};
 use crate::frontend::menu::MenuAction;
+use crate::frontend::speed::SpeedControl;
+#[cfg(target_os = "linux")]
+use crate::frontend::speed::{OffSpeedAudio, FF_MULTIPLIERS, SLOW_DIVISORS};
+#[cfg(target_os = "linux")]
+use crate::platform::{ConsoleRegion, MediaSlot, Platform, VideoStandard};
 
 pub struct EguiState {
     pub ctx:         Context,
@@ -67,8 +79,33 @@ pub struct DialogState {
     pub save_slot:        usize,
     pub binding:          Option<(usize, usize)>,
     pub key_config:       KeyConfig,
-    pub fm_disabled:      bool,
+    /// FM on/off chosen from the menu; `None` follows the console region
+    /// (built into the Japanese SMS, an add-on for export units).
+    pub fm:               Option<bool>,
+    /// Boot through `bios.sms` / `bios.gg` beside the executable, when present.
+    pub bios_enabled:     bool,
+    /// Run one emulated frame per display refresh instead of pacing by audio.
+    pub sync_to_vsync:    bool,
+    /// Keep a rewind history; hold Backspace to step back through it.
+    pub rewind_enabled:   bool,
+    pub video_standard:   crate::platform::VideoStandard,
+    pub console_region:   crate::platform::ConsoleRegion,
+    /// Platform forced from the menu or `--platform`; `None` detects it.
+    pub platform:         Option<crate::platform::Platform>,
+    /// Name of a setting that only takes effect after Reset, shown in a notice.
+    pub reset_notice:     Option<&'static str>,
     pub rom_loaded:       bool,
+    /// Movie recording/playback status ("● REC 120"); `None` when no movie is active.
+    pub movie_hud:        Option<String>,
+    /// Pause, frame advance, fast-forward and slow-motion state.
+    pub speed:            SpeedControl,
+    pub debugger:         Debugger,
+    pub debug_view:       DebugView,
+    pub vdp_view:         VdpView,
+    pub memory_view:      MemoryView,
+    pub cheat_view:       CheatView,
+    pub rom_info:         RomInfoView,
+    pub ram_search:       RamSearchView,
     /// Height of the egui menu bar in egui points (Linux only; 0 elsewhere).
     pub menu_bar_height:  f32,
 }
@@ -112,6 +149,82 @@ fn draw_dialogs(
         );
     }
 
+    // Movie HUD
+    if let Some(ref status) = d.movie_hud {
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("movie_hud"),
+        ));
+        let pos = egui::pos2(ctx.content_rect().right() - 16.0, 40.0);
+        painter.text(
+            pos + egui::vec2(1.0, 1.0),
+            egui::Align2::RIGHT_TOP,
+            status,
+            egui::FontId::proportional(16.0),
+            egui::Color32::from_black_alpha(220),
+        );
+        painter.text(
+            pos,
+            egui::Align2::RIGHT_TOP,
+            status,
+            egui::FontId::proportional(16.0),
+            egui::Color32::from_rgb(255, 80, 80),
+        );
+    }
+
+    // Speed HUD
+    let speed = &d.speed;
+    let speed_status = if speed.paused {
+        Some("❚❚ Paused".to_string())
+    } else if speed.is_fast_forwarding() {
+        Some(format!("▶▶ ×{}", speed.ff_multiplier))
+    } else if speed.slow_motion {
+        Some(format!("▶ 1/{}", speed.slow_divisor))
+    } else {
+        None
+    };
+    if let Some(ref status) = speed_status {
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("speed_hud"),
+        ));
+        let pos = egui::pos2(ctx.content_rect().right() - 16.0, 62.0);
+        painter.text(
+            pos + egui::vec2(1.0, 1.0),
+            egui::Align2::RIGHT_TOP,
+            status,
+            egui::FontId::proportional(16.0),
+            egui::Color32::from_black_alpha(220),
+        );
+        painter.text(
+            pos,
+            egui::Align2::RIGHT_TOP,
+            status,
+            egui::FontId::proportional(16.0),
+            egui::Color32::from_rgb(255, 255, 0),
+        );
+    }
+
+    // Debugger
+    if d.debug_view.open {
+        draw_debugger(ctx, &mut d.debug_view, &mut d.debugger, &mut d.speed);
+    }
+    if d.vdp_view.is_open() {
+        draw_vdp_viewers(ctx, &mut d.vdp_view);
+    }
+    if d.memory_view.open {
+        draw_memory_viewer(ctx, &mut d.memory_view);
+    }
+    if d.cheat_view.open {
+        draw_cheats(ctx, &mut d.cheat_view, d.rom_loaded);
+    }
+    if d.rom_info.open {
+        draw_rom_info(ctx, &mut d.rom_info);
+    }
+    if d.ram_search.open {
+        draw_ram_search(ctx, &mut d.ram_search, &mut d.debugger);
+    }
+
     // Controls
     let mut show_key_config = d.show_key_config;
     egui::Window::new("Controls")
@@ -157,6 +270,19 @@ fn draw_dialogs(
             });
     }
 
+    // Reset-required notice (video standard, console region)
+    if let Some(setting) = d.reset_notice {
+        egui::Window::new("Setting Changed")
+            .collapsible(false).resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label(format!("{setting} setting changed."));
+                ui.label("Reset the game (Emulator → Reset) for the change to take effect.");
+                ui.separator();
+                if ui.button("  OK  ").clicked() { d.reset_notice = None; }
+            });
+    }
+
     // About
     let mut show_about = d.show_about;
     egui::Window::new("About vibe-sms")
@@ -192,11 +318,17 @@ fn draw_linux_menu(
         egui::MenuBar::new().ui(ui, |ui| {
             // Emulator
             ui.menu_button("Emulator", |ui| {
-                if ui.button("Open ROM…").clicked() { ui.close(); menu_tx(MenuAction::OpenRom); }
+                if ui.button("Open ROM…").clicked() { ui.close(); menu_tx(MenuAction::OpenRom(MediaSlot::Cartridge)); }
+                if ui.button("Open Sega Card…").on_hover_text("Plug a Sega Card / My Card or SG-1000 game into the Master System card slot").clicked() {
+                    ui.close(); menu_tx(MenuAction::OpenRom(MediaSlot::Card));
+                }
                 ui.separator();
                 ui.add_enabled_ui(d.rom_loaded, |ui| {
                     if ui.button("Reset").clicked() { ui.close(); menu_tx(MenuAction::Reset); }
                     if ui.button("Stop").clicked()  { ui.close(); menu_tx(MenuAction::Stop);  }
+                    ui.separator();
+                    if ui.button("Cheats…").clicked() { ui.close(); menu_tx(MenuAction::ShowCheats); }
+                    if ui.button("ROM Info…").clicked() { ui.close(); menu_tx(MenuAction::ShowRomInfo); }
                 });
                 ui.separator();
                 if ui.button("Quit").clicked() { ui.close(); menu_tx(MenuAction::Quit); }
@@ -221,18 +353,140 @@ fn draw_linux_menu(
                     });
                 });
             });
+            // Movie
+            ui.menu_button("Movie", |ui| {
+                ui.add_enabled_ui(d.rom_loaded, |ui| {
+                    if ui.button("Record from Power-On").clicked() {
+                        ui.close(); menu_tx(MenuAction::RecordMovie { from_power_on: true });
+                    }
+                    if ui.button("Record from Current State").clicked() {
+                        ui.close(); menu_tx(MenuAction::RecordMovie { from_power_on: false });
+                    }
+                    ui.separator();
+                    if ui.button("Play").clicked() { ui.close(); menu_tx(MenuAction::PlayMovie); }
+                });
+                ui.add_enabled_ui(d.movie_hud.is_some(), |ui| {
+                    if ui.button("Stop").clicked() { ui.close(); menu_tx(MenuAction::StopMovie); }
+                });
+            });
+            // Speed
+            ui.menu_button("Speed", |ui| {
+                let s = &d.speed;
+                let mut paused = s.paused;
+                if ui.checkbox(&mut paused, "Pause  [F9]").changed() {
+                    menu_tx(MenuAction::TogglePause);
+                }
+                if ui.button("Frame Advance  [F10]").clicked() {
+                    menu_tx(MenuAction::FrameAdvance);
+                }
+                ui.separator();
+                let mut ff = s.fast_forward;
+                if ui.checkbox(&mut ff, "Fast-Forward  [hold Tab]").changed() {
+                    menu_tx(MenuAction::ToggleFastForward);
+                }
+                ui.menu_button(format!("Fast-Forward Speed  [×{}]", s.ff_multiplier), |ui| {
+                    for m in FF_MULTIPLIERS {
+                        let label = format!("{} ×{}", if m == s.ff_multiplier { "✓" } else { "  " }, m);
+                        if ui.button(label).clicked() {
+                            ui.close(); menu_tx(MenuAction::SetFastForwardSpeed(m));
+                        }
+                    }
+                });
+                ui.separator();
+                let mut slow = s.slow_motion;
+                if ui.checkbox(&mut slow, "Slow Motion  [F8]").changed() {
+                    menu_tx(MenuAction::ToggleSlowMotion);
+                }
+                ui.menu_button(format!("Slow Motion Speed  [1/{}]", s.slow_divisor), |ui| {
+                    for div in SLOW_DIVISORS {
+                        let label = format!("{} 1/{}", if div == s.slow_divisor { "✓" } else { "  " }, div);
+                        if ui.button(label).clicked() {
+                            ui.close(); menu_tx(MenuAction::SetSlowMotionSpeed(div));
+                        }
+                    }
+                });
+                ui.separator();
+                ui.menu_button("Off-Speed Audio", |ui| {
+                    for (mode, name) in [(OffSpeedAudio::Mute, "Mute"), (OffSpeedAudio::RepeatDrop, "Repeat/Drop Frames")] {
+                        let label = format!("{} {}", if mode == s.audio { "✓" } else { "  " }, name);
+                        if ui.button(label).clicked() {
+                            ui.close(); menu_tx(MenuAction::SetOffSpeedAudio(mode));
+                        }
+                    }
+                });
+            });
+            // Debug
+            ui.menu_button("Debug", |ui| {
+                if ui.button("Debugger…").clicked() {
+                    ui.close(); menu_tx(MenuAction::ShowDebugger);
+                }
+                if ui.button("Memory Viewer…").clicked() {
+                    ui.close(); menu_tx(MenuAction::ShowMemoryViewer);
+                }
+                if ui.button("RAM Search…").clicked() {
+                    ui.close(); menu_tx(MenuAction::ShowRamSearch);
+                }
+                ui.separator();
+                for (viewer, name) in [(VdpViewer::Tiles, "Tile Viewer…"), (VdpViewer::Tilemap, "Tilemap Viewer…"),
+                                       (VdpViewer::Sprites, "Sprite Viewer…"), (VdpViewer::Palette, "Palette Viewer…")] {
+                    if ui.button(name).clicked() {
+                        ui.close(); menu_tx(MenuAction::ShowVdpViewer(viewer));
+                    }
+                }
+            });
             // Configuration
             ui.menu_button("Configuration", |ui| {
                 if ui.button("Controls…").clicked() {
                     ui.close(); menu_tx(MenuAction::ShowControls);
                 }
                 ui.separator();
-                let mut fm_on = !d.fm_disabled;
+                let mut fm_on = d.fm.unwrap_or(d.console_region.has_builtin_fm());
                 let changed = ui.add_enabled(!is_gg && !is_sg, egui::Checkbox::new(&mut fm_on, "FM Sound")).changed();
                 if changed { menu_tx(MenuAction::ToggleFm); }
                 if is_gg || is_sg {
                     ui.label(egui::RichText::new("(SMS only)").small().color(egui::Color32::GRAY));
                 }
+                ui.separator();
+                ui.menu_button("Platform", |ui| {
+                    let choices = [None, Some(Platform::MasterSystem), Some(Platform::GameGear),
+                                   Some(Platform::Sg1000), Some(Platform::Sc3000)];
+                    for platform in choices {
+                        let name = platform.map_or("Auto-Detect", |p| p.name());
+                        let label = format!("{} {}", if platform == d.platform { "✓" } else { "  " }, name);
+                        if ui.button(label).clicked() {
+                            ui.close(); menu_tx(MenuAction::SetPlatform(platform));
+                        }
+                    }
+                });
+                ui.menu_button("Video Standard", |ui| {
+                    for (video, name) in [(VideoStandard::Ntsc, "NTSC (60 Hz)"), (VideoStandard::Pal, "PAL (50 Hz)")] {
+                        let label = format!("{} {}", if video == d.video_standard { "✓" } else { "  " }, name);
+                        if ui.button(label).clicked() {
+                            ui.close(); menu_tx(MenuAction::SetVideoStandard(video));
+                        }
+                    }
+                });
+                ui.menu_button("Console Region", |ui| {
+                    for (region, name) in [(ConsoleRegion::Japan, "Japan"), (ConsoleRegion::Export, "Export")] {
+                        let label = format!("{} {}", if region == d.console_region { "✓" } else { "  " }, name);
+                        if ui.button(label).clicked() {
+                            ui.close(); menu_tx(MenuAction::SetConsoleRegion(region));
+                        }
+                    }
+                });
+                let mut bios = d.bios_enabled;
+                if ui.add_enabled(!is_sg, egui::Checkbox::new(&mut bios, "Boot BIOS")).changed() {
+                    menu_tx(MenuAction::ToggleBios);
+                }
+                ui.separator();
+                let mut vsync = d.sync_to_vsync;
+                if ui.checkbox(&mut vsync, "Sync to VSync").changed() {
+                    menu_tx(MenuAction::ToggleVsync);
+                }
+                let mut rewind = d.rewind_enabled;
+                if ui.checkbox(&mut rewind, "Rewind  [Backspace]").changed() {
+                    menu_tx(MenuAction::ToggleRewind);
+                }
             });
             // About
             ui.menu_button("About", |ui| {
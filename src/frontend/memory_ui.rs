@@ -0,0 +1,279 @@
+//! Memory viewer / hex editor. Shows one [`MemoryRegion`] at a time,
+//! highlights bytes changed since the last frame and queues edits that are
+//! written into the machine on the next tick.
+
+use crate::{find_bytes, parse_hex_bytes, Emulator, MemoryRegion};
+
+pub struct MemoryView {
+    pub open:    bool,
+    region:      MemoryRegion,
+    regions:     Vec<MemoryRegion>,
+    data:        Vec<u8>,
+    /// Contents before the last change, for highlighting.
+    prev:        Vec<u8>,
+    selected:    Option<usize>,
+    edit_text:   String,
+    focus_edit:  bool,
+    goto_text:   String,
+    search_text: String,
+    status:      String,
+    scroll_to:   Option<usize>,
+    writes:      Vec<(MemoryRegion, usize, u8)>,
+}
+
+impl Default for MemoryView {
+    fn default() -> Self {
+        Self {
+            open: false,
+            region: MemoryRegion::Ram,
+            regions: Vec::new(),
+            data: Vec::new(),
+            prev: Vec::new(),
+            selected: None,
+            edit_text: String::new(),
+            focus_edit: false,
+            goto_text: String::new(),
+            search_text: String::new(),
+            status: String::new(),
+            scroll_to: None,
+            writes: Vec::new(),
+        }
+    }
+}
+
+impl MemoryView {
+    /// Applies pending edits and re-reads the selected region.
+    pub fn update(&mut self, emu: Option<&mut Emulator>) {
+        let Some(emu) = emu else {
+            self.regions.clear();
+            self.data.clear();
+            self.prev.clear();
+            self.writes.clear();
+            return;
+        };
+        for (region, offset, value) in self.writes.drain(..) {
+            emu.write_region(region, offset, value);
+        }
+        self.regions = emu.memory_regions();
+        if !self.regions.contains(&self.region) {
+            self.select_region(MemoryRegion::Ram);
+        }
+        self.refresh(emu.read_region(self.region));
+    }
+
+    /// Takes this frame's contents; `prev` keeps the last frame's, so only
+    /// bytes that changed since then are highlighted.
+    fn refresh(&mut self, data: Vec<u8>) {
+        if data.len() == self.data.len() {
+            self.prev = std::mem::replace(&mut self.data, data);
+        } else {
+            // A new region (or size) starts with nothing highlighted or selected.
+            self.prev = data.clone();
+            self.data = data;
+            self.selected = None;
+        }
+    }
+
+    fn is_changed(&self, offset: usize) -> bool {
+        self.prev.get(offset).is_some_and(|&p| p != self.data[offset])
+    }
+
+    fn select_region(&mut self, region: MemoryRegion) {
+        self.region = region;
+        self.data.clear();
+        self.prev.clear();
+        self.selected = None;
+        self.status.clear();
+    }
+
+    fn select(&mut self, offset: usize) {
+        self.selected = Some(offset);
+        self.edit_text = format!("{:02X}", self.data[offset]);
+        self.focus_edit = true;
+    }
+
+    /// Region offset for a typed address: the displayed address, a mirror
+    /// of it for RAM, or a plain offset.
+    fn offset_of(&self, addr: u32) -> Option<usize> {
+        let base = self.region.base_address();
+        let len = self.data.len() as u32;
+        if len == 0 { return None; }
+        let off = if self.region == MemoryRegion::Ram && addr >= base {
+            (addr - base) % len
+        } else if addr >= base && addr - base < len {
+            addr - base
+        } else {
+            addr
+        };
+        (off < len).then_some(off as usize)
+    }
+
+    fn goto(&mut self) {
+        let text = self.goto_text.trim().trim_start_matches('$');
+        match u32::from_str_radix(text, 16).ok().and_then(|a| self.offset_of(a)) {
+            Some(off) => {
+                self.select(off);
+                self.scroll_to = Some(off);
+                self.status.clear();
+            }
+            None => self.status = format!("Address out of range: {}", self.goto_text.trim()),
+        }
+    }
+
+    fn search(&mut self) {
+        let Some(needle) = parse_hex_bytes(&self.search_text) else {
+            self.status = "Enter hex bytes, e.g. 3E 20".to_string();
+            return;
+        };
+        let from = self.selected.map_or(0, |s| s + 1);
+        match find_bytes(&self.data, &needle, from) {
+            Some(off) => {
+                self.select(off);
+                self.scroll_to = Some(off);
+                self.status = format!("Found at {:04X}", self.region.base_address() as usize + off);
+            }
+            None => self.status = "Not found".to_string(),
+        }
+    }
+
+    fn commit_edit(&mut self) {
+        let Some(off) = self.selected else { return };
+        match u8::from_str_radix(self.edit_text.trim().trim_start_matches('$'), 16) {
+            Ok(value) => {
+                self.writes.push((self.region, off, value));
+                self.data[off] = value;
+                // Hex-editor style: move on to the next byte.
+                if off + 1 < self.data.len() { self.select(off + 1); }
+                self.status.clear();
+            }
+            Err(_) => self.status = format!("Invalid byte: {}", self.edit_text.trim()),
+        }
+    }
+}
+
+fn entered(resp: &egui::Response, ui: &egui::Ui) -> bool {
+    resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))
+}
+
+pub fn draw_memory_viewer(ctx: &egui::Context, view: &mut MemoryView) {
+    let mut open = view.open;
+    egui::Window::new("Memory")
+        .open(&mut open)
+        .default_width(600.0)
+        .show(ctx, |ui| {
+            if view.regions.is_empty() {
+                ui.label("No ROM loaded.");
+                return;
+            }
+
+            // Region, goto and search
+            ui.horizontal(|ui| {
+                let mut region = view.region;
+                egui::ComboBox::from_id_salt("mem_region")
+                    .selected_text(region.name())
+                    .show_ui(ui, |ui| {
+                        for r in &view.regions {
+                            ui.selectable_value(&mut region, *r, r.name());
+                        }
+                    });
+                if region != view.region { view.select_region(region); }
+                ui.separator();
+                ui.label("Go to");
+                let resp = ui.add(egui::TextEdit::singleline(&mut view.goto_text).desired_width(60.0).font(egui::TextStyle::Monospace));
+                if entered(&resp, ui) { view.goto(); }
+                ui.separator();
+                ui.label("Find");
+                let resp = ui.add(egui::TextEdit::singleline(&mut view.search_text).desired_width(120.0).font(egui::TextStyle::Monospace));
+                if entered(&resp, ui) || ui.button("Next").clicked() { view.search(); }
+            });
+
+            // Editing the selected byte
+            ui.horizontal(|ui| {
+                match view.selected {
+                    Some(off) => {
+                        ui.monospace(format!("{:04X} =", view.region.base_address() as usize + off));
+                        let resp = ui.add(egui::TextEdit::singleline(&mut view.edit_text).desired_width(30.0).font(egui::TextStyle::Monospace));
+                        if view.focus_edit {
+                            resp.request_focus();
+                            view.focus_edit = false;
+                        }
+                        if entered(&resp, ui) { view.commit_edit(); }
+                    }
+                    None => { ui.label("Click a byte to edit it."); }
+                }
+                if !view.status.is_empty() {
+                    ui.separator();
+                    ui.label(&view.status);
+                }
+            });
+            ui.separator();
+
+            // Hex dump
+            let base = view.region.base_address() as usize;
+            let width = if base + view.data.len() > 0x10000 { 6 } else { 4 };
+            let row_height = ui.text_style_height(&egui::TextStyle::Monospace) + ui.spacing().item_spacing.y;
+            let rows = view.data.len().div_ceil(16);
+            let mut area = egui::ScrollArea::vertical().max_height(400.0).auto_shrink([false, true]);
+            if let Some(off) = view.scroll_to.take() {
+                area = area.vertical_scroll_offset((off / 16) as f32 * row_height);
+            }
+            let changed_color = egui::Color32::from_rgb(255, 96, 96);
+            let selected_bg = ui.visuals().selection.bg_fill;
+            let mut clicked = None;
+            area.show_rows(ui, row_height, rows, |ui, range| {
+                for row in range {
+                    let start = row * 16;
+                    let bytes = &view.data[start..(start + 16).min(view.data.len())];
+                    ui.horizontal(|ui| {
+                        ui.spacing_mut().item_spacing.x = 5.0;
+                        ui.monospace(format!("{:0width$X}", base + start));
+                        ui.add_space(6.0);
+                        for (i, &b) in bytes.iter().enumerate() {
+                            let off = start + i;
+                            let mut text = egui::RichText::new(format!("{b:02X}")).monospace();
+                            if view.is_changed(off) { text = text.color(changed_color); }
+                            if view.selected == Some(off) { text = text.background_color(selected_bg); }
+                            if ui.add(egui::Label::new(text).sense(egui::Sense::click())).clicked() {
+                                clicked = Some(off);
+                            }
+                        }
+                        ui.add_space(6.0);
+                        let ascii: String = bytes.iter()
+                            .map(|&b| if (0x20..0x7F).contains(&b) { b as char } else { '.' })
+                            .collect();
+                        ui.monospace(ascii);
+                    });
+                }
+            });
+            if let Some(off) = clicked { view.select(off); }
+        });
+    view.open = open;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlight_clears_once_memory_stops_changing() {
+        let mut view = MemoryView::default();
+        view.refresh(vec![0; 4]);
+        assert!(!view.is_changed(1), "região nova começa sem destaque");
+        view.refresh(vec![0, 7, 0, 0]);
+        assert!(view.is_changed(1) && !view.is_changed(0));
+        view.refresh(vec![0, 7, 0, 0]);
+        assert!(!view.is_changed(1), "sem mudança no frame seguinte, o destaque some");
+    }
+
+    #[test]
+    fn resized_region_drops_a_stale_selection() {
+        let mut view = MemoryView::default();
+        view.refresh(vec![0; 8192]);
+        view.select(5000);
+        view.refresh(vec![0; 1024]); // RAM de 1KB do SG-1000
+        view.edit_text = "12".to_string();
+        view.commit_edit();
+        assert_eq!(view.selected, None);
+        assert!(view.writes.is_empty(), "nada é escrito fora da região nova");
+    }
+}
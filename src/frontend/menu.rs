@@ -1,16 +1,44 @@
 use winit::event_loop::EventLoopProxy;
 
+use crate::frontend::speed::OffSpeedAudio;
+use crate::frontend::vdp_ui::VdpViewer;
+use crate::platform::{ConsoleRegion, MediaSlot, Platform, VideoStandard};
+
 #[derive(Debug, Clone)]
 pub enum MenuAction {
-    OpenRom,
-    RomSelected(std::path::PathBuf),
+    /// Pick a file to plug into the given slot.
+    OpenRom(MediaSlot),
+    RomSelected(std::path::PathBuf, MediaSlot),
     Reset,
     Stop,
     Quit,
     SaveState,
     LoadState,
     SetSlot(usize),
+    RecordMovie { from_power_on: bool },
+    PlayMovie,
+    StopMovie,
+    TogglePause,
+    FrameAdvance,
+    ToggleFastForward,
+    SetFastForwardSpeed(u32),
+    ToggleSlowMotion,
+    SetSlowMotionSpeed(u32),
+    SetOffSpeedAudio(OffSpeedAudio),
     ToggleFm,
+    ToggleBios,
+    ToggleVsync,
+    ToggleRewind,
+    /// `None` goes back to detecting the platform from the ROM.
+    SetPlatform(Option<Platform>),
+    SetVideoStandard(VideoStandard),
+    SetConsoleRegion(ConsoleRegion),
+    ShowCheats,
+    ShowRomInfo,
+    ShowDebugger,
+    ShowMemoryViewer,
+    ShowRamSearch,
+    ShowVdpViewer(VdpViewer),
     ShowControls,
     ShowAbout,
 }
@@ -30,20 +58,28 @@ impl AppMenu {
     #[cfg(not(target_os = "linux"))]
     fn build_native(proxy: EventLoopProxy<MenuAction>) {
         use muda::{Menu, Submenu, MenuItem, PredefinedMenuItem, MenuEvent};
+        use crate::frontend::speed::{FF_MULTIPLIERS, SLOW_DIVISORS};
 
         let menu = Menu::new();
 
         // Emulator submenu
         let open_rom   = MenuItem::new("Open ROM…", true, None);
+        let open_card  = MenuItem::new("Open Sega Card…", true, None);
         let reset      = MenuItem::new("Reset",     true, None);
         let stop       = MenuItem::new("Stop",      true, None);
+        let cheats     = MenuItem::new("Cheats…",   true, None);
+        let rom_info   = MenuItem::new("ROM Info…", true, None);
         let quit       = MenuItem::new("Quit",      true, None);
         let emulator   = Submenu::with_items("Emulator", true, &[
             &open_rom,
+            &open_card,
             &PredefinedMenuItem::separator(),
             &reset,
             &stop,
             &PredefinedMenuItem::separator(),
+            &cheats,
+            &rom_info,
+            &PredefinedMenuItem::separator(),
             &quit,
         ]).unwrap();
         menu.append(&emulator).unwrap();
@@ -65,13 +101,113 @@ impl AppMenu {
         ]).unwrap();
         menu.append(&state_sub).unwrap();
 
+        // Movie submenu
+        let rec_power = MenuItem::new("Record from Power-On", true, None);
+        let rec_state = MenuItem::new("Record from Current State", true, None);
+        let play_movie = MenuItem::new("Play", true, None);
+        let stop_movie = MenuItem::new("Stop", true, None);
+        let movie_sub = Submenu::with_items("Movie", true, &[
+            &rec_power as &dyn muda::IsMenuItem,
+            &rec_state,
+            &PredefinedMenuItem::separator(),
+            &play_movie,
+            &stop_movie,
+        ]).unwrap();
+        menu.append(&movie_sub).unwrap();
+
+        // Speed submenu
+        let pause   = MenuItem::new("Pause / Resume  [F9]", true, None);
+        let advance = MenuItem::new("Frame Advance  [F10]", true, None);
+        let ff      = MenuItem::new("Toggle Fast-Forward  [hold Tab]", true, None);
+        let ff_items: Vec<MenuItem> = FF_MULTIPLIERS.iter().map(|m| {
+            MenuItem::new(format!("×{m}"), true, None)
+        }).collect();
+        let ff_sub_items: Vec<&dyn muda::IsMenuItem> =
+            ff_items.iter().map(|i| i as &dyn muda::IsMenuItem).collect();
+        let ff_sub = Submenu::with_items("Fast-Forward Speed", true, &ff_sub_items).unwrap();
+        let slow    = MenuItem::new("Toggle Slow Motion  [F8]", true, None);
+        let slow_items: Vec<MenuItem> = SLOW_DIVISORS.iter().map(|d| {
+            MenuItem::new(format!("1/{d}"), true, None)
+        }).collect();
+        let slow_sub_items: Vec<&dyn muda::IsMenuItem> =
+            slow_items.iter().map(|i| i as &dyn muda::IsMenuItem).collect();
+        let slow_sub = Submenu::with_items("Slow Motion Speed", true, &slow_sub_items).unwrap();
+        let audio_mute    = MenuItem::new("Mute", true, None);
+        let audio_repeat = MenuItem::new("Repeat/Drop Frames", true, None);
+        let audio_sub = Submenu::with_items("Off-Speed Audio", true, &[
+            &audio_mute as &dyn muda::IsMenuItem,
+            &audio_repeat,
+        ]).unwrap();
+        let speed_sub = Submenu::with_items("Speed", true, &[
+            &pause as &dyn muda::IsMenuItem,
+            &advance,
+            &PredefinedMenuItem::separator(),
+            &ff,
+            &ff_sub,
+            &PredefinedMenuItem::separator(),
+            &slow,
+            &slow_sub,
+            &PredefinedMenuItem::separator(),
+            &audio_sub,
+        ]).unwrap();
+        menu.append(&speed_sub).unwrap();
+
+        // Debug submenu
+        let debugger = MenuItem::new("Debugger…", true, None);
+        let memory   = MenuItem::new("Memory Viewer…", true, None);
+        let ram_search = MenuItem::new("RAM Search…", true, None);
+        let vdp_tiles   = MenuItem::new("Tile Viewer…", true, None);
+        let vdp_tilemap = MenuItem::new("Tilemap Viewer…", true, None);
+        let vdp_sprites = MenuItem::new("Sprite Viewer…", true, None);
+        let vdp_palette = MenuItem::new("Palette Viewer…", true, None);
+        let debug_sub = Submenu::with_items("Debug", true, &[
+            &debugger as &dyn muda::IsMenuItem,
+            &memory,
+            &ram_search,
+            &PredefinedMenuItem::separator(),
+            &vdp_tiles,
+            &vdp_tilemap,
+            &vdp_sprites,
+            &vdp_palette,
+        ]).unwrap();
+        menu.append(&debug_sub).unwrap();
+
         // Configuration submenu
         let controls = MenuItem::new("Controls…", true, None);
         let toggle_fm = MenuItem::new("Toggle FM Sound", true, None);
+        let toggle_vsync = MenuItem::new("Toggle Sync to VSync", true, None);
+        let toggle_rewind = MenuItem::new("Toggle Rewind  [Backspace]", true, None);
+        let platform_auto = MenuItem::new("Auto-Detect", true, None);
+        let platform_items: Vec<(MenuItem, Platform)> =
+            [Platform::MasterSystem, Platform::GameGear, Platform::Sg1000, Platform::Sc3000]
+                .into_iter().map(|p| (MenuItem::new(p.name(), true, None), p)).collect();
+        let mut platform_menu_items: Vec<&dyn muda::IsMenuItem> = vec![&platform_auto];
+        platform_menu_items.extend(platform_items.iter().map(|(i, _)| i as &dyn muda::IsMenuItem));
+        let platform_sub = Submenu::with_items("Platform", true, &platform_menu_items).unwrap();
+        let video_ntsc = MenuItem::new("NTSC (60 Hz)", true, None);
+        let video_pal  = MenuItem::new("PAL (50 Hz)", true, None);
+        let video_sub  = Submenu::with_items("Video Standard", true, &[
+            &video_ntsc as &dyn muda::IsMenuItem,
+            &video_pal,
+        ]).unwrap();
+        let region_jp     = MenuItem::new("Japan", true, None);
+        let region_export = MenuItem::new("Export", true, None);
+        let region_sub    = Submenu::with_items("Console Region", true, &[
+            &region_jp as &dyn muda::IsMenuItem,
+            &region_export,
+        ]).unwrap();
+        let toggle_bios = MenuItem::new("Toggle BIOS Boot", true, None);
         let config_sub = Submenu::with_items("Configuration", true, &[
             &controls as &dyn muda::IsMenuItem,
             &PredefinedMenuItem::separator(),
             &toggle_fm,
+            &platform_sub,
+            &video_sub,
+            &region_sub,
+            &toggle_bios,
+            &PredefinedMenuItem::separator(),
+            &toggle_vsync,
+            &toggle_rewind,
         ]).unwrap();
         menu.append(&config_sub).unwrap();
 
@@ -84,31 +220,114 @@ impl AppMenu {
 
         // Capture IDs for dispatch
         let open_id    = open_rom.id().clone();
+        let card_id    = open_card.id().clone();
         let reset_id   = reset.id().clone();
         let stop_id    = stop.id().clone();
+        let cheats_id  = cheats.id().clone();
+        let info_id    = rom_info.id().clone();
         let quit_id    = quit.id().clone();
         let save_id    = save_state.id().clone();
         let load_id    = load_state.id().clone();
         let slot_ids: Vec<_> = slot_items.iter().map(|i| i.id().clone()).collect();
+        let rec_power_id = rec_power.id().clone();
+        let rec_state_id = rec_state.id().clone();
+        let play_id    = play_movie.id().clone();
+        let stop_mv_id = stop_movie.id().clone();
+        let pause_id   = pause.id().clone();
+        let advance_id = advance.id().clone();
+        let ff_id      = ff.id().clone();
+        let ff_ids: Vec<_> = ff_items.iter().map(|i| i.id().clone()).collect();
+        let slow_id    = slow.id().clone();
+        let slow_ids: Vec<_> = slow_items.iter().map(|i| i.id().clone()).collect();
+        let mute_id    = audio_mute.id().clone();
+        let repeat_id = audio_repeat.id().clone();
+        let debugger_id = debugger.id().clone();
+        let memory_id  = memory.id().clone();
+        let search_id  = ram_search.id().clone();
+        let vdp_ids = [
+            (vdp_tiles.id().clone(), VdpViewer::Tiles),
+            (vdp_tilemap.id().clone(), VdpViewer::Tilemap),
+            (vdp_sprites.id().clone(), VdpViewer::Sprites),
+            (vdp_palette.id().clone(), VdpViewer::Palette),
+        ];
         let fm_id      = toggle_fm.id().clone();
+        let bios_id    = toggle_bios.id().clone();
+        let vsync_id   = toggle_vsync.id().clone();
+        let rewind_id  = toggle_rewind.id().clone();
+        let auto_id    = platform_auto.id().clone();
+        let platform_ids: Vec<_> = platform_items.iter().map(|(i, p)| (i.id().clone(), *p)).collect();
+        let ntsc_id    = video_ntsc.id().clone();
+        let pal_id     = video_pal.id().clone();
+        let jp_id      = region_jp.id().clone();
+        let export_id  = region_export.id().clone();
         let ctrl_id    = controls.id().clone();
         let about_id   = about_item.id().clone();
 
         MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
             let action = if event.id == open_id {
-                Some(MenuAction::OpenRom)
+                Some(MenuAction::OpenRom(MediaSlot::Cartridge))
+            } else if event.id == card_id {
+                Some(MenuAction::OpenRom(MediaSlot::Card))
             } else if event.id == reset_id {
                 Some(MenuAction::Reset)
             } else if event.id == stop_id {
                 Some(MenuAction::Stop)
+            } else if event.id == cheats_id {
+                Some(MenuAction::ShowCheats)
+            } else if event.id == info_id {
+                Some(MenuAction::ShowRomInfo)
             } else if event.id == quit_id {
                 Some(MenuAction::Quit)
             } else if event.id == save_id {
                 Some(MenuAction::SaveState)
             } else if event.id == load_id {
                 Some(MenuAction::LoadState)
+            } else if event.id == rec_power_id {
+                Some(MenuAction::RecordMovie { from_power_on: true })
+            } else if event.id == rec_state_id {
+                Some(MenuAction::RecordMovie { from_power_on: false })
+            } else if event.id == play_id {
+                Some(MenuAction::PlayMovie)
+            } else if event.id == stop_mv_id {
+                Some(MenuAction::StopMovie)
+            } else if event.id == pause_id {
+                Some(MenuAction::TogglePause)
+            } else if event.id == advance_id {
+                Some(MenuAction::FrameAdvance)
+            } else if event.id == ff_id {
+                Some(MenuAction::ToggleFastForward)
+            } else if event.id == slow_id {
+                Some(MenuAction::ToggleSlowMotion)
+            } else if event.id == mute_id {
+                Some(MenuAction::SetOffSpeedAudio(OffSpeedAudio::Mute))
+            } else if event.id == repeat_id {
+                Some(MenuAction::SetOffSpeedAudio(OffSpeedAudio::RepeatDrop))
+            } else if event.id == debugger_id {
+                Some(MenuAction::ShowDebugger)
+            } else if event.id == memory_id {
+                Some(MenuAction::ShowMemoryViewer)
+            } else if event.id == search_id {
+                Some(MenuAction::ShowRamSearch)
+            } else if let Some((_, viewer)) = vdp_ids.iter().find(|(id, _)| event.id == *id) {
+                Some(MenuAction::ShowVdpViewer(*viewer))
             } else if event.id == fm_id {
                 Some(MenuAction::ToggleFm)
+            } else if event.id == bios_id {
+                Some(MenuAction::ToggleBios)
+            } else if event.id == vsync_id {
+                Some(MenuAction::ToggleVsync)
+            } else if event.id == rewind_id {
+                Some(MenuAction::ToggleRewind)
+            } else if event.id == auto_id {
+                Some(MenuAction::SetPlatform(None))
+            } else if event.id == ntsc_id {
+                Some(MenuAction::SetVideoStandard(VideoStandard::Ntsc))
+            } else if event.id == pal_id {
+                Some(MenuAction::SetVideoStandard(VideoStandard::Pal))
+            } else if event.id == jp_id {
+                Some(MenuAction::SetConsoleRegion(ConsoleRegion::Japan))
+            } else if event.id == export_id {
+                Some(MenuAction::SetConsoleRegion(ConsoleRegion::Export))
             } else if event.id == ctrl_id {
                 Some(MenuAction::ShowControls)
             } else if event.id == about_id {
@@ -117,6 +336,15 @@ impl AppMenu {
                 slot_ids.iter().enumerate().find_map(|(i, id)| {
                     if event.id == *id { Some(MenuAction::SetSlot(i + 1)) } else { None }
                 })
+                .or_else(|| ff_ids.iter().zip(FF_MULTIPLIERS).find_map(|(id, m)| {
+                    (event.id == *id).then_some(MenuAction::SetFastForwardSpeed(m))
+                }))
+                .or_else(|| slow_ids.iter().zip(SLOW_DIVISORS).find_map(|(id, d)| {
+                    (event.id == *id).then_some(MenuAction::SetSlowMotionSpeed(d))
+                }))
+                .or_else(|| platform_ids.iter().find_map(|(id, p)| {
+                    (event.id == *id).then_some(MenuAction::SetPlatform(Some(*p)))
+                }))
             };
             if let Some(a) = action {
                 let _ = proxy.send_event(a);
@@ -0,0 +1,307 @@
+//! RAM Search window: snapshot work RAM or cart RAM, narrow the candidates
+//! frame by frame, then turn results into cheats, watches or watchpoints.
+
+use crate::debugger::{AddressSpace, Debugger, Watchpoint};
+use crate::{ram_cheats, Cheat, Comparison, Emulator, MemoryRegion, Operand, RamSearch, ValueFormat, ValueSize};
+
+/// Most result rows listed; the count is always shown.
+const MAX_RESULTS: usize = 1000;
+
+const COMPARISONS: [&str; 5] = ["Equal to", "Not equal to", "Greater than", "Less than", "Changed by"];
+
+enum SearchAction {
+    Start,
+    Filter(Comparison, Operand),
+    AddCheats(Vec<Cheat>),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Watch {
+    region: MemoryRegion,
+    offset: usize,
+    format: ValueFormat,
+}
+
+pub struct RamSearchView {
+    pub open:   bool,
+    region:     MemoryRegion,
+    format:     ValueFormat,
+    search:     Option<RamSearch>,
+    /// Latest contents of each searchable region.
+    snapshots:  Vec<(MemoryRegion, Vec<u8>)>,
+    comparison: usize,
+    against_value: bool,
+    value_text: String,
+    watches:    Vec<Watch>,
+    pending:    Vec<SearchAction>,
+    status:     String,
+}
+
+impl Default for RamSearchView {
+    fn default() -> Self {
+        Self {
+            open: false,
+            region: MemoryRegion::Ram,
+            format: ValueFormat::default(),
+            search: None,
+            snapshots: Vec::new(),
+            comparison: 0,
+            against_value: false,
+            value_text: String::new(),
+            watches: Vec::new(),
+            pending: Vec::new(),
+            status: String::new(),
+        }
+    }
+}
+
+impl RamSearchView {
+    /// Runs queued searches and cheat additions, then re-reads RAM.
+    /// Returns true when cheats were added and should be saved.
+    pub fn update(&mut self, emu: Option<&mut Emulator>) -> bool {
+        let Some(emu) = emu else {
+            self.snapshots.clear();
+            self.search = None;
+            self.watches.clear();
+            self.pending.clear();
+            return false;
+        };
+        self.snapshots = emu.memory_regions().into_iter()
+            .filter(|r| matches!(r, MemoryRegion::Ram | MemoryRegion::CartRam))
+            .map(|r| (r, emu.read_region(r)))
+            .collect();
+        if !self.snapshots.iter().any(|(r, _)| *r == self.region) {
+            self.region = MemoryRegion::Ram;
+            self.search = None;
+        }
+        let mut cheats_added = false;
+        for action in std::mem::take(&mut self.pending) {
+            match action {
+                SearchAction::Start => {
+                    self.search = Some(RamSearch::new(self.region, self.format, self.data(self.region)));
+                    self.status.clear();
+                }
+                SearchAction::Filter(comparison, operand) => {
+                    let Some(mut search) = self.search.take() else { continue };
+                    search.filter(self.data(search.region()), comparison, operand);
+                    self.search = Some(search);
+                }
+                SearchAction::AddCheats(new) => {
+                    self.status = format!("Added {} cheat code(s)", new.len());
+                    let mut cheats = emu.cheats().to_vec();
+                    cheats.extend(new);
+                    emu.set_cheats(cheats);
+                    cheats_added = true;
+                }
+            }
+        }
+        cheats_added
+    }
+
+    fn data(&self, region: MemoryRegion) -> &[u8] {
+        self.snapshots.iter().find(|(r, _)| *r == region).map_or(&[], |(_, d)| d)
+    }
+
+    fn set_format(&mut self, format: ValueFormat) {
+        self.format = format;
+        if let Some(ref mut s) = self.search { s.set_format(format); }
+    }
+
+    fn queue_filter(&mut self) {
+        let changed_by = self.comparison == 4;
+        let value = if changed_by || self.against_value {
+            match parse_number(&self.value_text) {
+                Some(v) => Some(v),
+                None => {
+                    self.status = format!("Invalid number: {}", self.value_text.trim());
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+        let (comparison, operand) = match value {
+            Some(n) if changed_by => (Comparison::ChangedBy(n), Operand::Previous),
+            Some(v) => (comparison_at(self.comparison), Operand::Value(v)),
+            None => (comparison_at(self.comparison), Operand::Previous),
+        };
+        self.status.clear();
+        self.pending.push(SearchAction::Filter(comparison, operand));
+    }
+}
+
+fn comparison_at(i: usize) -> Comparison {
+    match i {
+        0 => Comparison::Equal,
+        1 => Comparison::NotEqual,
+        2 => Comparison::Greater,
+        _ => Comparison::Less,
+    }
+}
+
+/// Decimal, or hexadecimal with `$` / `0x`; may be negative.
+fn parse_number(text: &str) -> Option<i64> {
+    let text = text.trim();
+    let (neg, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let value = match text.strip_prefix('$').or_else(|| text.strip_prefix("0x")) {
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None => text.parse().ok()?,
+    };
+    Some(if neg { -value } else { value })
+}
+
+fn address_label(region: MemoryRegion, offset: usize) -> String {
+    match region {
+        MemoryRegion::Ram => format!("{:04X}", 0xC000 + offset),
+        _ => format!("SRAM {:04X}", offset),
+    }
+}
+
+/// Write watchpoint covering a work-RAM value, for the debugger.
+fn watchpoint(offset: usize, format: ValueFormat) -> Watchpoint {
+    let start = 0xC000 + offset as u16;
+    Watchpoint {
+        space: AddressSpace::Memory,
+        start,
+        end: start + format.byte_len() as u16 - 1,
+        read: false,
+        write: true,
+    }
+}
+
+pub fn draw_ram_search(ctx: &egui::Context, view: &mut RamSearchView, dbg: &mut Debugger) {
+    let mut open = view.open;
+    egui::Window::new("RAM Search")
+        .open(&mut open)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            if view.snapshots.is_empty() {
+                ui.label("No ROM loaded.");
+                return;
+            }
+
+            // Region and value format
+            ui.horizontal(|ui| {
+                let mut region = view.region;
+                egui::ComboBox::from_id_salt("ram_search_region")
+                    .selected_text(region.name())
+                    .show_ui(ui, |ui| {
+                        for (r, _) in &view.snapshots {
+                            ui.selectable_value(&mut region, *r, r.name());
+                        }
+                    });
+                if region != view.region {
+                    view.region = region;
+                    view.search = None;
+                }
+                let mut f = view.format;
+                ui.radio_value(&mut f.size, ValueSize::Byte, "8-bit");
+                ui.radio_value(&mut f.size, ValueSize::Word, "16-bit");
+                ui.add_enabled_ui(f.size == ValueSize::Word, |ui| {
+                    egui::ComboBox::from_id_salt("ram_search_endian")
+                        .selected_text(if f.big_endian { "Big-endian" } else { "Little-endian" })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut f.big_endian, false, "Little-endian");
+                            ui.selectable_value(&mut f.big_endian, true, "Big-endian");
+                        });
+                });
+                ui.checkbox(&mut f.signed, "Signed");
+                if f != view.format { view.set_format(f); }
+            });
+
+            // Comparison
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("ram_search_cmp")
+                    .selected_text(COMPARISONS[view.comparison])
+                    .show_ui(ui, |ui| {
+                        for (i, name) in COMPARISONS.iter().enumerate() {
+                            ui.selectable_value(&mut view.comparison, i, *name);
+                        }
+                    });
+                if view.comparison == 4 {
+                    ui.label("N =");
+                } else {
+                    ui.radio_value(&mut view.against_value, false, "previous");
+                    ui.radio_value(&mut view.against_value, true, "value");
+                }
+                let enabled = view.comparison == 4 || view.against_value;
+                ui.add_enabled(enabled, egui::TextEdit::singleline(&mut view.value_text).desired_width(60.0));
+            });
+            ui.horizontal(|ui| {
+                let label = if view.search.is_some() { "Restart" } else { "Start" };
+                if ui.button(label).on_hover_text("Snapshot the region; every address is a candidate").clicked() {
+                    view.pending.push(SearchAction::Start);
+                }
+                if ui.add_enabled(view.search.is_some(), egui::Button::new("Filter")).clicked() {
+                    view.queue_filter();
+                }
+                if let Some(ref s) = view.search {
+                    ui.label(format!("{} candidates", s.candidates().len()));
+                }
+                if !view.status.is_empty() { ui.label(&view.status); }
+            });
+            ui.separator();
+
+            // Results
+            if let Some(ref search) = view.search {
+                let data = view.data(search.region());
+                let format = search.format();
+                let shown = &search.candidates()[..search.candidates().len().min(MAX_RESULTS)];
+                let row_height = ui.text_style_height(&egui::TextStyle::Monospace) + ui.spacing().item_spacing.y + 4.0;
+                let mut actions = Vec::new();
+                egui::ScrollArea::vertical().id_salt("ram_search_results").max_height(240.0).show_rows(ui, row_height, shown.len(), |ui, range| {
+                    for &offset in &shown[range] {
+                        let current = format.read(data, offset).unwrap_or(0);
+                        let previous = search.previous(offset).unwrap_or(0);
+                        ui.horizontal(|ui| {
+                            ui.monospace(format!("{:<10} {current:>6} (prev {previous:>6})", address_label(search.region(), offset)));
+                            if ui.small_button("Cheat").on_hover_text("Freeze at the current value").clicked() {
+                                let name = format!("{} = {current}", address_label(search.region(), offset));
+                                actions.push((offset, Some(ram_cheats(search.region(), offset, format, current, &name))));
+                            }
+                            if ui.small_button("Watch").clicked() { actions.push((offset, None)); }
+                        });
+                    }
+                });
+                let region = search.region();
+                for (offset, cheat) in actions {
+                    match cheat {
+                        Some(Ok(cheats)) => view.pending.push(SearchAction::AddCheats(cheats)),
+                        Some(Err(e)) => view.status = e,
+                        None => {
+                            let watch = Watch { region, offset, format };
+                            if !view.watches.contains(&watch) { view.watches.push(watch); }
+                        }
+                    }
+                }
+            } else {
+                ui.label("Press Start to snapshot memory, let the game run, then filter.");
+            }
+
+            // Watches
+            if !view.watches.is_empty() {
+                ui.separator();
+                ui.label("Watches");
+                let mut remove = None;
+                for (i, w) in view.watches.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let value = w.format.read(view.data(w.region), w.offset)
+                            .map_or("--".to_string(), |v| v.to_string());
+                        ui.monospace(format!("{:<10} {value:>6}", address_label(w.region, w.offset)));
+                        if w.region == MemoryRegion::Ram
+                            && ui.small_button("Break on write").on_hover_text("Add a debugger watchpoint").clicked()
+                        {
+                            let wp = watchpoint(w.offset, w.format);
+                            if !dbg.watchpoints.contains(&wp) { dbg.watchpoints.push(wp); }
+                        }
+                        if ui.small_button("✖").clicked() { remove = Some(i); }
+                    });
+                }
+                if let Some(i) = remove { view.watches.remove(i); }
+            }
+        });
+    view.open = open;
+}
@@ -0,0 +1,85 @@
+//! ROM Info window: CRC32, `TMR SEGA` and Codemasters headers, checksum
+//! check, and the mapper and save hardware the game was set up with.
+
+use crate::{Emulator, MapperKind, RomInfo, SaveMemory};
+
+#[derive(Default)]
+pub struct RomInfoView {
+    pub open: bool,
+    info:     Option<RomInfo>,
+    title:    Option<String>,
+    mapper:   String,
+    save:     String,
+}
+
+impl RomInfoView {
+    /// Re-reads the ROM when a different one has been loaded.
+    pub fn update(&mut self, emu: Option<&Emulator>) {
+        let Some(emu) = emu else { self.info = None; return; };
+        if self.info.as_ref().is_some_and(|i| i.crc32 == emu.rom_crc32()) { return; }
+        self.info = Some(emu.rom_info());
+        self.title = emu.game_info().map(|g| g.title.clone()).filter(|t| !t.is_empty());
+        self.mapper = emu.mapper().name().to_string();
+        self.save = save_hardware(emu);
+    }
+}
+
+fn save_hardware(emu: &Emulator) -> String {
+    if emu.has_eeprom() {
+        return "93C46 EEPROM".to_string();
+    }
+    match (emu.game_info().and_then(|g| g.save), emu.mapper()) {
+        (Some(SaveMemory::Sram(size)), _) => format!("Battery SRAM, {}KB", size / 1024),
+        (_, MapperKind::Sega)        => "SRAM if the game enables it (up to 16KB)".to_string(),
+        (_, MapperKind::Codemasters) => "8KB RAM if the game enables it".to_string(),
+        _ => "None".to_string(),
+    }
+}
+
+pub fn draw_rom_info(ctx: &egui::Context, view: &mut RomInfoView) {
+    let mut open = view.open;
+    egui::Window::new("ROM Info")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let Some(ref info) = view.info else {
+                ui.label("No ROM loaded.");
+                return;
+            };
+            egui::Grid::new("rom_info").num_columns(2).striped(true).show(ui, |ui| {
+                let mut row = |name: &str, value: String| {
+                    ui.label(name);
+                    ui.monospace(value);
+                    ui.end_row();
+                };
+                if let Some(ref title) = view.title {
+                    row("Title", title.clone());
+                }
+                row("Size", format!("{}KB ({} bytes)", info.size / 1024, info.size));
+                row("CRC32", format!("{:08X}", info.crc32));
+                row("Mapper", view.mapper.clone());
+                row("Save", view.save.clone());
+                match info.header {
+                    Some(h) => {
+                        row("Header", format!("TMR SEGA at ${:04X}", h.offset));
+                        row("Product code", h.product_code.to_string());
+                        row("Version", h.version.to_string());
+                        row("Region", h.region.name());
+                        row("Declared size", h.rom_size.map_or("Unknown".to_string(), |s| format!("{}KB", s / 1024)));
+                        let check = match (info.checksum_ok(), info.computed_checksum) {
+                            (Some(true), _) => "OK".to_string(),
+                            (Some(false), Some(sum)) => format!("Mismatch (computed {sum:04X})"),
+                            _ => "Not checked".to_string(),
+                        };
+                        row("Checksum", format!("{:04X} · {check}", h.checksum));
+                    }
+                    None => row("Header", "None".to_string()),
+                }
+                if let Some(cm) = info.codemasters {
+                    row("Codemasters", format!("{} banks, built {}", cm.banks, cm.date()));
+                    row("CM checksum", format!("{:04X}", cm.checksum));
+                }
+            });
+        });
+    view.open = open;
+}
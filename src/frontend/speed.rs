@@ -0,0 +1,193 @@
+//! Emulation speed: pause, frame advance, fast-forward and slow motion.
+//!
+//! The render loop hands out "ticks" — one console frame's worth of output
+//! time each (paced by audio, vsync or the wall clock). The speed setting
+//! decides how many emulated frames fill a tick and what audio is queued for
+//! it, so pacing keeps working unchanged at any speed.
+
+/// What to play while running faster or slower than real time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OffSpeedAudio {
+    Mute,
+    /// One frame of audio per tick, pitch unchanged: fast-forward drops all
+    /// but the last frame of each batch, slow motion repeats the last frame.
+    /// This is not a time-stretch; each block fades in from where the previous
+    /// one ended so the seams don't click.
+    RepeatDrop,
+}
+
+/// Stereo pairs over which a repeated or dropped-to block fades in.
+const SEAM_PAIRS: usize = 32;
+
+pub const FF_MULTIPLIERS: [u32; 4] = [2, 3, 4, 8];
+pub const SLOW_DIVISORS: [u32; 2] = [2, 4];
+
+pub struct SpeedControl {
+    pub paused:        bool,
+    /// Fast-forward toggled from the menu.
+    pub fast_forward:  bool,
+    /// Fast-forward hotkey currently held.
+    pub ff_held:       bool,
+    pub ff_multiplier: u32,
+    pub slow_motion:   bool,
+    pub slow_divisor:  u32,
+    pub audio:         OffSpeedAudio,
+    advance:    bool,
+    slow_phase: u32,
+}
+
+impl Default for SpeedControl {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            fast_forward: false,
+            ff_held: false,
+            ff_multiplier: 4,
+            slow_motion: false,
+            slow_divisor: 2,
+            audio: OffSpeedAudio::Mute,
+            advance: false,
+            slow_phase: 0,
+        }
+    }
+}
+
+impl SpeedControl {
+    pub fn is_fast_forwarding(&self) -> bool {
+        !self.paused && (self.fast_forward || self.ff_held)
+    }
+
+    fn is_off_speed(&self) -> bool {
+        self.is_fast_forwarding() || (!self.paused && self.slow_motion)
+    }
+
+    /// Pauses (if needed) and runs exactly one frame on the next tick.
+    pub fn request_frame_advance(&mut self) {
+        self.paused = true;
+        self.advance = true;
+    }
+
+    /// Emulated frames to run during one tick.
+    pub fn frames_for_tick(&mut self) -> u32 {
+        if self.paused {
+            return std::mem::take(&mut self.advance) as u32;
+        }
+        if self.is_fast_forwarding() {
+            return self.ff_multiplier;
+        }
+        if self.slow_motion {
+            self.slow_phase = (self.slow_phase + 1) % self.slow_divisor.max(1);
+            return (self.slow_phase == 0) as u32;
+        }
+        1
+    }
+
+    /// Audio to queue for one tick. `frame` is the last emulated frame's audio
+    /// (`None` if no frame ran); `last` remembers it for slow-motion repeats.
+    pub fn tick_audio(&self, frame: Option<Vec<f32>>, last: &mut Vec<f32>) -> Vec<f32> {
+        let prev_end = last.rchunks_exact(2).next().map(|p| [p[0], p[1]]);
+        match frame {
+            Some(s) => *last = s,
+            None if self.paused || !self.slow_motion => return Vec::new(),
+            None => {}
+        }
+        if !self.is_off_speed() {
+            return last.clone();
+        }
+        match (self.audio, prev_end) {
+            (OffSpeedAudio::Mute, _) => vec![0.0; last.len()],
+            (OffSpeedAudio::RepeatDrop, Some(end)) => fade_in_from(end, last),
+            (OffSpeedAudio::RepeatDrop, None) => last.clone(),
+        }
+    }
+}
+
+/// `block` with its first pairs cross-faded from the stereo sample `end`.
+fn fade_in_from(end: [f32; 2], block: &[f32]) -> Vec<f32> {
+    let mut out = block.to_vec();
+    let pairs = (out.len() / 2).min(SEAM_PAIRS);
+    for (i, pair) in out.chunks_exact_mut(2).take(pairs).enumerate() {
+        let t = (i + 1) as f32 / (pairs + 1) as f32;
+        for (x, e) in pair.iter_mut().zip(end) {
+            *x = e + (*x - e) * t;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normal_speed_runs_one_frame_per_tick() {
+        let mut s = SpeedControl::default();
+        assert_eq!((0..10).map(|_| s.frames_for_tick()).sum::<u32>(), 10);
+    }
+
+    #[test]
+    fn paused_runs_nothing_until_frame_advance() {
+        let mut s = SpeedControl { paused: true, ..Default::default() };
+        assert_eq!(s.frames_for_tick(), 0);
+        s.request_frame_advance();
+        assert_eq!(s.frames_for_tick(), 1);
+        assert_eq!(s.frames_for_tick(), 0, "avança só um frame");
+    }
+
+    #[test]
+    fn fast_forward_runs_multiplier_frames() {
+        let mut s = SpeedControl { ff_held: true, ff_multiplier: 3, ..Default::default() };
+        assert_eq!(s.frames_for_tick(), 3);
+    }
+
+    #[test]
+    fn slow_motion_runs_one_frame_every_divisor_ticks() {
+        let mut s = SpeedControl { slow_motion: true, slow_divisor: 4, ..Default::default() };
+        assert_eq!((0..16).map(|_| s.frames_for_tick()).sum::<u32>(), 4);
+    }
+
+    #[test]
+    fn muted_fast_forward_queues_silence_of_same_length() {
+        let s = SpeedControl { fast_forward: true, ..Default::default() };
+        let mut last = Vec::new();
+        let out = s.tick_audio(Some(vec![0.5; 1470]), &mut last);
+        assert_eq!(out.len(), 1470);
+        assert!(out.iter().all(|&x| x == 0.0));
+    }
+
+    #[test]
+    fn repeated_slow_motion_audio_is_full_length_and_continuous() {
+        let s = SpeedControl { slow_motion: true, audio: OffSpeedAudio::RepeatDrop, ..Default::default() };
+        // Rampa de -0.5 a 0.5 em ambos os canais: repetir crua dá um salto de 1.0.
+        let frame: Vec<f32> = (0..735).flat_map(|i| [i as f32 / 734.0 - 0.5; 2]).collect();
+        let mut last = Vec::new();
+        let mut out = s.tick_audio(Some(frame.clone()), &mut last);
+        assert_eq!(out, frame, "primeiro bloco sai intacto");
+        for _ in 0..3 {
+            let tick = s.tick_audio(None, &mut last);
+            assert_eq!(tick.len(), frame.len(), "tick sem frame repete um frame inteiro");
+            out.extend(tick);
+        }
+        assert_eq!(out.len(), 4 * frame.len());
+        let max_step = out.windows(2).map(|w| (w[1] - w[0]).abs()).fold(0.0, f32::max);
+        assert!(max_step < 0.05, "emenda sem estalo: salto de {max_step}");
+        assert_eq!(&out[out.len() - 100..], &frame[frame.len() - 100..], "fim do bloco não muda");
+    }
+
+    #[test]
+    fn fast_forward_drops_to_last_frame_without_a_click() {
+        let s = SpeedControl { fast_forward: true, audio: OffSpeedAudio::RepeatDrop, ..Default::default() };
+        let mut last = vec![0.5; 1470];
+        let out = s.tick_audio(Some(vec![-0.5; 1470]), &mut last);
+        assert_eq!(out.len(), 1470);
+        assert!((out[0] - 0.5).abs() < 0.05, "começa de onde o bloco anterior parou");
+        assert_eq!(out[1469], -0.5);
+    }
+
+    #[test]
+    fn paused_queues_no_audio() {
+        let s = SpeedControl { paused: true, ..Default::default() };
+        let mut last = vec![0.5; 4];
+        assert!(s.tick_audio(None, &mut last).is_empty());
+    }
+}
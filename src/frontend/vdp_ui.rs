@@ -0,0 +1,288 @@
+//! VDP viewers: tiles/patterns, tilemap, sprite table and palette, decoded
+//! live from a [`VdpSnapshot`] taken after each emulated tick.
+
+use crate::vdp_view::{Image, VdpSnapshot};
+use crate::Emulator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VdpViewer {
+    Tiles,
+    Tilemap,
+    Sprites,
+    Palette,
+}
+
+#[derive(Default)]
+pub struct VdpView {
+    pub tiles:   bool,
+    pub tilemap: bool,
+    pub sprites: bool,
+    pub palette: bool,
+    /// Palette used to draw the Mode 4 tile sheet (0 = background, 1 = sprites).
+    tile_bank:   usize,
+    show_scroll: bool,
+    selected_sprite: usize,
+    snapshot:    Option<VdpSnapshot>,
+    tiles_tex:   Option<egui::TextureHandle>,
+    tilemap_tex: Option<egui::TextureHandle>,
+    sprite_tex:  Vec<egui::TextureHandle>,
+}
+
+impl VdpView {
+    pub fn open(&mut self, viewer: VdpViewer) {
+        match viewer {
+            VdpViewer::Tiles   => self.tiles = true,
+            VdpViewer::Tilemap => { self.tilemap = true; self.show_scroll = true; }
+            VdpViewer::Sprites => self.sprites = true,
+            VdpViewer::Palette => self.palette = true,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.tiles || self.tilemap || self.sprites || self.palette
+    }
+
+    pub fn update(&mut self, emu: Option<&Emulator>) {
+        self.snapshot = emu.map(Emulator::vdp_snapshot);
+    }
+}
+
+fn color32(argb: u32) -> egui::Color32 {
+    let [a, r, g, b] = argb.to_be_bytes();
+    egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+}
+
+/// Uploads `img` into `slot` (reusing the texture) and draws it at `scale`.
+fn show_image(ui: &mut egui::Ui, slot: &mut Option<egui::TextureHandle>, name: &str, img: &Image, scale: f32) -> egui::Response {
+    let color = egui::ColorImage::new([img.width, img.height], img.pixels.iter().map(|&p| color32(p)).collect());
+    let tex = match slot {
+        Some(tex) => { tex.set(color, egui::TextureOptions::NEAREST); tex }
+        None => slot.insert(ui.ctx().load_texture(name, color, egui::TextureOptions::NEAREST)),
+    };
+    let size = egui::vec2(img.width as f32, img.height as f32) * scale;
+    ui.add(egui::Image::new((tex.id(), size)).sense(egui::Sense::hover()))
+}
+
+/// Image-space pixel under the pointer.
+fn hovered_pixel(resp: &egui::Response, scale: f32) -> Option<(usize, usize)> {
+    let pos = resp.hover_pos()? - resp.rect.min;
+    (pos.x >= 0.0 && pos.y >= 0.0).then(|| ((pos.x / scale) as usize, (pos.y / scale) as usize))
+}
+
+pub fn draw_vdp_viewers(ctx: &egui::Context, view: &mut VdpView) {
+    let Some(snap) = view.snapshot.take() else {
+        for (open, title) in [(&mut view.tiles, "Tiles"), (&mut view.tilemap, "Tilemap"),
+                              (&mut view.sprites, "Sprites"), (&mut view.palette, "Palette")] {
+            egui::Window::new(title).open(open).show(ctx, |ui| { ui.label("No ROM loaded."); });
+        }
+        return;
+    };
+    if view.tiles   { draw_tiles(ctx, view, &snap); }
+    if view.tilemap { draw_tilemap(ctx, view, &snap); }
+    if view.sprites { draw_sprites(ctx, view, &snap); }
+    if view.palette { draw_palette(ctx, view, &snap); }
+    view.snapshot = Some(snap);
+}
+
+fn draw_tiles(ctx: &egui::Context, view: &mut VdpView, snap: &VdpSnapshot) {
+    const SCALE: f32 = 2.0;
+    let mut open = view.tiles;
+    egui::Window::new("Tiles").open(&mut open).resizable(false).show(ctx, |ui| {
+        let tms = snap.tms_mode();
+        if tms.is_none() {
+            ui.horizontal(|ui| {
+                ui.label("Palette:");
+                ui.radio_value(&mut view.tile_bank, 0, "Background");
+                ui.radio_value(&mut view.tile_bank, 1, "Sprites");
+            });
+        }
+        let img = match tms {
+            Some(_) => snap.tms_patterns(),
+            None => snap.tiles(view.tile_bank),
+        };
+        let resp = egui::ScrollArea::vertical().max_height(560.0)
+            .show(ui, |ui| show_image(ui, &mut view.tiles_tex, "vdp_tiles", &img, SCALE)).inner;
+        let info = hovered_pixel(&resp, SCALE).map(|(x, y)| {
+            let n = (y / 8) * 16 + x / 8;
+            match tms {
+                Some(2) => format!("Pattern {:03} (third {})", n % 256, n / 256),
+                Some(_) => format!("Pattern {n:03}"),
+                None => format!("Tile {n:03}  VRAM ${:04X}", n * 32),
+            }
+        });
+        ui.monospace(info.unwrap_or_default());
+    });
+    view.tiles = open;
+}
+
+fn draw_tilemap(ctx: &egui::Context, view: &mut VdpView, snap: &VdpSnapshot) {
+    const SCALE: f32 = 2.0;
+    let mut open = view.tilemap;
+    egui::Window::new("Tilemap").open(&mut open).resizable(false).show(ctx, |ui| {
+        let tms = snap.tms_mode();
+        if tms.is_none() {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut view.show_scroll, "Show scroll window");
+                ui.monospace(format!("Name table ${:04X}  Scroll X {:3}  Y {:3}",
+                    snap.name_table_base(), snap.registers[8], snap.registers[9]));
+            });
+        }
+        let img = match tms {
+            Some(_) => snap.tms_name_table(),
+            None => snap.name_table(),
+        };
+        let resp = show_image(ui, &mut view.tilemap_tex, "vdp_tilemap", &img, SCALE);
+
+        if view.show_scroll && tms.is_none() {
+            let painter = ui.painter_at(resp.rect);
+            for (x, y, w, h) in snap.scroll_window() {
+                let rect = egui::Rect::from_min_size(
+                    resp.rect.min + egui::vec2(x as f32, y as f32) * SCALE,
+                    egui::vec2(w as f32, h as f32) * SCALE);
+                painter.rect_stroke(rect, 0.0, egui::Stroke::new(2.0, egui::Color32::YELLOW), egui::StrokeKind::Inside);
+            }
+        }
+
+        let info = hovered_pixel(&resp, SCALE).map(|(x, y)| match tms {
+            Some(1) => {
+                let col = x.saturating_sub(8) / 6;
+                format!("Col {col:2} Row {:2}", y / 8)
+            }
+            Some(_) => {
+                let (col, row) = (x / 8, y / 8);
+                let name_base = ((snap.registers[2] & 0x0F) as usize) << 10;
+                let addr = (name_base + row * 32 + col) & 0x3FFF;
+                format!("Col {col:2} Row {row:2}  ${addr:04X}  Pattern {:03}", snap.vram()[addr])
+            }
+            None => {
+                let (col, row) = (x / 8, y / 8);
+                let e = snap.tile_entry(col, row);
+                let addr = snap.name_table_base() + (row * 32 + col) * 2;
+                format!("Col {col:2} Row {row:2}  ${addr:04X}  Tile {:03}{}{}{}{}",
+                    e.tile,
+                    if e.h_flip { "  H-flip" } else { "" },
+                    if e.v_flip { "  V-flip" } else { "" },
+                    if e.sprite_palette { "  Sprite palette" } else { "" },
+                    if e.priority { "  Priority" } else { "" })
+            }
+        });
+        ui.monospace(info.unwrap_or_default());
+    });
+    view.tilemap = open;
+}
+
+fn draw_sprites(ctx: &egui::Context, view: &mut VdpView, snap: &VdpSnapshot) {
+    const SCALE: f32 = 2.0;
+    let sprites = snap.sprites();
+    let mut open = view.sprites;
+    egui::Window::new("Sprites").open(&mut open).resizable(false).show(ctx, |ui| {
+        let r = &snap.registers;
+        let tms = snap.tms_mode().is_some();
+        let sat = if tms { ((r[5] & 0x7F) as usize) << 7 } else { ((r[5] & 0x7E) as usize) << 7 };
+        let size = match (tms, r[1] & 0x02 != 0) {
+            (true, true) => "16×16",
+            (true, false) | (false, false) => "8×8",
+            (false, true) => "8×16",
+        };
+        ui.monospace(format!("SAT ${sat:04X}  {size}{}", if r[1] & 0x01 != 0 { "  zoomed" } else { "" }));
+        ui.separator();
+
+        view.sprite_tex.truncate(sprites.len());
+        egui::ScrollArea::vertical().max_height(420.0).show(ui, |ui| {
+            egui::Grid::new("vdp_sprites").num_columns(6).striped(true).show(ui, |ui| {
+                for h in ["#", "X", "Y", if tms { "Pattern" } else { "Tile" }, if tms { "Color" } else { "" }, ""] {
+                    ui.strong(h);
+                }
+                ui.end_row();
+                for s in &sprites {
+                    let text = |t: String| {
+                        let t = egui::RichText::new(t).monospace();
+                        if s.active { t } else { t.color(egui::Color32::DARK_GRAY) }
+                    };
+                    if ui.selectable_label(view.selected_sprite == s.index, text(format!("{:2}", s.index))).clicked() {
+                        view.selected_sprite = s.index;
+                    }
+                    ui.label(text(format!("{:4}", s.x)));
+                    ui.label(text(format!("{:3}", s.y)));
+                    ui.label(text(format!("{:03}", s.tile)));
+                    ui.label(text(s.color.map(|c| format!("{c:2}")).unwrap_or_default()));
+                    let img = snap.sprite_image(s);
+                    let color = egui::ColorImage::new([img.width, img.height], img.pixels.iter().map(|&p| color32(p)).collect());
+                    if let Some(tex) = view.sprite_tex.get_mut(s.index) {
+                        tex.set(color, egui::TextureOptions::NEAREST);
+                    } else {
+                        let name = format!("vdp_sprite_{}", s.index);
+                        view.sprite_tex.push(ui.ctx().load_texture(name, color, egui::TextureOptions::NEAREST));
+                    }
+                    let tex = &view.sprite_tex[s.index];
+                    ui.image((tex.id(), egui::vec2(img.width as f32, img.height as f32) * SCALE));
+                    ui.end_row();
+                }
+            });
+        });
+
+        if let Some(s) = sprites.get(view.selected_sprite) {
+            ui.separator();
+            let addr = if tms { sat + s.index * 4 } else { sat + s.index };
+            ui.monospace(format!("Sprite {}  SAT ${addr:04X}  {}×{} at ({}, {}){}", s.index, s.width, s.height, s.x, s.y,
+                if s.active { "" } else { "  (after terminator)" }));
+        }
+    });
+    view.sprites = open;
+}
+
+fn swatch(ui: &mut egui::Ui, argb: u32, tooltip: String) {
+    let (rect, resp) = ui.allocate_exact_size(egui::vec2(22.0, 22.0), egui::Sense::hover());
+    ui.painter().rect_filled(rect, 2.0, color32(argb));
+    resp.on_hover_text(tooltip);
+}
+
+fn draw_palette(ctx: &egui::Context, view: &mut VdpView, snap: &VdpSnapshot) {
+    let mut open = view.palette;
+    egui::Window::new("Palette").open(&mut open).resizable(false).show(ctx, |ui| {
+        let palette = snap.palette();
+        match snap.tms_mode() {
+            None => {
+                for (bank, name) in [(0, "Background"), (1, "Sprites")] {
+                    ui.label(name);
+                    ui.horizontal(|ui| {
+                        ui.spacing_mut().item_spacing.x = 2.0;
+                        for (i, &c) in palette.iter().enumerate().skip(bank * 16).take(16) {
+                            let raw = snap.cram_value(i);
+                            let value = if snap.is_gg() { format!("${raw:03X}") } else { format!("${raw:02X}") };
+                            let [_, r, g, b] = c.to_be_bytes();
+                            swatch(ui, c, format!("{i:2}: {value}  RGB {r:3} {g:3} {b:3}"));
+                        }
+                    });
+                }
+                ui.label(egui::RichText::new(if snap.is_gg() { "12-bit Game Gear CRAM" } else { "6-bit Master System CRAM" })
+                    .small().color(egui::Color32::GRAY));
+            }
+            Some(mode) => {
+                ui.label("TMS9918 palette");
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 2.0;
+                    for (i, &c) in palette.iter().enumerate() {
+                        swatch(ui, c, format!("{i:2}"));
+                    }
+                });
+                if mode == 0 {
+                    ui.separator();
+                    ui.label("Colour table (foreground / background per 8 patterns)");
+                    egui::Grid::new("vdp_color_table").num_columns(8).show(ui, |ui| {
+                        for (i, (fg, bg)) in snap.tms_color_table().into_iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.spacing_mut().item_spacing.x = 1.0;
+                                let range = format!("{:03}–{:03}", i * 8, i * 8 + 7);
+                                swatch(ui, palette[fg as usize], format!("{range} fg {fg}"));
+                                swatch(ui, palette[bg as usize], format!("{range} bg {bg}"));
+                            });
+                            if i % 8 == 7 { ui.end_row(); }
+                        }
+                    });
+                }
+            }
+        }
+    });
+    view.palette = open;
+}
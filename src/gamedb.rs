@@ -0,0 +1,316 @@
+//! Per-game knowledge keyed by the CRC32 of the ROM image, for settings that
+//! can't be recognised from the cartridge contents.
+//!
+//! The database is plain text, one game per line; `#` at the start of a
+//! line or after whitespace starts a comment (so titles may contain `#`).
+//! The CRC comes first, then any `key=value` settings, then the title:
+//!
+//! ```text
+//! # crc32   settings                          title
+//! 36EBCD6D  platform=gg save=eeprom          Majors Pro Baseball
+//! A67F2A5C  mapper=4pak                      4 Pak All Action
+//! ```
+//!
+//! | key            | values                                                          |
+//! |----------------|-----------------------------------------------------------------|
+//! | `platform`     | `sms`, `gg`, `sg`, `sc`                                         |
+//! | `video`        | `ntsc`, `pal`                                                   |
+//! | `region`       | `japan`, `export`                                               |
+//! | `mapper`       | `none`, `sega`, `codemasters`, `korean`, `msx`, `nemesis`, `janggun`, `4pak` |
+//! | `save`         | `sram`, `sram:N` (N KB), `eeprom`                               |
+//! | `input`        | comma-separated `phaser`, `paddle`, `3d`, `keyboard`            |
+//! | `fm`           | `on`, `off`                                                     |
+//! | `sprite-limit` | `on`, `off`                                                     |
+//!
+//! A bundled list ships with the emulator; a user file loaded on top of it
+//! replaces entries with the same CRC.
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::mapper::MapperKind;
+use crate::platform::{ConsoleRegion, Platform, VideoStandard};
+
+const BUILTIN: &str = include_str!("gamedb.txt");
+
+/// Battery-backed memory on the cartridge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SaveMemory {
+    /// Cart SRAM of this many bytes (at most 16KB).
+    Sram(usize),
+    /// 93C46 serial EEPROM (Game Gear sports titles).
+    Eeprom,
+}
+
+/// Controller or accessory a game needs besides the standard pad.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Peripheral {
+    LightPhaser,
+    Paddle,
+    Glasses3d,
+    Keyboard,
+}
+
+impl Peripheral {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Peripheral::LightPhaser => "Light Phaser",
+            Peripheral::Paddle      => "Paddle",
+            Peripheral::Glasses3d   => "3-D Glasses",
+            Peripheral::Keyboard    => "Keyboard",
+        }
+    }
+}
+
+/// One database entry. `None` fields keep the detected or user-chosen setting.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameInfo {
+    pub crc:          u32,
+    pub title:        String,
+    pub platform:     Option<Platform>,
+    pub video:        Option<VideoStandard>,
+    pub region:       Option<ConsoleRegion>,
+    /// Mapper to use instead of the detected one.
+    pub mapper:       Option<MapperKind>,
+    pub save:         Option<SaveMemory>,
+    pub peripherals:  Vec<Peripheral>,
+    /// `Some(false)`: the game misbehaves with the FM unit, keep it hidden.
+    pub fm:           Option<bool>,
+    /// Whether to drop sprites past the per-line hardware limit.
+    pub sprite_limit: Option<bool>,
+}
+
+impl GameInfo {
+    pub fn new(crc: u32, title: &str) -> Self {
+        Self {
+            crc,
+            title: title.to_string(),
+            platform: None,
+            video: None,
+            region: None,
+            mapper: None,
+            save: None,
+            peripherals: Vec::new(),
+            fm: None,
+            sprite_limit: None,
+        }
+    }
+
+    /// Non-default settings, for the log line printed when a game is loaded.
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(p) = self.platform { parts.push(format!("{p:?}")); }
+        if let Some(v) = self.video { parts.push(format!("{v:?}")); }
+        if let Some(r) = self.region { parts.push(format!("{r:?}")); }
+        if let Some(m) = self.mapper { parts.push(format!("{} mapper", m.name())); }
+        match self.save {
+            Some(SaveMemory::Sram(size)) => parts.push(format!("{}KB SRAM", size / 1024)),
+            Some(SaveMemory::Eeprom) => parts.push("EEPROM".to_string()),
+            None => {}
+        }
+        parts.extend(self.peripherals.iter().map(|p| p.name().to_string()));
+        if let Some(fm) = self.fm { parts.push(format!("FM {}", if fm { "on" } else { "off" })); }
+        if let Some(l) = self.sprite_limit { parts.push(format!("sprite limit {}", if l { "on" } else { "off" })); }
+        parts.join(", ")
+    }
+}
+
+pub struct GameDb {
+    games: Vec<GameInfo>,
+}
+
+impl GameDb {
+    /// The database bundled with the emulator.
+    pub fn builtin() -> Self {
+        Self::parse(BUILTIN).expect("bundled game database is valid")
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let games = text.lines().enumerate()
+            .filter_map(|(n, raw)| {
+                let line = strip_comment(raw).trim();
+                (!line.is_empty()).then(|| parse_line(line).map_err(|e| format!("line {}: {e}", n + 1)))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { games })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        Self::parse(&text).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Adds `other`'s entries, replacing any with the same CRC.
+    pub fn extend(&mut self, other: GameDb) {
+        for game in other.games {
+            self.games.retain(|g| g.crc != game.crc);
+            self.games.push(game);
+        }
+    }
+
+    pub fn lookup(&self, crc: u32) -> Option<&GameInfo> {
+        self.games.iter().find(|g| g.crc == crc)
+    }
+
+    /// Looks up a ROM image by its CRC32.
+    pub fn lookup_rom(&self, rom: &[u8]) -> Option<&GameInfo> {
+        self.lookup(crate::mmu::crc32(rom))
+    }
+}
+
+/// Entry for `crc` in the bundled database, which is parsed once.
+pub(crate) fn builtin_entry(crc: u32) -> Option<&'static GameInfo> {
+    static BUILTIN_DB: OnceLock<GameDb> = OnceLock::new();
+    BUILTIN_DB.get_or_init(GameDb::builtin).lookup(crc)
+}
+
+/// `raw` up to its comment: a `#` at the start of the line or after whitespace.
+fn strip_comment(raw: &str) -> &str {
+    let at = raw.char_indices()
+        .find(|&(i, c)| c == '#' && raw[..i].chars().next_back().is_none_or(char::is_whitespace))
+        .map_or(raw.len(), |(i, _)| i);
+    &raw[..at]
+}
+
+/// First whitespace-separated token of `s` and what follows it.
+fn split_token(s: &str) -> (&str, &str) {
+    let s = s.trim_start();
+    s.split_at(s.find(char::is_whitespace).unwrap_or(s.len()))
+}
+
+fn parse_line(line: &str) -> Result<GameInfo, String> {
+    let (crc_tok, mut rest) = split_token(line);
+    let hex = crc_tok.strip_prefix("0x").unwrap_or(crc_tok);
+    let crc = u32::from_str_radix(hex, 16).map_err(|_| format!("invalid CRC32 '{crc_tok}'"))?;
+    let mut game = GameInfo::new(crc, "");
+    loop {
+        let (tok, tail) = split_token(rest);
+        let Some((key, value)) = tok.split_once('=') else {
+            game.title = rest.trim().to_string();
+            return Ok(game);
+        };
+        rest = tail;
+        let bad = || format!("invalid value '{value}' for {key}");
+        match key {
+            "platform" => game.platform = Some(Platform::from_name(value).ok_or_else(bad)?),
+            "video" => game.video = Some(match value {
+                "ntsc" => VideoStandard::Ntsc,
+                "pal"  => VideoStandard::Pal,
+                _ => return Err(bad()),
+            }),
+            "region" => game.region = Some(match value {
+                "japan"  => ConsoleRegion::Japan,
+                "export" => ConsoleRegion::Export,
+                _ => return Err(bad()),
+            }),
+            "mapper" => game.mapper = Some(match value {
+                "none"        => MapperKind::None,
+                "sega"        => MapperKind::Sega,
+                "codemasters" => MapperKind::Codemasters,
+                "korean"      => MapperKind::Korean,
+                "msx"         => MapperKind::Msx,
+                "nemesis"     => MapperKind::MsxNemesis,
+                "janggun"     => MapperKind::Janggun,
+                "4pak"        => MapperKind::FourPak,
+                _ => return Err(bad()),
+            }),
+            "save" => game.save = Some(match value.split_once(':') {
+                None if value == "sram" => SaveMemory::Sram(0x4000),
+                None if value == "eeprom" => SaveMemory::Eeprom,
+                Some(("sram", kb)) => match kb.parse::<usize>() {
+                    Ok(kb @ 1..=16) => SaveMemory::Sram(kb * 1024),
+                    _ => return Err(bad()),
+                },
+                _ => return Err(bad()),
+            }),
+            "input" => for name in value.split(',') {
+                game.peripherals.push(match name {
+                    "phaser"   => Peripheral::LightPhaser,
+                    "paddle"   => Peripheral::Paddle,
+                    "3d"       => Peripheral::Glasses3d,
+                    "keyboard" => Peripheral::Keyboard,
+                    _ => return Err(bad()),
+                });
+            },
+            "fm" => game.fm = Some(on_off(value).ok_or_else(bad)?),
+            "sprite-limit" => game.sprite_limit = Some(on_off(value).ok_or_else(bad)?),
+            _ => return Err(format!("unknown setting '{key}'")),
+        }
+    }
+}
+
+fn on_off(value: &str) -> Option<bool> {
+    match value {
+        "on"  => Some(true),
+        "off" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_crcs_are_unique() {
+        let db = GameDb::builtin();
+        for (i, g) in db.games.iter().enumerate() {
+            assert!(db.games[i + 1..].iter().all(|o| o.crc != g.crc), "CRC duplicado: {}", g.title);
+            assert!(!g.title.is_empty(), "entrada sem título: {:08X}", g.crc);
+        }
+    }
+
+    #[test]
+    fn builtin_knows_eeprom_and_mapper_games() {
+        let db = GameDb::builtin();
+        assert_eq!(db.lookup(0x36EBCD6D).and_then(|g| g.save), Some(SaveMemory::Eeprom));
+        assert_eq!(db.lookup(0xA67F2A5C).and_then(|g| g.mapper), Some(MapperKind::FourPak));
+        assert!(db.lookup(0x12345678).is_none());
+    }
+
+    #[test]
+    fn hash_inside_a_title_is_not_a_comment() {
+        let db = GameDb::parse("00000002 region=japan  Puyo#2 Deluxe  # versão coreana\n#00000003 comentado\n").unwrap();
+        assert_eq!(db.lookup(2).unwrap().title, "Puyo#2 Deluxe");
+        assert!(db.lookup(3).is_none(), "# no início da linha é comentário");
+    }
+
+    #[test]
+    fn parses_every_setting() {
+        let db = GameDb::parse("\
+            # comentário\n\
+            0xDEADBEEF platform=sg video=pal region=japan mapper=msx save=sram:8 input=paddle,3d fm=off sprite-limit=off  Jogo  de Teste # fim\n\
+            \n\
+            00000001 save=eeprom\n").unwrap();
+        let g = db.lookup(0xDEADBEEF).unwrap();
+        assert_eq!(g.title, "Jogo  de Teste");
+        assert_eq!(g.platform, Some(Platform::Sg1000));
+        assert_eq!(g.video, Some(VideoStandard::Pal));
+        assert_eq!(g.region, Some(ConsoleRegion::Japan));
+        assert_eq!(g.mapper, Some(MapperKind::Msx));
+        assert_eq!(g.save, Some(SaveMemory::Sram(8192)));
+        assert_eq!(g.peripherals, vec![Peripheral::Paddle, Peripheral::Glasses3d]);
+        assert_eq!(g.fm, Some(false));
+        assert_eq!(g.sprite_limit, Some(false));
+        assert_eq!(db.lookup(1).map(|g| g.title.as_str()), Some(""), "título é opcional");
+    }
+
+    #[test]
+    fn rejects_bad_lines() {
+        let err = |text: &str| GameDb::parse(text).err().unwrap();
+        assert_eq!(err("XYZ Jogo"), "line 1: invalid CRC32 'XYZ'");
+        assert_eq!(err("\n00000001 color=red Jogo"), "line 2: unknown setting 'color'");
+        assert_eq!(err("00000001 save=sram:32 Jogo"), "line 1: invalid value 'sram:32' for save");
+        assert_eq!(err("00000001 fm=yes Jogo"), "line 1: invalid value 'yes' for fm");
+    }
+
+    #[test]
+    fn user_entries_replace_builtin() {
+        let mut db = GameDb::builtin();
+        db.extend(GameDb::parse("A67F2A5C mapper=sega Outro\n12345678 Novo").unwrap());
+        assert_eq!(db.lookup(0xA67F2A5C).and_then(|g| g.mapper), Some(MapperKind::Sega));
+        assert_eq!(db.lookup(0xA67F2A5C).map(|g| g.title.as_str()), Some("Outro"));
+        assert!(db.lookup(0x12345678).is_some());
+        assert_eq!(db.games.iter().filter(|g| g.crc == 0xA67F2A5C).count(), 1);
+    }
+}
@@ -0,0 +1,542 @@
+//! GDB remote serial protocol stub, so gdb-multiarch (`set architecture z80`)
+//! or any other RSP client can debug a running game over localhost TCP.
+//!
+//! Breakpoints (`Z0`/`Z1`) and watchpoints (`Z2` write, `Z3` read, `Z4`
+//! access) go into the same [`Debugger`] as the debugger window, and execution
+//! runs through [`Emulator::step_frame_debug`]. While a client is attached it
+//! owns run control: the target is stopped until it sends `c` or `s`.
+//!
+//! Registers (`g`/`G`/`p`/`P`) follow gdb's Z80 layout, 16 bits each:
+//! `af bc de hl sp pc ix iy af' bc' de' hl' ir`.
+
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+
+use crate::core::Emulator;
+use crate::debugger::{Access, AddressSpace, Breakpoint, DebugBreak, Debugger, Registers, StepMode, Watchpoint};
+
+/// Largest `m` read we answer in one packet.
+const MAX_READ: usize = 0x800;
+
+const SIGINT: u8 = 2;
+const SIGTRAP: u8 = 5;
+
+pub struct GdbStub {
+    listener: TcpListener,
+    conn:     Option<TcpStream>,
+    /// Bytes received but not yet parsed into packets.
+    input:    Vec<u8>,
+    /// Whether the client has resumed the target (`c`/`s`).
+    running:  bool,
+    /// Reply for `?`: why the target last stopped.
+    last_stop: String,
+    /// Breakpoints and watchpoints the client inserted, removed again on detach.
+    breakpoints: Vec<Breakpoint>,
+    watchpoints: Vec<Watchpoint>,
+}
+
+impl GdbStub {
+    /// Listens on `127.0.0.1:port` (0 picks a free port).
+    pub fn listen(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            conn: None,
+            input: Vec::new(),
+            running: false,
+            last_stop: format!("S{SIGTRAP:02x}"),
+            breakpoints: Vec::new(),
+            watchpoints: Vec::new(),
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.listener.local_addr().map(|a| a.port()).unwrap_or(0)
+    }
+
+    pub fn is_attached(&self) -> bool {
+        self.conn.is_some()
+    }
+
+    /// Blocks until a client connects.
+    pub fn wait_for_client(&mut self) -> io::Result<()> {
+        self.listener.set_nonblocking(false)?;
+        let accepted = self.listener.accept();
+        self.listener.set_nonblocking(true)?;
+        self.attach(accepted?.0)
+    }
+
+    fn attach(&mut self, stream: TcpStream) -> io::Result<()> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        println!("gdb: client attached from {}", stream.peer_addr()?);
+        self.conn = Some(stream);
+        self.input.clear();
+        // A freshly attached debugger expects the target to be stopped.
+        self.running = false;
+        self.last_stop = format!("S{SIGTRAP:02x}");
+        Ok(())
+    }
+
+    fn detach(&mut self, dbg: &mut Debugger) {
+        self.conn = None;
+        self.running = false;
+        for bp in self.breakpoints.drain(..) {
+            if let Some(i) = dbg.breakpoints.iter().position(|b| *b == bp) { dbg.breakpoints.remove(i); }
+        }
+        for wp in self.watchpoints.drain(..) {
+            if let Some(i) = dbg.watchpoints.iter().position(|w| *w == wp) { dbg.watchpoints.remove(i); }
+        }
+        dbg.cancel_step();
+        println!("gdb: client detached");
+    }
+
+    /// Accepts a client and answers its pending packets. Returns `None` with no
+    /// client attached, otherwise whether the client lets the target run.
+    pub fn poll(&mut self, emu: &mut Emulator, dbg: &mut Debugger) -> Option<bool> {
+        if self.conn.is_none() {
+            if let Ok((stream, _)) = self.listener.accept() {
+                if let Err(e) = self.attach(stream) { eprintln!("gdb: {e}"); }
+            }
+        }
+        let conn = self.conn.as_mut()?;
+
+        let mut buf = [0u8; 4096];
+        let closed = loop {
+            match conn.read(&mut buf) {
+                Ok(0) => break true,
+                Ok(n) => self.input.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break false,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(_) => break true,
+            }
+        };
+
+        while let Some(packet) = self.next_packet() {
+            let reply = match packet {
+                Packet::Interrupt => {
+                    if !self.running { continue; }
+                    dbg.cancel_step();
+                    self.running = false;
+                    self.last_stop = format!("S{SIGINT:02x}");
+                    Some(self.last_stop.clone())
+                }
+                Packet::Data(data) => self.handle(&data, emu, dbg),
+                Packet::Invalid => { self.send_raw(b"-"); continue; }
+            };
+            if self.conn.is_none() { break; }
+            if let Some(reply) = reply { self.send(&reply); }
+        }
+        if closed && self.conn.is_some() { self.detach(dbg); }
+        self.conn.as_ref().map(|_| self.running)
+    }
+
+    /// Reports a break from [`Emulator::step_frame_debug`] to the client.
+    pub fn stopped(&mut self, reason: &DebugBreak, dbg: &Debugger) {
+        if self.conn.is_none() { return; }
+        self.running = false;
+        self.last_stop = match reason {
+            DebugBreak::Watchpoint(hit) if hit.space == AddressSpace::Memory => {
+                let both = dbg.watchpoints.iter()
+                    .any(|w| w.read && w.write && w.matches(hit.space, hit.access, hit.addr));
+                let kind = match (both, hit.access) {
+                    (true, _) => "awatch",
+                    (false, Access::Write) => "watch",
+                    (false, Access::Read) => "rwatch",
+                };
+                format!("T{SIGTRAP:02x}{kind}:{:x};", hit.addr)
+            }
+            _ => format!("S{SIGTRAP:02x}"),
+        };
+        let reply = self.last_stop.clone();
+        self.send(&reply);
+    }
+
+    /// Runs one frame under the client's control. Returns `None` while the
+    /// target is stopped or when a break cut the frame short.
+    pub fn run_frame(&mut self, emu: &mut Emulator, dbg: &mut Debugger) -> Option<(bool, Vec<f32>)> {
+        if self.poll(emu, dbg) == Some(false) { return None; }
+        let (vblank, audio, stop) = emu.step_frame_debug(dbg);
+        match stop {
+            Some(reason) => { self.stopped(&reason, dbg); None }
+            None => Some((vblank, audio)),
+        }
+    }
+
+    // ── Packets ──────────────────────────────────────────────────────────────
+
+    fn next_packet(&mut self) -> Option<Packet> {
+        loop {
+            match *self.input.first()? {
+                b'+' | b'-' => { self.input.remove(0); }
+                0x03 => { self.input.remove(0); return Some(Packet::Interrupt); }
+                b'$' => break,
+                _ => { self.input.remove(0); }
+            }
+        }
+        let end = self.input.iter().position(|&b| b == b'#')?;
+        if self.input.len() < end + 3 { return None; }
+        let packet: Vec<u8> = self.input.drain(..end + 3).collect();
+        let body = &packet[1..end];
+        let sum = body.iter().fold(0u8, |s, &b| s.wrapping_add(b));
+        let valid = std::str::from_utf8(&packet[end + 1..])
+            .ok()
+            .and_then(|h| u8::from_str_radix(h, 16).ok())
+            == Some(sum);
+        if !valid { return Some(Packet::Invalid); }
+        self.send_raw(b"+");
+        Some(Packet::Data(String::from_utf8_lossy(body).into_owned()))
+    }
+
+    fn send(&mut self, body: &str) {
+        let sum = body.bytes().fold(0u8, |s, b| s.wrapping_add(b));
+        self.send_raw(format!("${body}#{sum:02x}").as_bytes());
+    }
+
+    fn send_raw(&mut self, bytes: &[u8]) {
+        let failed = match self.conn.as_mut() {
+            Some(conn) => write_all(conn, bytes).is_err(),
+            None => false,
+        };
+        if failed { self.conn = None; }
+    }
+
+    /// Answers one packet; `None` when the reply comes later (`c`/`s`).
+    fn handle(&mut self, packet: &str, emu: &mut Emulator, dbg: &mut Debugger) -> Option<String> {
+        let (cmd, args) = packet.split_at(packet.chars().next().map_or(0, char::len_utf8));
+        let reply = match cmd {
+            "?" => self.last_stop.clone(),
+            "g" => registers_to_hex(&emu.registers()),
+            "G" => match registers_from_hex(args, emu.registers()) {
+                Some(r) => { emu.set_registers(&r); "OK".into() }
+                None => "E01".into(),
+            },
+            "p" => match usize::from_str_radix(args, 16).ok().and_then(|n| register(&emu.registers(), n)) {
+                Some(v) => hex_u16(v),
+                None => "E01".into(),
+            },
+            "P" => {
+                let mut r = emu.registers();
+                let ok = args.split_once('=').and_then(|(n, v)| {
+                    set_register(&mut r, usize::from_str_radix(n, 16).ok()?, parse_u16_le(v)?)
+                });
+                match ok {
+                    Some(()) => { emu.set_registers(&r); "OK".into() }
+                    None => "E01".into(),
+                }
+            }
+            "m" => match parse_addr_len(args) {
+                Some((addr, len)) => (0..len.min(MAX_READ))
+                    .map(|i| format!("{:02x}", emu.peek(addr.wrapping_add(i as u16))))
+                    .collect(),
+                None => "E01".into(),
+            },
+            "M" => {
+                let written = args.split_once(':').and_then(|(range, data)| {
+                    let (addr, len) = parse_addr_len(range)?;
+                    let bytes = parse_bytes(data)?;
+                    (bytes.len() == len).then(|| {
+                        for (i, b) in bytes.into_iter().enumerate() { emu.poke(addr.wrapping_add(i as u16), b); }
+                    })
+                });
+                if written.is_some() { "OK".into() } else { "E01".into() }
+            }
+            "c" | "s" => {
+                if let Some(addr) = parse_hex(args) {
+                    let mut r = emu.registers();
+                    r.pc = addr;
+                    emu.set_registers(&r);
+                }
+                if cmd == "s" { dbg.step(StepMode::Into); }
+                self.running = true;
+                return None;
+            }
+            "Z" | "z" => self.breakpoint(cmd == "Z", args, dbg),
+            "D" => {
+                self.send("OK");
+                self.detach(dbg);
+                return None;
+            }
+            "k" => {
+                self.detach(dbg);
+                return None;
+            }
+            "H" => "OK".into(),
+            "q" if args.starts_with("Supported") => format!("PacketSize={:x}", MAX_READ * 2 + 16),
+            "q" if args == "Attached" => "1".into(),
+            _ => String::new(),
+        };
+        Some(reply)
+    }
+
+    /// `Z`/`z` type,addr,kind: inserts or removes a breakpoint or watchpoint.
+    fn breakpoint(&mut self, insert: bool, args: &str, dbg: &mut Debugger) -> String {
+        let mut fields = args.split(',');
+        let kind = fields.next();
+        let addr = fields.next().and_then(parse_hex);
+        let len = fields.next().and_then(parse_hex).unwrap_or(1).max(1);
+        let Some(addr) = addr else { return "E01".into() };
+
+        let (read, write) = match kind {
+            Some("0") | Some("1") => {
+                let bp = Breakpoint { addr, bank: None };
+                if insert {
+                    dbg.breakpoints.push(bp);
+                    self.breakpoints.push(bp);
+                } else {
+                    remove_first(&mut dbg.breakpoints, &bp);
+                    remove_first(&mut self.breakpoints, &bp);
+                }
+                return "OK".into();
+            }
+            Some("2") => (false, true),
+            Some("3") => (true, false),
+            Some("4") => (true, true),
+            _ => return String::new(),
+        };
+        let wp = Watchpoint {
+            space: AddressSpace::Memory,
+            start: addr,
+            end: addr.saturating_add(len - 1),
+            read,
+            write,
+        };
+        if insert {
+            dbg.watchpoints.push(wp);
+            self.watchpoints.push(wp);
+        } else {
+            remove_first(&mut dbg.watchpoints, &wp);
+            remove_first(&mut self.watchpoints, &wp);
+        }
+        "OK".into()
+    }
+}
+
+enum Packet {
+    Data(String),
+    /// Ctrl-C from the client.
+    Interrupt,
+    /// Bad checksum; the client resends.
+    Invalid,
+}
+
+fn remove_first<T: PartialEq>(list: &mut Vec<T>, item: &T) {
+    if let Some(i) = list.iter().position(|x| x == item) { list.remove(i); }
+}
+
+/// `write_all` for a non-blocking socket.
+fn write_all(conn: &mut TcpStream, mut bytes: &[u8]) -> io::Result<()> {
+    while !bytes.is_empty() {
+        match conn.write(bytes) {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(n) => bytes = &bytes[n..],
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::Interrupted) => std::thread::yield_now(),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+fn parse_hex(s: &str) -> Option<u16> {
+    u16::from_str_radix(s, 16).ok()
+}
+
+fn parse_addr_len(s: &str) -> Option<(u16, usize)> {
+    let (addr, len) = s.split_once(',')?;
+    Some((parse_hex(addr)?, usize::from_str_radix(len, 16).ok()?))
+}
+
+fn parse_bytes(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) { return None; }
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok()).collect()
+}
+
+/// Register values travel as little-endian byte pairs.
+fn hex_u16(v: u16) -> String {
+    format!("{:02x}{:02x}", v as u8, (v >> 8) as u8)
+}
+
+fn parse_u16_le(s: &str) -> Option<u16> {
+    match parse_bytes(s)?.as_slice() {
+        [lo, hi] => Some(u16::from_le_bytes([*lo, *hi])),
+        _ => None,
+    }
+}
+
+const REGISTER_COUNT: usize = 13;
+
+fn register(r: &Registers, n: usize) -> Option<u16> {
+    Some(match n {
+        0 => r.af, 1 => r.bc, 2 => r.de, 3 => r.hl,
+        4 => r.sp, 5 => r.pc, 6 => r.ix, 7 => r.iy,
+        8 => r.af_alt, 9 => r.bc_alt, 10 => r.de_alt, 11 => r.hl_alt,
+        12 => (r.i as u16) << 8 | r.r as u16,
+        _ => return None,
+    })
+}
+
+fn set_register(r: &mut Registers, n: usize, v: u16) -> Option<()> {
+    match n {
+        0 => r.af = v, 1 => r.bc = v, 2 => r.de = v, 3 => r.hl = v,
+        4 => r.sp = v, 5 => r.pc = v, 6 => r.ix = v, 7 => r.iy = v,
+        8 => r.af_alt = v, 9 => r.bc_alt = v, 10 => r.de_alt = v, 11 => r.hl_alt = v,
+        12 => { r.i = (v >> 8) as u8; r.r = v as u8; }
+        _ => return None,
+    }
+    Some(())
+}
+
+fn registers_to_hex(r: &Registers) -> String {
+    (0..REGISTER_COUNT).filter_map(|n| register(r, n)).map(hex_u16).collect()
+}
+
+fn registers_from_hex(s: &str, mut r: Registers) -> Option<Registers> {
+    if s.len() < REGISTER_COUNT * 4 { return None; }
+    for n in 0..REGISTER_COUNT {
+        set_register(&mut r, n, parse_u16_le(s.get(n * 4..n * 4 + 4)?)?)?;
+    }
+    Some(r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::{Platform, VideoStandard};
+    use crate::test_util::with_large_stack;
+    use std::time::{Duration, Instant};
+
+    // LD SP,$DFF0; loop: LD A,$42; LD ($C000),A; JR loop
+    fn make_emu() -> Emulator {
+        let mut rom = vec![0u8; 0x8000];
+        rom[..10].copy_from_slice(&[0x31, 0xF0, 0xDF, 0x3E, 0x42, 0x32, 0x00, 0xC0, 0x18, 0xF9]);
+        Emulator::new(rom, Platform::MasterSystem, VideoStandard::Ntsc, 44100.0)
+    }
+
+    /// The stub plus a connected client socket.
+    struct Session {
+        stub:   GdbStub,
+        client: TcpStream,
+        emu:    Emulator,
+        dbg:    Debugger,
+    }
+
+    impl Session {
+        fn new() -> Self {
+            let mut stub = GdbStub::listen(0).unwrap();
+            let client = TcpStream::connect((Ipv4Addr::LOCALHOST, stub.port())).unwrap();
+            client.set_read_timeout(Some(Duration::from_millis(10))).unwrap();
+            let (mut emu, mut dbg) = (make_emu(), Debugger::default());
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while stub.poll(&mut emu, &mut dbg).is_none() {
+                assert!(Instant::now() < deadline, "cliente não conectou");
+            }
+            Session { stub, client, emu, dbg }
+        }
+
+        fn send(&mut self, body: &str) {
+            let sum = body.bytes().fold(0u8, |s, b| s.wrapping_add(b));
+            self.client.write_all(format!("${body}#{sum:02x}").as_bytes()).unwrap();
+        }
+
+        /// Polls the stub (running frames while the target runs) until a reply arrives.
+        fn reply(&mut self) -> String {
+            let mut got = Vec::new();
+            let deadline = Instant::now() + Duration::from_secs(5);
+            loop {
+                if self.stub.poll(&mut self.emu, &mut self.dbg) == Some(true) {
+                    self.stub.run_frame(&mut self.emu, &mut self.dbg);
+                }
+                let mut buf = [0u8; 4096];
+                if let Ok(n) = self.client.read(&mut buf) { got.extend_from_slice(&buf[..n]); }
+                let text = String::from_utf8_lossy(&got).trim_start_matches('+').to_string();
+                if let (Some(start), Some(end)) = (text.find('$'), text.find('#')) {
+                    if text.len() >= end + 3 { return text[start + 1..end].to_string(); }
+                }
+                assert!(Instant::now() < deadline, "sem resposta: {text:?}");
+            }
+        }
+
+        fn exchange(&mut self, body: &str) -> String {
+            self.send(body);
+            self.reply()
+        }
+    }
+
+    // ── Encoding ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn registers_round_trip_through_hex() {
+        let r = Registers {
+            af: 0x1234, bc: 0x5678, de: 0x9ABC, hl: 0xDEF0,
+            af_alt: 1, bc_alt: 2, de_alt: 3, hl_alt: 4,
+            ix: 0x1111, iy: 0x2222, sp: 0xDFF0, pc: 0x0038,
+            i: 0xAB, r: 0xCD, iff1: true, iff2: true, interrupt_mode: 1, halted: false,
+        };
+        let hex = registers_to_hex(&r);
+        assert_eq!(hex.len(), REGISTER_COUNT * 4);
+        assert!(hex.starts_with("3412"), "little-endian: {hex}");
+        assert!(hex.ends_with("cdab"), "IR por último: {hex}");
+        let back = registers_from_hex(&hex, Registers { af: 0, ..r }).unwrap();
+        assert_eq!(back.af, 0x1234);
+        assert_eq!((back.i, back.r), (0xAB, 0xCD));
+    }
+
+    // ── Session ──────────────────────────────────────────────────────────────
+
+    #[test]
+    fn reads_and_writes_registers_and_memory() {
+        with_large_stack(|| {
+            let mut s = Session::new();
+            assert_eq!(s.exchange("?"), "S05");
+            let regs = s.exchange("g");
+            assert_eq!(&regs[20..24], "0000", "PC no reset");
+            assert_eq!(s.exchange("m0,3"), "31f0df");
+
+            assert_eq!(s.exchange("Mc000,2:beef"), "OK");
+            assert_eq!(s.exchange("mc000,2"), "beef");
+            assert_eq!(s.emu.peek(0xC000), 0xBE);
+
+            assert_eq!(s.exchange("P5=0300"), "OK");
+            assert_eq!(s.emu.registers().pc, 0x0003);
+            assert_eq!(s.exchange("p5"), "0300");
+        });
+    }
+
+    #[test]
+    fn step_executes_one_instruction() {
+        with_large_stack(|| {
+            let mut s = Session::new();
+            assert_eq!(s.exchange("s"), "S05");
+            let r = s.emu.registers();
+            assert_eq!((r.pc, r.sp), (0x0003, 0xDFF0));
+        });
+    }
+
+    #[test]
+    fn continue_stops_at_breakpoint_and_watchpoint() {
+        with_large_stack(|| {
+            let mut s = Session::new();
+            assert_eq!(s.exchange("Z0,5,1"), "OK");
+            assert_eq!(s.exchange("c"), "S05");
+            assert_eq!(s.emu.registers().pc, 0x0005);
+
+            assert_eq!(s.exchange("z0,5,1"), "OK");
+            assert!(s.dbg.breakpoints.is_empty());
+            assert_eq!(s.exchange("Z2,c000,1"), "OK");
+            assert_eq!(s.exchange("c"), "T05watch:c000;");
+        });
+    }
+
+    #[test]
+    fn detach_removes_client_breakpoints() {
+        with_large_stack(|| {
+            let mut s = Session::new();
+            s.dbg.breakpoints.push(Breakpoint { addr: 0x0100, bank: None });
+            assert_eq!(s.exchange("Z0,5,1"), "OK");
+            assert_eq!(s.exchange("D"), "OK");
+            assert!(!s.stub.is_attached());
+            assert_eq!(s.dbg.breakpoints, vec![Breakpoint { addr: 0x0100, bank: None }], "só os do gdb saem");
+        });
+    }
+}
@@ -0,0 +1,568 @@
+//! Headless runner: plays a ROM for a fixed number of frames with scripted
+//! input, then dumps the final screen (PNG), the audio (WAV) and optionally
+//! the machine state. Needs no window, GPU or sound card, so it runs in CI.
+//!
+//! Input script format — one entry per line, `#` starts a comment:
+//!
+//! ```text
+//! # frame  buttons held from this frame until the next entry
+//! 0
+//! 120      start
+//! 122
+//! 300      right b1
+//! 360      p2-left p2-b2 gun=128,96
+//! ```
+//!
+//! Player 1 buttons: `up down left right b1 b2 start`.
+//! Player 2 buttons: `p2-up p2-down p2-left p2-right p2-b1 p2-b2`.
+//! `gun=X,Y` pulls the Light Phaser trigger aimed at screen pixel (X, Y).
+//! `reset` power-cycles the console at the start of that frame.
+//!
+//! Instead of a script, `--movie` replays a recorded input movie and fails if
+//! it desyncs; `--record` saves the inputs of this run as a movie.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::{apply_patch, find_patch, ConsoleRegion, Debugger, Emulator, FrameInput, GameDb, GdbStub, MediaSlot, Movie, MoviePlayer, MovieRecorder, Platform, SaveState,
+            TraceOptions, Tracer, VideoStandard, GG_H, GG_W, SMS_H, SMS_W};
+
+const SAMPLE_RATE: u32 = 44100;
+
+pub const USAGE: &str = "\
+usage: vibe-sms-headless <rom> --frames N [options]
+       vibe-sms-headless <rom> --movie FILE [options]
+
+options:
+  --frames N         number of frames to run (default with --movie: its length)
+  --input FILE       scripted input (see `headless` module docs)
+  --movie FILE       replay an input movie; exits with an error on desync
+  --record FILE      record this run's input as a movie
+  --png FILE         write the final frame as PNG
+  --wav FILE         write all generated audio as 16-bit stereo WAV
+  --save-state FILE  write the machine state after the last frame
+  --load-state FILE  start from a save state instead of power-on
+  --platform NAME    force sms, gg, sg or sc instead of detecting it
+  --pal              PAL (50 Hz) timing
+  --japan            Japanese console
+  --fm               fit the FM unit (default: only on a Japanese console)
+  --no-fm            hide the FM unit from the game
+  --gamedb FILE      extra game database entries (see `gamedb` module docs)
+  --bios FILE        boot through this console BIOS first
+  --card             plug the ROM into the Master System card slot
+  --patch FILE       apply an IPS/BPS/UPS patch (default: same-named one beside the ROM)
+  --trace FILE       write an instruction trace (see `trace` module docs)
+  --trace-pc A-B     only trace PCs in $A-$B (hex)
+  --trace-bank N     only trace code fetched from ROM bank N (hex)
+  --trace-frames A-B only trace frames A to B ('A-' is open-ended)
+  --trace-vdp        also log VDP register writes
+  --trace-irq        also log accepted interrupts
+  --gdb PORT         wait for a GDB remote client on 127.0.0.1:PORT before running";
+
+const BUTTONS: [&str; 6] = ["up", "down", "left", "right", "b1", "b2"];
+
+/// Parses an input script into `(first_frame, input)` entries sorted by frame.
+pub(crate) fn parse_input_script(text: &str) -> Result<Vec<(u32, FrameInput)>, String> {
+    let mut entries = Vec::new();
+    for (n, raw) in text.lines().enumerate() {
+        let line = raw.split('#').next().unwrap_or("").trim();
+        if line.is_empty() { continue; }
+
+        let mut tokens = line.split_whitespace();
+        let frame_tok = tokens.next().unwrap_or("");
+        let frame: u32 = frame_tok.parse()
+            .map_err(|_| format!("line {}: invalid frame number '{}'", n + 1, frame_tok))?;
+
+        let mut input = FrameInput::default();
+        for tok in tokens {
+            let tok = tok.to_ascii_lowercase();
+            if tok == "start" {
+                input.start = true;
+            } else if tok == "reset" {
+                input.reset = true;
+            } else if let Some(i) = BUTTONS.iter().position(|b| *b == tok) {
+                input.p1[i] = true;
+            } else if let Some(i) = tok.strip_prefix("p2-").and_then(|b| BUTTONS.iter().position(|x| *x == b)) {
+                input.p2[i] = true;
+            } else if let Some(pos) = tok.strip_prefix("gun=") {
+                let (x, y) = pos.split_once(',')
+                    .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)))
+                    .ok_or_else(|| format!("line {}: invalid gun position '{}'", n + 1, pos))?;
+                input.gun = Some((x, y));
+            } else {
+                return Err(format!("line {}: unknown button '{}'", n + 1, tok));
+            }
+        }
+        entries.push((frame, input));
+    }
+    entries.sort_by_key(|(f, _)| *f);
+    Ok(entries)
+}
+
+/// Input in effect on `frame`: the last script entry at or before it.
+fn input_at(script: &[(u32, FrameInput)], frame: u32) -> FrameInput {
+    script.iter().rev().find(|(f, _)| *f <= frame).map(|(_, i)| *i).unwrap_or_default()
+}
+
+/// Encodes interleaved stereo f32 samples as a 16-bit PCM WAV file.
+pub(crate) fn wav_bytes(samples: &[f32], sample_rate: u32) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut out = Vec::with_capacity(44 + data_len as usize);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());          // fmt chunk size
+    out.extend_from_slice(&1u16.to_le_bytes());           // PCM
+    out.extend_from_slice(&2u16.to_le_bytes());           // stereo
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    out.extend_from_slice(&(sample_rate * 4).to_le_bytes()); // byte rate
+    out.extend_from_slice(&4u16.to_le_bytes());           // block align
+    out.extend_from_slice(&16u16.to_le_bytes());          // bits per sample
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    for &s in samples {
+        let v = (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    out
+}
+
+/// Visible area of the framebuffer as packed RGB8 (GG games are cropped to 160×144).
+pub(crate) fn visible_rgb(fb: &[u32], is_gg: bool) -> (u32, u32, Vec<u8>) {
+    let (w, h, xo, yo) = if is_gg { (GG_W, GG_H, 48, 24) } else { (SMS_W, SMS_H, 0, 0) };
+    let mut rgb = Vec::with_capacity(w * h * 3);
+    for y in 0..h {
+        for x in 0..w {
+            let px = fb[(y + yo) * SMS_W + (x + xo)];
+            rgb.extend_from_slice(&[(px >> 16) as u8, (px >> 8) as u8, px as u8]);
+        }
+    }
+    (w as u32, h as u32, rgb)
+}
+
+pub struct HeadlessOptions {
+    pub rom:         PathBuf,
+    pub frames:      u32,
+    pub input:       Option<PathBuf>,
+    pub movie:       Option<PathBuf>,
+    pub record:      Option<PathBuf>,
+    pub png:         Option<PathBuf>,
+    pub wav:         Option<PathBuf>,
+    pub save_state:  Option<PathBuf>,
+    pub load_state:  Option<PathBuf>,
+    /// Forced platform; `None` detects it from the game database and the ROM.
+    pub platform:    Option<Platform>,
+    pub video:       VideoStandard,
+    pub region:      ConsoleRegion,
+    /// `--fm` / `--no-fm`; without either the console region decides.
+    pub fm:          Option<bool>,
+    pub gamedb:      Option<PathBuf>,
+    /// Console BIOS to boot through before the cartridge.
+    pub bios:        Option<PathBuf>,
+    /// Slot the ROM is plugged into; cards always run on a Master System.
+    pub media:       MediaSlot,
+    /// Patch to apply; without one, a same-named `.ips`/`.bps`/`.ups` is used.
+    pub patch:       Option<PathBuf>,
+    pub trace:       Option<TraceOptions>,
+    pub gdb:         Option<u16>,
+}
+
+impl HeadlessOptions {
+    /// Parses command-line arguments (without the program name).
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut rom = None;
+        let mut frames = None;
+        let mut opts = HeadlessOptions {
+            rom: PathBuf::new(), frames: 0,
+            input: None, movie: None, record: None, png: None, wav: None, save_state: None, load_state: None,
+            platform: None, video: VideoStandard::Ntsc, region: ConsoleRegion::Export, fm: None, gamedb: None, bios: None, media: MediaSlot::Cartridge, patch: None,
+            trace: TraceOptions::from_args(args)?, gdb: None,
+        };
+
+        let mut it = args.iter();
+        while let Some(arg) = it.next() {
+            let mut value = || it.next().cloned().ok_or_else(|| format!("{arg} needs a value"));
+            match arg.as_str() {
+                "--frames" => {
+                    let v = value()?;
+                    frames = Some(v.parse::<u32>().map_err(|_| format!("invalid frame count '{v}'"))?);
+                }
+                "--input"      => opts.input      = Some(value()?.into()),
+                "--movie"      => opts.movie      = Some(value()?.into()),
+                "--record"     => opts.record     = Some(value()?.into()),
+                "--png"        => opts.png        = Some(value()?.into()),
+                "--wav"        => opts.wav        = Some(value()?.into()),
+                "--save-state" => opts.save_state = Some(value()?.into()),
+                "--load-state" => opts.load_state = Some(value()?.into()),
+                "--gamedb"     => opts.gamedb     = Some(value()?.into()),
+                "--bios"       => opts.bios       = Some(value()?.into()),
+                "--patch"      => opts.patch      = Some(value()?.into()),
+                "--card"       => opts.media = MediaSlot::Card,
+                "--platform" => {
+                    let v = value()?;
+                    opts.platform = Some(Platform::from_name(&v).ok_or_else(|| format!("unknown platform '{v}'"))?);
+                }
+                "--pal"        => opts.video  = VideoStandard::Pal,
+                "--japan"      => opts.region = ConsoleRegion::Japan,
+                "--fm"         => opts.fm = Some(true),
+                "--no-fm"      => opts.fm = Some(false),
+                "--gdb" => {
+                    let v = value()?;
+                    opts.gdb = Some(v.parse::<u16>().map_err(|_| format!("invalid port '{v}'"))?);
+                }
+                a if TraceOptions::is_trace_arg(a) == Some(true) => { value()?; }
+                a if TraceOptions::is_trace_arg(a).is_some() => {}
+                a if a.starts_with('-') => return Err(format!("unknown option '{a}'")),
+                a => rom = Some(PathBuf::from(a)),
+            }
+        }
+
+        opts.rom = rom.ok_or("missing ROM path")?;
+        opts.frames = match frames {
+            Some(n) => n,
+            None if opts.movie.is_some() => u32::MAX,
+            None => return Err("missing --frames".into()),
+        };
+        if opts.movie.is_some() && (opts.input.is_some() || opts.record.is_some()) {
+            return Err("--movie cannot be combined with --input or --record".into());
+        }
+        if opts.gdb.is_some() && (opts.movie.is_some() || opts.record.is_some()) {
+            return Err("--gdb cannot be combined with --movie or --record".into());
+        }
+        Ok(opts)
+    }
+}
+
+fn read(path: &Path, what: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|e| format!("failed to read {what} {}: {e}", path.display()))
+}
+
+fn write(path: &Path, what: &str, data: &[u8]) -> Result<(), String> {
+    std::fs::write(path, data).map_err(|e| format!("failed to write {what} {}: {e}", path.display()))
+}
+
+/// Builds the machine for `rom`: game database lookup, platform, region,
+/// media, BIOS and FM.
+fn build_emulator(opts: &HeadlessOptions, rom: Vec<u8>) -> Result<Emulator, String> {
+    let mut db = GameDb::builtin();
+    if let Some(ref p) = opts.gamedb {
+        db.extend(GameDb::load(p)?);
+    }
+    let game = db.lookup_rom(&rom).cloned();
+    let platform = if opts.media != MediaSlot::Cartridge {
+        Platform::MasterSystem
+    } else {
+        opts.platform.or(game.as_ref().and_then(|g| g.platform)).unwrap_or_else(|| Platform::detect(
+            opts.rom.extension().and_then(|e| e.to_str()).unwrap_or(""), &rom))
+    };
+    let video = game.as_ref().and_then(|g| g.video).unwrap_or(opts.video);
+    let mut emu = Emulator::new(rom, platform, video, SAMPLE_RATE as f32);
+    emu.set_console_region(game.as_ref().and_then(|g| g.region).unwrap_or(opts.region));
+    emu.set_media_slot(opts.media);
+    if let Some(game) = game {
+        println!("Game database: {} ({})", game.title, game.describe());
+        emu.set_game_info(game);
+    }
+    if let Some(ref p) = opts.bios {
+        emu.set_bios(read(p, "BIOS")?);
+    }
+    emu.set_fm_disabled(emu.console_region().fm_disabled(platform, opts.fm));
+    Ok(emu)
+}
+
+/// Runs the ROM as described by `opts` and writes the requested outputs.
+pub fn run(opts: &HeadlessOptions) -> Result<(), String> {
+    let mut rom = read(&opts.rom, "ROM")?;
+    if let Some(p) = opts.patch.clone().or_else(|| find_patch(&opts.rom)) {
+        rom = apply_patch(&rom, &read(&p, "patch")?).map_err(|e| format!("{}: {e}", p.display()))?;
+        println!("Patch applied: {}", p.display());
+    }
+    let script = match opts.input {
+        Some(ref p) => {
+            let text = String::from_utf8(read(p, "input script")?)
+                .map_err(|_| format!("input script {} is not UTF-8", p.display()))?;
+            parse_input_script(&text)?
+        }
+        None => Vec::new(),
+    };
+
+    let mut emu = build_emulator(opts, rom)?;
+    if let Some(ref t) = opts.trace {
+        emu.set_tracer(Some(Box::new(Tracer::create(t.clone())?)));
+    }
+
+    if let Some(ref p) = opts.load_state {
+        let state = SaveState::deserialize(&read(p, "save state")?)
+            .ok_or_else(|| format!("save state {} is invalid", p.display()))?;
+        emu.load_state(state);
+    }
+
+    let mut player = match opts.movie {
+        Some(ref p) => {
+            let movie = Movie::deserialize(&read(p, "movie")?)
+                .ok_or_else(|| format!("movie {} is invalid", p.display()))?;
+            Some(MoviePlayer::start(movie, &mut emu)?)
+        }
+        None => None,
+    };
+    // Without --load-state the recording starts at power-on (blank battery RAM).
+    let mut recorder = opts.record.as_ref().map(|_| MovieRecorder::start(&mut emu, opts.load_state.is_none()));
+
+    let mut gdb = match opts.gdb {
+        Some(port) => {
+            let mut stub = GdbStub::listen(port).map_err(|e| format!("failed to listen on port {port}: {e}"))?;
+            println!("gdb: waiting for a client on 127.0.0.1:{}", stub.port());
+            stub.wait_for_client().map_err(|e| format!("gdb: {e}"))?;
+            Some(stub)
+        }
+        None => None,
+    };
+    let mut dbg = Debugger::default();
+
+    let mut audio = Vec::new();
+    let mut frame = 0;
+    // A gdb stop leaves the frame unfinished; its input was already applied.
+    let mut frame_started = false;
+    while frame < opts.frames {
+        let (_, mut samples) = if let Some(ref mut pl) = player {
+            match pl.step(&mut emu) {
+                Some(out) => out,
+                None => break,
+            }
+        } else {
+            let input = input_at(&script, frame);
+            match (&mut recorder, &mut gdb) {
+                (Some(rec), _) => rec.step(&mut emu, input),
+                (None, Some(stub)) => {
+                    if !frame_started { emu.apply_input(&input); }
+                    match stub.run_frame(&mut emu, &mut dbg) {
+                        Some(out) => out,
+                        None => {
+                            frame_started = true;
+                            std::thread::sleep(Duration::from_millis(1));
+                            continue;
+                        }
+                    }
+                }
+                (None, None) => {
+                    emu.apply_input(&input);
+                    emu.step_frame()
+                }
+            }
+        };
+        frame_started = false;
+        frame += 1;
+        if opts.wav.is_some() { audio.append(&mut samples); }
+    }
+
+    if let Some(mut t) = emu.take_tracer() {
+        t.finish()?;
+    }
+    if let Some(ref p) = opts.png {
+        let (w, h, rgb) = visible_rgb(&emu.get_framebuffer(), emu.platform().is_gg());
+        image::save_buffer(p, &rgb, w, h, image::ExtendedColorType::Rgb8)
+            .map_err(|e| format!("failed to write PNG {}: {e}", p.display()))?;
+    }
+    if let Some(ref p) = opts.wav {
+        write(p, "WAV", &wav_bytes(&audio, SAMPLE_RATE))?;
+    }
+    if let Some(ref p) = opts.save_state {
+        write(p, "save state", &emu.save_state().serialize())?;
+    }
+    if let (Some(rec), Some(ref p)) = (recorder, &opts.record) {
+        write(p, "movie", &rec.finish().serialize())?;
+    }
+    if let Some(frame) = player.and_then(|pl| pl.desync_frame()) {
+        return Err(format!("movie desynced at frame {frame}"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::with_large_stack;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    // ── Input script ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn script_parses_buttons_for_both_players() {
+        let script = parse_input_script("10 up b1 start\n20 p2-right p2-b2\n").unwrap();
+        assert_eq!(script.len(), 2);
+        assert_eq!(script[0].0, 10);
+        assert!(script[0].1.p1[0] && script[0].1.p1[4] && script[0].1.start);
+        assert!(script[1].1.p2[3] && script[1].1.p2[5]);
+        assert_eq!(script[1].1.p1, [false; 6]);
+    }
+
+    #[test]
+    fn script_ignores_comments_and_blank_lines() {
+        let script = parse_input_script("# header\n\n5 left # hold left\n").unwrap();
+        assert_eq!(script.len(), 1);
+        assert!(script[0].1.p1[2]);
+    }
+
+    #[test]
+    fn script_parses_gun_position() {
+        let script = parse_input_script("0 gun=128,96").unwrap();
+        assert_eq!(script[0].1.gun, Some((128, 96)));
+    }
+
+    #[test]
+    fn script_rejects_unknown_button() {
+        let err = parse_input_script("0 jump").unwrap_err();
+        assert!(err.contains("line 1"), "{err}");
+    }
+
+    #[test]
+    fn input_holds_until_next_entry() {
+        let script = parse_input_script("10 up\n20\n").unwrap();
+        assert!(!input_at(&script, 9).p1[0]);
+        assert!(input_at(&script, 10).p1[0]);
+        assert!(input_at(&script, 19).p1[0]);
+        assert!(!input_at(&script, 20).p1[0]);
+    }
+
+    // ── Outputs ──────────────────────────────────────────────────────────────
+
+    #[test]
+    fn wav_header_describes_16bit_stereo() {
+        let wav = wav_bytes(&[0.0, 1.0, -1.0, 0.5], 44100);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(u16::from_le_bytes([wav[22], wav[23]]), 2);
+        assert_eq!(u32::from_le_bytes([wav[24], wav[25], wav[26], wav[27]]), 44100);
+        assert_eq!(u32::from_le_bytes([wav[40], wav[41], wav[42], wav[43]]), 8);
+        assert_eq!(wav.len(), 44 + 8);
+        assert_eq!(i16::from_le_bytes([wav[46], wav[47]]), i16::MAX);
+    }
+
+    #[test]
+    fn visible_rgb_crops_game_gear_viewport() {
+        let mut fb = vec![0u32; SMS_W * SMS_H];
+        fb[24 * SMS_W + 48] = 0xFF112233;
+        let (w, h, rgb) = visible_rgb(&fb, true);
+        assert_eq!((w, h), (GG_W as u32, GG_H as u32));
+        assert_eq!(&rgb[0..3], &[0x11, 0x22, 0x33]);
+    }
+
+    // ── Command line ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn options_parse_rom_frames_and_outputs() {
+        let o = HeadlessOptions::from_args(&args(&["game.sms", "--frames", "600", "--png", "out.png", "--pal"])).unwrap();
+        assert_eq!(o.rom, PathBuf::from("game.sms"));
+        assert_eq!(o.frames, 600);
+        assert_eq!(o.png, Some(PathBuf::from("out.png")));
+        assert_eq!(o.video, VideoStandard::Pal);
+    }
+
+    #[test]
+    fn options_keep_trace_values_out_of_rom_path() {
+        let o = HeadlessOptions::from_args(&args(&["--trace", "t.log", "--trace-vdp", "game.sms", "--frames", "1"])).unwrap();
+        assert_eq!(o.rom, PathBuf::from("game.sms"));
+        let trace = o.trace.unwrap();
+        assert_eq!(trace.path, PathBuf::from("t.log"));
+        assert!(trace.vdp_writes);
+    }
+
+    #[test]
+    fn options_select_card_slot_bios_gamedb_and_patch() {
+        let o = HeadlessOptions::from_args(&args(&["game.sg", "--frames", "1", "--card", "--bios", "b.sms", "--gamedb", "db.txt"])).unwrap();
+        assert_eq!(o.media, MediaSlot::Card);
+        assert_eq!(o.bios, Some(PathBuf::from("b.sms")));
+        assert_eq!(o.gamedb, Some(PathBuf::from("db.txt")));
+        assert_eq!(o.patch, None);
+        let o = HeadlessOptions::from_args(&args(&["game.sms", "--patch", "fix.ips", "--frames", "1"])).unwrap();
+        assert_eq!(o.patch, Some(PathBuf::from("fix.ips")));
+        assert_eq!(o.rom, PathBuf::from("game.sms"));
+        let o = HeadlessOptions::from_args(&args(&["game.sms", "--frames", "1"])).unwrap();
+        assert_eq!(o.media, MediaSlot::Cartridge);
+    }
+
+    #[test]
+    fn fm_unit_follows_console_region() {
+        with_large_stack(|| {
+            let fm_off = |extra: &[&str]| {
+                let mut list = vec!["game.sms", "--frames", "1"];
+                list.extend_from_slice(extra);
+                let opts = HeadlessOptions::from_args(&args(&list)).unwrap();
+                build_emulator(&opts, vec![0u8; 0xC000]).unwrap().is_fm_disabled()
+            };
+            assert!(fm_off(&[]), "console de exportação vem sem FM");
+            assert!(!fm_off(&["--japan"]), "SMS japonês tem FM embutido");
+            assert!(!fm_off(&["--fm"]), "unidade FM avulsa");
+            assert!(fm_off(&["--japan", "--no-fm"]));
+        });
+    }
+
+    /// Programa SG-1000 que desenha o tile 0 (metade esquerda acesa) no modo
+    /// TMS escolhido por `reg0` e fica parado.
+    fn sg_program(reg0: u8) -> Vec<u8> {
+        let mut code = vec![0xF3]; // di
+        let mut out = |value: u8, port: u8| code.extend_from_slice(&[0x3E, value, 0xD3, port]);
+        for (reg, value) in [(0, reg0), (1, 0xC0), (2, 0x0E), (3, 0x80), (4, 0x00), (5, 0x7E), (7, 0x01)] {
+            out(value, 0xBF);
+            out(0x80 | reg, 0xBF);
+        }
+        for (addr, byte) in [(0x0000u16, 0xF0), (0x2000, 0x64), (0x3F00, 0xD0)] {
+            out(addr as u8, 0xBF);
+            out(0x40 | (addr >> 8) as u8, 0xBF);
+            for _ in 0..if addr == 0x3F00 { 1 } else { 8 } {
+                out(byte, 0xBE);
+            }
+        }
+        code.extend_from_slice(&[0x18, 0xFE]); // jr $
+        code.resize(0x8000, 0);
+        code
+    }
+
+    #[test]
+    fn sg1000_card_renders_tms_modes_on_master_system() {
+        with_large_stack(|| {
+            use crate::vdp::TMS_PALETTE;
+            for (reg0, mode) in [(0x00, "Graphics I"), (0x02, "Graphics II")] {
+                let opts = HeadlessOptions::from_args(&args(&["game.sg", "--frames", "1", "--card"])).unwrap();
+                let mut emu = build_emulator(&opts, sg_program(reg0)).unwrap();
+                assert_eq!(emu.platform(), Platform::MasterSystem);
+                emu.step_frame();
+                emu.step_frame();
+                let fb = emu.get_framebuffer();
+                assert_eq!(fb[0], TMS_PALETTE[6], "{mode}: pixel aceso usa a cor de frente");
+                assert_eq!(fb[4], TMS_PALETTE[4], "{mode}: pixel apagado usa a cor de fundo");
+                assert_eq!(fb[7 * 256 + 3], TMS_PALETTE[6], "{mode}: última linha do tile");
+            }
+        });
+    }
+
+    #[test]
+    fn options_force_platform() {
+        let o = HeadlessOptions::from_args(&args(&["game.bin", "--frames", "1", "--platform", "GG"])).unwrap();
+        assert_eq!(o.platform, Some(Platform::GameGear));
+        assert_eq!(o.rom, PathBuf::from("game.bin"), "o valor de --platform não é a ROM");
+        let err = HeadlessOptions::from_args(&args(&["game.bin", "--frames", "1", "--platform", "nes"])).err();
+        assert_eq!(err.as_deref(), Some("unknown platform 'nes'"));
+        assert_eq!(HeadlessOptions::from_args(&args(&["game.sms", "--frames", "1"])).unwrap().platform, None);
+    }
+
+    #[test]
+    fn options_require_frames() {
+        assert!(HeadlessOptions::from_args(&args(&["game.sms"])).is_err());
+    }
+
+    #[test]
+    fn options_movie_runs_to_its_end_by_default() {
+        let o = HeadlessOptions::from_args(&args(&["game.sms", "--movie", "run.vsm"])).unwrap();
+        assert_eq!(o.frames, u32::MAX);
+        assert!(HeadlessOptions::from_args(&args(&["game.sms", "--movie", "a", "--record", "b"])).is_err());
+    }
+
+    #[test]
+    fn script_parses_reset() {
+        assert!(parse_input_script("30 reset").unwrap()[0].1.reset);
+    }
+}
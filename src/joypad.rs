@@ -1,3 +1,5 @@
+use crate::platform::ConsoleRegion;
+
 pub(crate) struct Joypad {
     pub(crate) p1_up: bool,
     pub(crate) p1_down: bool,
@@ -5,6 +7,12 @@ pub(crate) struct Joypad {
     pub(crate) p1_right: bool,
     pub(crate) p1_b1: bool,
     pub(crate) p1_b2: bool,
+    pub(crate) p2_up: bool,
+    pub(crate) p2_down: bool,
+    pub(crate) p2_left: bool,
+    pub(crate) p2_right: bool,
+    pub(crate) p2_b1: bool,
+    pub(crate) p2_b2: bool,
     pub(crate) gg_start: bool,
 
     // Light Phaser
@@ -15,21 +23,33 @@ pub(crate) struct Joypad {
 
     // I/O Control
     pub(crate) port_3f: u8,
+    pub(crate) region: ConsoleRegion,
 }
 
 impl Joypad {
     pub(crate) fn new() -> Self {
         Self {
             p1_up: false, p1_down: false, p1_left: false, p1_right: false,
-            p1_b1: false, p1_b2: false, gg_start: false,
+            p1_b1: false, p1_b2: false,
+            p2_up: false, p2_down: false, p2_left: false, p2_right: false,
+            p2_b1: false, p2_b2: false, gg_start: false,
             lightgun_active: false,
             mouse_x: 0,
             mouse_y: 0,
             th_pin_low: false,
             port_3f: 0xFF,
+            region: ConsoleRegion::Export,
         }
     }
 
+    /// True when `dir_bit` of port $3F configures the pin as an output and
+    /// `level_bit` drives it low. Only export consoles read this level back.
+    fn output_low(&self, dir_bit: u8, level_bit: u8) -> bool {
+        self.region == ConsoleRegion::Export
+            && (self.port_3f & dir_bit) == 0
+            && (self.port_3f & level_bit) == 0
+    }
+
     // Porta DC ($DC) - Entradas de Controle do Jogador 1 (e parte do Jogador 2)
     pub(crate) fn read_port_dc(&self) -> u8 {
         let mut port = 0xFF; // Invertido, apertar botão altera para 0
@@ -42,28 +62,45 @@ impl Joypad {
         if self.p1_b1 { port &= !0x10; }
         
         if self.p1_b2 { port &= !0x20; }
-        
+
+        // Bit 5 doubles as Port A TR: export consoles echo its output level.
+        if self.output_low(0x01, 0x10) { port &= !0x20; }
+
+        // Bits 6-7: Player 2 up/down
+        if self.p2_up { port &= !0x40; }
+        if self.p2_down { port &= !0x80; }
+
         port
     }
 
     // Porta DD ($DD) - Jogador 2 e outras funções
-    // Port $3F I/O Control bits:
-    //   Bit 1: Port A (P1) TH direction (1=output, 0=input)
-    //   Bit 5: Port A (P1) TH output level
-    //   Bit 3: Port B (P2) TH direction (1=output, 0=input)
-    //   Bit 7: Port B (P2) TH output level
+    // Port $3F I/O Control bits (direction: 1=input, 0=output):
+    //   Bit 0: Port A (P1) TR direction    Bit 4: Port A TR output level
+    //   Bit 1: Port A (P1) TH direction    Bit 5: Port A TH output level
+    //   Bit 2: Port B (P2) TR direction    Bit 6: Port B TR output level
+    //   Bit 3: Port B (P2) TH direction    Bit 7: Port B TH output level
+    // Export consoles read output levels back on $DC/$DD; Japanese consoles
+    // leave the pins floating high, which is how games tell them apart.
     pub(crate) fn read_port_dd(&self) -> u8 {
         let mut port = 0xFF;
 
+        // Bits 0-3: Player 2 left/right/button 1/button 2 (TR)
+        if self.p2_left { port &= !0x01; }
+        if self.p2_right { port &= !0x02; }
+        if self.p2_b1 { port &= !0x04; }
+        if self.p2_b2 || self.output_low(0x04, 0x40) {
+            port &= !0x08;
+        }
+
         // Bit 6: Player 1 TH pin — always reflects physical pin state.
         // When Light Phaser pulls TH low, this reads 0 regardless of
         // port $3F direction setting (the pin is physically pulled low).
-        if self.th_pin_low {
+        if self.th_pin_low || self.output_low(0x02, 0x20) {
             port &= !0x40;
         }
 
         // Bit 7: Player 2 TH pin
-        if (self.port_3f & 0x08) != 0 && (self.port_3f & 0x80) == 0 {
+        if self.output_low(0x08, 0x80) {
             port &= !0x80;
         }
 
@@ -79,6 +116,10 @@ impl Joypad {
         if !self.gg_start {
             port |= 0x80;
         }
+        // Bit 6: 0 = Japanese Game Gear, 1 = export
+        if self.region == ConsoleRegion::Japan {
+            port &= !0x40;
+        }
         port
     }
 }
@@ -147,6 +188,29 @@ mod tests {
         assert_eq!(dc & 0x22, 0x22, "down e b2 devem estar soltos");
     }
 
+    #[test]
+    fn p2_up_down_on_port_dc_bits_6_7() {
+        let mut j = Joypad::new();
+        j.p2_up = true;
+        assert_eq!(j.read_port_dc(), 0xBF);
+        j.p2_up = false;
+        j.p2_down = true;
+        assert_eq!(j.read_port_dc(), 0x7F);
+    }
+
+    #[test]
+    fn p2_directions_and_buttons_on_port_dd_bits_0_3() {
+        let mut j = Joypad::new();
+        j.p2_left = true;
+        j.p2_b2 = true;
+        assert_eq!(j.read_port_dd() & 0x0F, 0x06, "left (bit 0) e TR (bit 3) ativos");
+        j.p2_left = false;
+        j.p2_b2 = false;
+        j.p2_right = true;
+        j.p2_b1 = true;
+        assert_eq!(j.read_port_dd() & 0x0F, 0x09, "right (bit 1) e TL (bit 2) ativos");
+    }
+
     #[test]
     fn th_pin_low_clears_bit6_of_port_dd() {
         let mut j = Joypad::new();
@@ -179,4 +243,47 @@ mod tests {
         j.write_port_3f(0xAB);
         assert_eq!(j.port_3f, 0xAB);
     }
+
+    #[test]
+    fn export_console_echoes_th_output_levels() {
+        let mut j = Joypad::new();
+        // Both TH pins as outputs, driven high
+        j.write_port_3f(0xF5);
+        assert_eq!(j.read_port_dd() & 0xC0, 0xC0);
+        // Both TH pins as outputs, driven low
+        j.write_port_3f(0x55);
+        assert_eq!(j.read_port_dd() & 0xC0, 0x00, "export: níveis de saída TH refletidos em $DD");
+    }
+
+    #[test]
+    fn japanese_console_does_not_echo_th_output_levels() {
+        let mut j = Joypad::new();
+        j.region = ConsoleRegion::Japan;
+        j.write_port_3f(0x55);
+        assert_eq!(j.read_port_dd() & 0xC0, 0xC0, "japonês: pinos TH continuam em 1");
+    }
+
+    #[test]
+    fn export_console_echoes_tr_output_levels() {
+        let mut j = Joypad::new();
+        // Port A TR and Port B TR as outputs, driven low
+        j.write_port_3f(0xAA);
+        assert_eq!(j.read_port_dc() & 0x20, 0, "TR da porta A → bit 5 de $DC");
+        assert_eq!(j.read_port_dd() & 0x08, 0, "TR da porta B → bit 3 de $DD");
+    }
+
+    #[test]
+    fn th_input_direction_ignores_output_level() {
+        let mut j = Joypad::new();
+        // Direction bits set (inputs), output levels low → pins read high
+        j.write_port_3f(0x0F);
+        assert_eq!(j.read_port_dd() & 0xC8, 0xC8);
+    }
+
+    #[test]
+    fn japanese_game_gear_clears_bit6_of_port_00() {
+        let mut j = Joypad::new();
+        j.region = ConsoleRegion::Japan;
+        assert_eq!(j.read_port_00() & 0x40, 0);
+    }
 }
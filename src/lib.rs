@@ -0,0 +1,69 @@
+//! vibe-sms — Sega Master System / Game Gear / SG-1000 / SC-3000 emulator core.
+//!
+//! The core has no system-library dependencies. The desktop GUI (winit, glutin,
+//! egui, cpal, gilrs, rfd/GTK) lives behind the default `frontend` feature;
+//! build with `--no-default-features` to embed only the emulator. The `headless`
+//! feature adds a windowless runner (see [`headless`] and the `vibe-sms-headless` binary).
+//!
+//! ```no_run
+//! use vibe_sms::{Emulator, Platform, VideoStandard};
+//!
+//! let rom = std::fs::read("game.sms").unwrap();
+//! let mut emu = Emulator::new(rom, Platform::MasterSystem, VideoStandard::Ntsc, 44100.0);
+//! emu.set_input(false, false, false, true, false, false, false);
+//! let (_vblank, audio) = emu.step_frame();
+//! let frame = emu.get_framebuffer();
+//! let state = emu.save_state().serialize();
+//! # let _ = (audio, frame, state);
+//! ```
+
+mod audio;
+mod bus;
+mod platform;
+mod cheats;
+mod core;
+mod debugger;
+mod disasm;
+mod eeprom;
+mod gamedb;
+mod gdb;
+mod savestate;
+#[cfg(test)]
+mod test_util;
+mod joypad;
+mod mapper;
+mod memory;
+mod mmu;
+mod movie;
+mod patch;
+mod ram_search;
+mod rewind;
+mod rom_header;
+mod trace;
+mod vdp;
+mod vdp_view;
+
+#[cfg(feature = "frontend")]
+pub mod frontend;
+#[cfg(feature = "headless")]
+pub mod headless;
+
+pub use crate::cheats::{cheats_from_text, cheats_to_text, load_cheats, save_cheats, Cheat, CheatEffect};
+pub use crate::core::Emulator;
+pub use crate::debugger::{
+    Access, AddressSpace, Breakpoint, DebugBreak, Debugger, Registers, StepMode, WatchHit, Watchpoint,
+};
+pub use crate::gamedb::{GameDb, GameInfo, Peripheral, SaveMemory};
+pub use crate::gdb::GdbStub;
+pub use crate::mapper::MapperKind;
+pub use crate::memory::{find_bytes, parse_hex_bytes, MemoryRegion};
+pub use crate::movie::{FrameInput, Movie, MoviePlayer, MovieRecorder};
+pub use crate::patch::{apply_patch, find_patch};
+pub use crate::disasm::{port_name, Instruction};
+pub use crate::ram_search::{ram_cheats, Comparison, Operand, RamSearch, ValueFormat, ValueSize};
+pub use crate::rewind::RewindBuffer;
+pub use crate::platform::{ConsoleRegion, MediaSlot, Platform, VideoStandard, GG_H, GG_W, SMS_H, SMS_W};
+pub use crate::rom_header::{CodemastersHeader, HeaderRegion, RomHeader, RomInfo};
+pub use crate::savestate::SaveState;
+pub use crate::trace::{TraceOptions, Tracer};
+pub use crate::vdp_view::{Image, SpriteInfo, TileEntry, VdpSnapshot};
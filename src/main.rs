@@ -1,21 +1,40 @@
 #![cfg_attr(windows, windows_subsystem = "windows")]
 
-mod audio;
-mod bus;
-mod platform;
-mod core;
-mod eeprom;
-mod frontend;
-mod savestate;
-mod joypad;
-mod mmu;
-mod vdp;
-
-use frontend::launch_frontend;
+use vibe_sms::frontend::launch_frontend;
+use vibe_sms::{ConsoleRegion, GdbStub, Platform, TraceOptions, Tracer, VideoStandard};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
-    let rom_path = args.iter().skip(1).find(|a| !a.starts_with('-')).cloned();
+    let mut rom_path = None;
+    let mut it = args.iter().skip(1);
+    while let Some(a) = it.next() {
+        if TraceOptions::is_trace_arg(a) == Some(true) || a == "--gdb" || a == "--platform" || a == "--patch" {
+            it.next();
+        } else if !a.starts_with('-') && rom_path.is_none() {
+            rom_path = Some(a.clone());
+        }
+    }
+    let video = if args.iter().any(|a| a == "--pal") { VideoStandard::Pal } else { VideoStandard::Ntsc };
+    let region = if args.iter().any(|a| a == "--japan") { ConsoleRegion::Japan } else { ConsoleRegion::Export };
+
+    let platform = match args.iter().position(|a| a == "--platform").map(|i| args.get(i + 1)) {
+        None => None,
+        Some(name) => match name.and_then(|n| Platform::from_name(n)) {
+            Some(p) => Some(p),
+            None => {
+                eprintln!("error: --platform needs one of sms, gg, sg, sc");
+                std::process::exit(2);
+            }
+        },
+    };
+
+    let patch = args.iter().position(|a| a == "--patch").map(|i| match args.get(i + 1) {
+        Some(p) => std::path::PathBuf::from(p),
+        None => {
+            eprintln!("error: --patch needs a file");
+            std::process::exit(2);
+        }
+    });
 
     #[cfg(windows)]
     {
@@ -25,7 +44,34 @@ fn main() {
         }
     }
 
-    launch_frontend(rom_path);
+    let tracer = match TraceOptions::from_args(&args[1..]).and_then(|o| o.map(Tracer::create).transpose()) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(2);
+        }
+    };
+
+    let gdb_port = args.iter().position(|a| a == "--gdb").map(|i| args.get(i + 1).and_then(|p| p.parse::<u16>().ok()));
+    let gdb = match gdb_port {
+        None => None,
+        Some(None) => {
+            eprintln!("error: --gdb needs a port number");
+            std::process::exit(2);
+        }
+        Some(Some(port)) => match GdbStub::listen(port) {
+            Ok(stub) => {
+                println!("gdb: listening on 127.0.0.1:{}", stub.port());
+                Some(stub)
+            }
+            Err(e) => {
+                eprintln!("error: failed to listen on port {port}: {e}");
+                std::process::exit(2);
+            }
+        },
+    };
+
+    launch_frontend(rom_path, tracer, gdb, video, region, platform, patch);
 }
 
 /// Allocates a console window for debug output.
@@ -0,0 +1,486 @@
+//! Cartridge mappers. The [`Mmu`](crate::mmu::Mmu) owns work RAM, cart RAM
+//! and EEPROM; a [`Mapper`] decides what each CPU address in $0000–$BFFF
+//! sees and reacts to bank-switch writes.
+
+use crate::platform::Platform;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapperKind {
+    /// SG-1000 / SC-3000: ROM mapped flat from $0000, no banking.
+    None,
+    /// Sega 315-5235: bank registers at $FFFD–$FFFF, cart RAM control at $FFFC.
+    Sega,
+    /// Codemasters: bank registers at $0000/$4000/$8000, optional 8KB RAM at $A000.
+    Codemasters,
+    /// Korean single-bank: writes to $A000 select the bank at $8000.
+    Korean,
+    /// Korean MSX-style 8KB pages: registers $0000–$0003 map $8000, $A000, $4000, $6000.
+    Msx,
+    /// MSX mapper with the last 8KB page fixed at $0000 (Nemesis).
+    MsxNemesis,
+    /// Janggun-ui Adeul: 8KB pages plus 16KB registers at $FFFE/$FFFF whose
+    /// bit 6 serves the bank with each byte bit-reversed.
+    Janggun,
+    /// 4 Pak All Action: registers at $3FFE, $7FFF and $BFFF.
+    FourPak,
+}
+
+impl MapperKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MapperKind::None        => "None",
+            MapperKind::Sega        => "Sega",
+            MapperKind::Codemasters => "Codemasters",
+            MapperKind::Korean      => "Korean",
+            MapperKind::Msx         => "Korean MSX",
+            MapperKind::MsxNemesis  => "Korean MSX (Nemesis)",
+            MapperKind::Janggun     => "Janggun",
+            MapperKind::FourPak     => "4 Pak",
+        }
+    }
+}
+
+/// Where a CPU access in $0000–$BFFF lands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Mapped {
+    /// Offset into the ROM image (may be past its end: open bus).
+    Rom(usize),
+    /// Like `Rom`, but the byte is read with its bits reversed.
+    RomFlipped(usize),
+    /// Offset into cart RAM (always below 16KB).
+    CartRam(usize),
+}
+
+pub(crate) trait Mapper: Send {
+    fn kind(&self) -> MapperKind;
+
+    /// Maps a read of `addr` ($0000–$BFFF).
+    fn map(&self, addr: u16) -> Mapped;
+
+    /// Sees every CPU write, RAM included. Returns the cart RAM offset when
+    /// the write should be stored there.
+    fn write(&mut self, addr: u16, value: u8) -> Option<usize>;
+
+    /// Bank registers, for save states.
+    fn registers(&self) -> Vec<u8>;
+
+    fn set_registers(&mut self, regs: &[u8]);
+}
+
+/// Picks the mapper from the ROM contents: the Codemasters header, else the
+/// Sega mapper. Carts that can't be told apart come from the game database.
+pub(crate) fn detect(rom: &[u8], platform: Platform) -> MapperKind {
+    if platform.is_sg_family() {
+        MapperKind::None
+    } else if has_codemasters_header(rom) {
+        MapperKind::Codemasters
+    } else {
+        MapperKind::Sega
+    }
+}
+
+/// Codemasters carts store a checksum at $7FE6 and its complement
+/// (0x10000 − checksum) at $7FE8.
+pub(crate) fn has_codemasters_header(rom: &[u8]) -> bool {
+    if rom.len() < 0x8000 { return false; }
+    let word = |at: usize| u16::from_le_bytes([rom[at], rom[at + 1]]) as u32;
+    word(0x7FE6) + word(0x7FE8) == 0x10000
+}
+
+pub(crate) fn new_mapper(kind: MapperKind, rom_len: usize) -> Box<dyn Mapper> {
+    let banks = (rom_len / 0x4000).max(1);
+    let pages = (rom_len / 0x2000).max(1);
+    match kind {
+        MapperKind::None        => Box::new(FlatMapper),
+        MapperKind::Sega        => Box::new(SegaMapper { banks, ram_control: 0, slots: [0, 1, 2] }),
+        MapperKind::Codemasters => Box::new(CodemastersMapper { banks, slots: [0, 1, 0], ram_enabled: false }),
+        MapperKind::Korean      => Box::new(KoreanMapper { banks, slot2: 2 }),
+        MapperKind::Msx         => Box::new(MsxMapper { pages, regs: [0; 4], nemesis: false }),
+        MapperKind::MsxNemesis  => Box::new(MsxMapper { pages, regs: [0; 4], nemesis: true }),
+        MapperKind::Janggun     => Box::new(JanggunMapper { pages, regs: [2, 3, 4, 5], flipped: [false; 4] }),
+        MapperKind::FourPak     => Box::new(FourPakMapper { banks, slots: [0, 1, 2] }),
+    }
+}
+
+fn rom_offset(banks: usize, bank: u8, addr: u16) -> usize {
+    (bank as usize % banks) * 0x4000 + (addr as usize & 0x3FFF)
+}
+
+fn page_offset(pages: usize, page: u8, addr: u16) -> usize {
+    (page as usize % pages) * 0x2000 + (addr as usize & 0x1FFF)
+}
+
+// ── None ──
+
+struct FlatMapper;
+
+impl Mapper for FlatMapper {
+    fn kind(&self) -> MapperKind { MapperKind::None }
+
+    fn map(&self, addr: u16) -> Mapped {
+        Mapped::Rom(addr as usize)
+    }
+
+    fn write(&mut self, _addr: u16, _value: u8) -> Option<usize> {
+        // Writes to ROM area are silently ignored
+        None
+    }
+
+    fn registers(&self) -> Vec<u8> { Vec::new() }
+
+    fn set_registers(&mut self, _regs: &[u8]) {}
+}
+
+// ── Sega ──
+
+struct SegaMapper {
+    banks:       usize,
+    ram_control: u8,      // $FFFC
+    slots:       [u8; 3], // $FFFD–$FFFF
+}
+
+impl SegaMapper {
+    fn ram_offset(&self, addr: u16) -> usize {
+        let ram_page = if (self.ram_control & 0x04) != 0 { 1 } else { 0 };
+        ((ram_page * 0x2000) + (addr as usize - 0x8000)) % 0x4000
+    }
+}
+
+impl Mapper for SegaMapper {
+    fn kind(&self) -> MapperKind { MapperKind::Sega }
+
+    fn map(&self, addr: u16) -> Mapped {
+        match addr {
+            // Primeiros 1KB são FIXOS no Banco 0
+            0x0000..=0x03FF => Mapped::Rom(addr as usize),
+            0x0400..=0x3FFF => Mapped::Rom(rom_offset(self.banks, self.slots[0], addr)),
+            0x4000..=0x7FFF => Mapped::Rom(rom_offset(self.banks, self.slots[1], addr)),
+            // Bit 3 ($08): RAM do cartucho no lugar do slot 2
+            _ if (self.ram_control & 0x08) != 0 => Mapped::CartRam(self.ram_offset(addr)),
+            _ => Mapped::Rom(rom_offset(self.banks, self.slots[2], addr)),
+        }
+    }
+
+    fn write(&mut self, addr: u16, value: u8) -> Option<usize> {
+        match addr {
+            // Bit 0 ($01): write-protect (1 = protegido)
+            0x8000..=0xBFFF if (self.ram_control & 0x09) == 0x08 => return Some(self.ram_offset(addr)),
+            // Mappers só recebem escritas, independentes da RAM física espelhada
+            0xFFFC => self.ram_control = value,
+            0xFFFD..=0xFFFF => self.slots[(addr - 0xFFFD) as usize] = value,
+            _ => {}
+        }
+        None
+    }
+
+    fn registers(&self) -> Vec<u8> {
+        vec![self.ram_control, self.slots[0], self.slots[1], self.slots[2]]
+    }
+
+    fn set_registers(&mut self, regs: &[u8]) {
+        if let [control, s0, s1, s2] = *regs {
+            self.ram_control = control;
+            self.slots = [s0, s1, s2];
+        }
+    }
+}
+
+// ── Codemasters ──
+
+struct CodemastersMapper {
+    banks:       usize,
+    slots:       [u8; 3], // $0000, $4000, $8000
+    /// Bit 7 of the $4000 register maps 8KB of cart RAM at $A000–$BFFF.
+    ram_enabled: bool,
+}
+
+impl Mapper for CodemastersMapper {
+    fn kind(&self) -> MapperKind { MapperKind::Codemasters }
+
+    fn map(&self, addr: u16) -> Mapped {
+        match addr {
+            0xA000..=0xBFFF if self.ram_enabled => Mapped::CartRam(addr as usize - 0xA000),
+            _ => Mapped::Rom(rom_offset(self.banks, self.slots[addr as usize >> 14], addr)),
+        }
+    }
+
+    fn write(&mut self, addr: u16, value: u8) -> Option<usize> {
+        match addr {
+            0x0000 => self.slots[0] = value,
+            0x4000 => {
+                self.ram_enabled = value & 0x80 != 0;
+                self.slots[1] = value & 0x7F;
+            }
+            0x8000 => self.slots[2] = value,
+            0xA000..=0xBFFF if self.ram_enabled => return Some(addr as usize - 0xA000),
+            _ => {}
+        }
+        None
+    }
+
+    fn registers(&self) -> Vec<u8> {
+        vec![self.slots[0], self.slots[1], self.slots[2], self.ram_enabled as u8]
+    }
+
+    fn set_registers(&mut self, regs: &[u8]) {
+        if let [s0, s1, s2, ram] = *regs {
+            self.slots = [s0, s1, s2];
+            self.ram_enabled = ram != 0;
+        }
+    }
+}
+
+// ── Korean ──
+
+struct KoreanMapper {
+    banks: usize,
+    slot2: u8, // $A000
+}
+
+impl Mapper for KoreanMapper {
+    fn kind(&self) -> MapperKind { MapperKind::Korean }
+
+    fn map(&self, addr: u16) -> Mapped {
+        match addr {
+            0x0000..=0x7FFF => Mapped::Rom(addr as usize),
+            _ => Mapped::Rom(rom_offset(self.banks, self.slot2, addr)),
+        }
+    }
+
+    fn write(&mut self, addr: u16, value: u8) -> Option<usize> {
+        if addr == 0xA000 { self.slot2 = value; }
+        None
+    }
+
+    fn registers(&self) -> Vec<u8> { vec![self.slot2] }
+
+    fn set_registers(&mut self, regs: &[u8]) {
+        if let [slot2] = *regs { self.slot2 = slot2; }
+    }
+}
+
+// ── Korean MSX ──
+
+struct MsxMapper {
+    pages:   usize,
+    /// 8KB pages for $8000, $A000, $4000 and $6000 (registers $0000–$0003).
+    regs:    [u8; 4],
+    nemesis: bool,
+}
+
+impl Mapper for MsxMapper {
+    fn kind(&self) -> MapperKind {
+        if self.nemesis { MapperKind::MsxNemesis } else { MapperKind::Msx }
+    }
+
+    fn map(&self, addr: u16) -> Mapped {
+        let page = match addr {
+            0x0000..=0x1FFF if self.nemesis => 0x0F,
+            0x0000..=0x3FFF => return Mapped::Rom(addr as usize),
+            0x4000..=0x5FFF => self.regs[2],
+            0x6000..=0x7FFF => self.regs[3],
+            0x8000..=0x9FFF => self.regs[0],
+            _ => self.regs[1],
+        };
+        Mapped::Rom(page_offset(self.pages, page, addr))
+    }
+
+    fn write(&mut self, addr: u16, value: u8) -> Option<usize> {
+        if addr <= 0x0003 { self.regs[addr as usize] = value; }
+        None
+    }
+
+    fn registers(&self) -> Vec<u8> { self.regs.to_vec() }
+
+    fn set_registers(&mut self, regs: &[u8]) {
+        if let Ok(regs) = regs.try_into() { self.regs = regs; }
+    }
+}
+
+// ── Janggun ──
+
+struct JanggunMapper {
+    pages:   usize,
+    /// 8KB pages for $4000, $6000, $8000 and $A000.
+    regs:    [u8; 4],
+    flipped: [bool; 4],
+}
+
+impl Mapper for JanggunMapper {
+    fn kind(&self) -> MapperKind { MapperKind::Janggun }
+
+    fn map(&self, addr: u16) -> Mapped {
+        if addr < 0x4000 { return Mapped::Rom(addr as usize); }
+        let slot = (addr as usize >> 13) - 2;
+        let offset = page_offset(self.pages, self.regs[slot], addr);
+        if self.flipped[slot] { Mapped::RomFlipped(offset) } else { Mapped::Rom(offset) }
+    }
+
+    fn write(&mut self, addr: u16, value: u8) -> Option<usize> {
+        match addr {
+            0x4000 | 0x6000 | 0x8000 | 0xA000 => self.regs[(addr as usize >> 13) - 2] = value & 0x3F,
+            // 16KB registers: two 8KB slots each; bit 6 reverses the bits
+            0xFFFE | 0xFFFF => {
+                let slot = if addr == 0xFFFE { 0 } else { 2 };
+                let page = (value & 0x3F) * 2;
+                self.regs[slot] = page;
+                self.regs[slot + 1] = page + 1;
+                self.flipped[slot] = value & 0x40 != 0;
+                self.flipped[slot + 1] = value & 0x40 != 0;
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn registers(&self) -> Vec<u8> {
+        let flips = self.flipped.iter().enumerate().fold(0u8, |acc, (i, &f)| acc | (f as u8) << i);
+        vec![self.regs[0], self.regs[1], self.regs[2], self.regs[3], flips]
+    }
+
+    fn set_registers(&mut self, regs: &[u8]) {
+        if let [r0, r1, r2, r3, flips] = *regs {
+            self.regs = [r0, r1, r2, r3];
+            self.flipped = std::array::from_fn(|i| flips & (1 << i) != 0);
+        }
+    }
+}
+
+// ── 4 Pak ──
+
+struct FourPakMapper {
+    banks: usize,
+    slots: [u8; 3], // $3FFE, $7FFF, $BFFF
+}
+
+impl Mapper for FourPakMapper {
+    fn kind(&self) -> MapperKind { MapperKind::FourPak }
+
+    fn map(&self, addr: u16) -> Mapped {
+        Mapped::Rom(rom_offset(self.banks, self.slots[addr as usize >> 14], addr))
+    }
+
+    fn write(&mut self, addr: u16, value: u8) -> Option<usize> {
+        match addr {
+            0x3FFE => self.slots[0] = value,
+            0x7FFF => self.slots[1] = value,
+            // O slot 2 fica no mesmo jogo (bloco de 256KB) que o slot 0
+            0xBFFF => self.slots[2] = (self.slots[0] & 0x30).wrapping_add(value),
+            _ => {}
+        }
+        None
+    }
+
+    fn registers(&self) -> Vec<u8> { self.slots.to_vec() }
+
+    fn set_registers(&mut self, regs: &[u8]) {
+        if let Ok(slots) = regs.try_into() { self.slots = slots; }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ROM com `banks` bancos, cada byte igual ao número do banco, e um
+    /// cabeçalho Codemasters válido (checksum 0x1234).
+    fn codemasters_rom(banks: usize) -> Vec<u8> {
+        let mut rom: Vec<u8> = (0..banks).flat_map(|b| vec![b as u8; 0x4000]).collect();
+        rom[0x7FE6..0x7FEA].copy_from_slice(&[0x34, 0x12, 0xCC, 0xED]); // 0x1234 + 0xEDCC = 0x10000
+        rom
+    }
+
+    #[test]
+    fn detects_codemasters_header() {
+        assert_eq!(detect(&codemasters_rom(8), Platform::MasterSystem), MapperKind::Codemasters);
+        assert_eq!(detect(&codemasters_rom(8), Platform::GameGear), MapperKind::Codemasters);
+        assert_eq!(detect(&vec![0u8; 0x20000], Platform::MasterSystem), MapperKind::Sega, "sem cabeçalho");
+        assert_eq!(detect(&vec![0u8; 0x4000], Platform::MasterSystem), MapperKind::Sega, "ROM pequena demais");
+        assert_eq!(detect(&codemasters_rom(2), Platform::Sg1000), MapperKind::None);
+    }
+
+    #[test]
+    fn codemasters_banks_every_slot() {
+        let mut m = new_mapper(MapperKind::Codemasters, 8 * 0x4000);
+        assert_eq!(m.map(0x0000), Mapped::Rom(0), "slot 0 não tem 1KB fixo");
+        assert_eq!(m.map(0x8000), Mapped::Rom(0), "slot 2 começa no banco 0");
+        m.write(0x0000, 3);
+        m.write(0x4000, 5);
+        m.write(0x8000, 9); // 9 % 8 = 1
+        assert_eq!(m.map(0x0000), Mapped::Rom(3 * 0x4000));
+        assert_eq!(m.map(0x7FFF), Mapped::Rom(5 * 0x4000 + 0x3FFF));
+        assert_eq!(m.map(0x8001), Mapped::Rom(0x4001));
+        assert_eq!(m.write(0xFFFF, 2), None, "registradores Sega não existem");
+        assert_eq!(m.map(0x8000), Mapped::Rom(0x4000));
+    }
+
+    #[test]
+    fn codemasters_ram_at_a000() {
+        let mut m = new_mapper(MapperKind::Codemasters, 8 * 0x4000);
+        assert_eq!(m.write(0xA010, 0x55), None, "RAM desligada");
+        m.write(0x4000, 0x82);
+        assert_eq!(m.map(0x4000), Mapped::Rom(2 * 0x4000), "bit 7 não entra no banco");
+        assert_eq!(m.map(0xA010), Mapped::CartRam(0x10));
+        assert_eq!(m.map(0x9FFF), Mapped::Rom(0x1FFF), "$8000–$9FFF continua ROM");
+        assert_eq!(m.write(0xA010, 0x55), Some(0x10));
+
+        let regs = m.registers();
+        let mut restored = new_mapper(MapperKind::Codemasters, 8 * 0x4000);
+        restored.set_registers(&regs);
+        assert_eq!(restored.map(0xA010), Mapped::CartRam(0x10));
+    }
+
+    #[test]
+    fn korean_bank_at_a000() {
+        let mut m = new_mapper(MapperKind::Korean, 8 * 0x4000);
+        assert_eq!(m.map(0x4000), Mapped::Rom(0x4000));
+        m.write(0xFFFF, 5);
+        assert_eq!(m.map(0x8000), Mapped::Rom(2 * 0x4000), "registradores Sega ignorados");
+        m.write(0xA000, 5);
+        assert_eq!(m.map(0x8123), Mapped::Rom(5 * 0x4000 + 0x123));
+    }
+
+    #[test]
+    fn msx_pages_in_8kb_steps() {
+        let mut m = new_mapper(MapperKind::Msx, 16 * 0x2000);
+        m.write(0x0000, 4); // $8000
+        m.write(0x0001, 5); // $A000
+        m.write(0x0002, 6); // $4000
+        m.write(0x0003, 7); // $6000
+        assert_eq!(m.map(0x1234), Mapped::Rom(0x1234), "$0000–$3FFF fixo");
+        assert_eq!(m.map(0x4001), Mapped::Rom(6 * 0x2000 + 1));
+        assert_eq!(m.map(0x6001), Mapped::Rom(7 * 0x2000 + 1));
+        assert_eq!(m.map(0x8001), Mapped::Rom(4 * 0x2000 + 1));
+        assert_eq!(m.map(0xBFFF), Mapped::Rom(5 * 0x2000 + 0x1FFF));
+
+        let nemesis = new_mapper(MapperKind::MsxNemesis, 16 * 0x2000);
+        assert_eq!(nemesis.map(0x0010), Mapped::Rom(15 * 0x2000 + 0x10), "última página em $0000");
+        assert_eq!(nemesis.map(0x2010), Mapped::Rom(0x2010));
+    }
+
+    #[test]
+    fn janggun_flips_16kb_banks() {
+        let mut m = new_mapper(MapperKind::Janggun, 32 * 0x2000);
+        m.write(0x6000, 9);
+        assert_eq!(m.map(0x6001), Mapped::Rom(9 * 0x2000 + 1));
+        m.write(0xFFFF, 0x40 | 3);
+        assert_eq!(m.map(0x8001), Mapped::RomFlipped(6 * 0x2000 + 1));
+        assert_eq!(m.map(0xA001), Mapped::RomFlipped(7 * 0x2000 + 1));
+        assert_eq!(m.map(0x4001), Mapped::Rom(0x4001), "$FFFE não mudou");
+
+        let mut restored = new_mapper(MapperKind::Janggun, 32 * 0x2000);
+        restored.set_registers(&m.registers());
+        assert_eq!(restored.map(0xA001), Mapped::RomFlipped(7 * 0x2000 + 1));
+    }
+
+    #[test]
+    fn four_pak_slot2_follows_slot0_game() {
+        let mut m = new_mapper(MapperKind::FourPak, 64 * 0x4000);
+        m.write(0x3FFE, 0x12);
+        m.write(0x7FFF, 0x13);
+        m.write(0xBFFF, 0x02);
+        assert_eq!(m.map(0x0000), Mapped::Rom(0x12 * 0x4000));
+        assert_eq!(m.map(0x4000), Mapped::Rom(0x13 * 0x4000));
+        assert_eq!(m.map(0x8000), Mapped::Rom(0x12 * 0x4000), "0x10 + 2");
+    }
+}
@@ -0,0 +1,196 @@
+//! Raw access to the machine's memories for the memory viewer / hex editor.
+//! Each [`MemoryRegion`] is a flat byte array; writes go straight into the
+//! running machine.
+
+use crate::core::Emulator;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MemoryRegion {
+    /// Work RAM at $C000 (1KB on SG-1000, 2KB on SC-3000, 8KB otherwise).
+    Ram,
+    /// Battery-backed cartridge RAM, both 8KB pages.
+    CartRam,
+    /// 93C46 serial EEPROM contents.
+    Eeprom,
+    Vram,
+    Cram,
+    VdpRegisters,
+    /// One 16KB ROM bank.
+    Rom(u16),
+}
+
+impl MemoryRegion {
+    pub fn name(&self) -> String {
+        match self {
+            MemoryRegion::Ram          => "RAM".to_string(),
+            MemoryRegion::CartRam      => "Cart RAM".to_string(),
+            MemoryRegion::Eeprom       => "EEPROM".to_string(),
+            MemoryRegion::Vram         => "VRAM".to_string(),
+            MemoryRegion::Cram         => "CRAM".to_string(),
+            MemoryRegion::VdpRegisters => "VDP registers".to_string(),
+            MemoryRegion::Rom(bank)    => format!("ROM bank {bank}"),
+        }
+    }
+
+    /// Address shown for offset 0: the CPU address for RAM, the file
+    /// offset for ROM banks, 0 for everything else.
+    pub fn base_address(&self) -> u32 {
+        match self {
+            MemoryRegion::Ram       => 0xC000,
+            MemoryRegion::Rom(bank) => *bank as u32 * 0x4000,
+            _ => 0,
+        }
+    }
+}
+
+impl Emulator {
+    /// Regions present on this machine, in display order.
+    pub fn memory_regions(&self) -> Vec<MemoryRegion> {
+        let bus = self.cpu.io.bus.borrow();
+        let mut regions = vec![MemoryRegion::Ram];
+        if bus.mmu.platform.is_sg_family() {
+            regions.extend([MemoryRegion::Vram, MemoryRegion::VdpRegisters]);
+        } else {
+            regions.push(if bus.mmu.eeprom.is_some() { MemoryRegion::Eeprom } else { MemoryRegion::CartRam });
+            regions.extend([MemoryRegion::Vram, MemoryRegion::Cram, MemoryRegion::VdpRegisters]);
+        }
+        let banks = bus.mmu.rom.len().div_ceil(0x4000);
+        regions.extend((0..banks as u16).map(MemoryRegion::Rom));
+        regions
+    }
+
+    /// Copy of a region's bytes (empty if the region doesn't exist).
+    pub fn read_region(&self, region: MemoryRegion) -> Vec<u8> {
+        let bus = self.cpu.io.bus.borrow();
+        match region {
+            MemoryRegion::Ram          => bus.mmu.ram[..bus.mmu.ram_size()].to_vec(),
+            MemoryRegion::CartRam      => bus.mmu.cart_ram[..bus.mmu.cart_ram_size].to_vec(),
+            MemoryRegion::Eeprom       => bus.mmu.eeprom.as_ref().map(|e| e.data.to_vec()).unwrap_or_default(),
+            MemoryRegion::Vram         => bus.vdp.vram.to_vec(),
+            MemoryRegion::Cram         => bus.vdp.cram[..self.cram_size()].to_vec(),
+            MemoryRegion::VdpRegisters => bus.vdp.registers[..11].to_vec(),
+            MemoryRegion::Rom(bank) => {
+                let start = (bank as usize * 0x4000).min(bus.mmu.rom.len());
+                let end = (start + 0x4000).min(bus.mmu.rom.len());
+                bus.mmu.rom[start..end].to_vec()
+            }
+        }
+    }
+
+    /// Writes one byte into a region. Returns false if `offset` is out of range.
+    pub fn write_region(&mut self, region: MemoryRegion, offset: usize, value: u8) -> bool {
+        let cram_size = self.cram_size();
+        let mut bus = self.cpu.io.bus.borrow_mut();
+        let ram_size = bus.mmu.ram_size();
+        match region {
+            MemoryRegion::Ram if offset < ram_size => bus.mmu.ram[offset] = value,
+            MemoryRegion::CartRam if offset < bus.mmu.cart_ram_size => {
+                bus.mmu.cart_ram[offset] = value;
+                bus.mmu.sram_dirty = true;
+            }
+            MemoryRegion::Eeprom => match bus.mmu.eeprom.as_mut() {
+                Some(e) if offset < e.data.len() => {
+                    e.data[offset] = value;
+                    e.dirty = true;
+                }
+                _ => return false,
+            },
+            MemoryRegion::Vram if offset < bus.vdp.vram.len() => bus.vdp.vram[offset] = value,
+            MemoryRegion::Cram if offset < cram_size => bus.vdp.cram[offset] = value,
+            MemoryRegion::VdpRegisters if offset < 11 => bus.vdp.registers[offset] = value,
+            MemoryRegion::Rom(bank) => {
+                let addr = bank as usize * 0x4000 + offset;
+                if offset >= 0x4000 || addr >= bus.mmu.rom.len() { return false; }
+                bus.mmu.rom[addr] = value;
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn cram_size(&self) -> usize {
+        if self.platform() == crate::Platform::GameGear { 64 } else { 32 }
+    }
+}
+
+/// Parses "3E 20", "3e20" or "$3E,$20" into bytes.
+pub fn parse_hex_bytes(text: &str) -> Option<Vec<u8>> {
+    let digits: String = text.chars().filter(|c| !c.is_whitespace() && *c != ',' && *c != '$').collect();
+    if digits.is_empty() || !digits.len().is_multiple_of(2) { return None; }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(digits.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// First occurrence of `needle` at or after `from`, wrapping to the start.
+pub fn find_bytes(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if needle.is_empty() || needle.len() > haystack.len() { return None; }
+    let last = haystack.len() - needle.len();
+    let at = |i: usize| haystack[i..i + needle.len()] == *needle;
+    (from.min(last + 1)..=last).find(|&i| at(i)).or_else(|| (0..from.min(last + 1)).find(|&i| at(i)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Platform, VideoStandard};
+    use crate::test_util::with_large_stack;
+
+    fn emu(platform: Platform) -> Emulator {
+        Emulator::new(vec![0u8; 0x10000], platform, VideoStandard::Ntsc, 44100.0)
+    }
+
+    #[test]
+    fn lists_regions_per_platform() {
+        with_large_stack(|| {
+            let sms = emu(Platform::MasterSystem);
+            let regions = sms.memory_regions();
+            assert!(regions.contains(&MemoryRegion::CartRam));
+            assert!(regions.contains(&MemoryRegion::Cram));
+            assert_eq!(regions.iter().filter(|r| matches!(r, MemoryRegion::Rom(_))).count(), 4, "64KB = 4 bancos");
+            assert_eq!(sms.read_region(MemoryRegion::Cram).len(), 32);
+
+            let gg = emu(Platform::GameGear);
+            assert_eq!(gg.read_region(MemoryRegion::Cram).len(), 64, "CRAM do GG tem 64 bytes");
+
+            let sg = emu(Platform::Sg1000);
+            assert!(!sg.memory_regions().contains(&MemoryRegion::Cram), "TMS9918 não tem CRAM");
+            assert_eq!(sg.read_region(MemoryRegion::Ram).len(), 1024);
+            assert_eq!(emu(Platform::Sc3000).read_region(MemoryRegion::Ram).len(), 2048);
+        });
+    }
+
+    #[test]
+    fn writes_reach_the_running_machine() {
+        with_large_stack(|| {
+            let mut sg = emu(Platform::Sg1000);
+            assert!(sg.write_region(MemoryRegion::Ram, 0x10, 0x5A));
+            // RAM de 1KB espelhada: $C410 é o mesmo byte que $C010
+            assert_eq!(sg.peek(0xC410), 0x5A);
+            assert!(!sg.write_region(MemoryRegion::Ram, 1024, 0), "fora da RAM real");
+
+            let mut sms = emu(Platform::MasterSystem);
+            assert!(sms.write_region(MemoryRegion::Rom(1), 0x0123, 0xC9));
+            assert_eq!(sms.read_region(MemoryRegion::Rom(1))[0x0123], 0xC9);
+            assert_eq!(sms.peek(0x4123), 0xC9, "banco 1 mapeado no slot 1");
+            assert!(sms.write_region(MemoryRegion::CartRam, 0, 1));
+            assert!(sms.is_sram_dirty(), "edição da cart RAM deve ser salva");
+            assert!(!sms.write_region(MemoryRegion::Eeprom, 0, 1), "sem EEPROM neste cartucho");
+        });
+    }
+
+    #[test]
+    fn parses_and_finds_byte_patterns() {
+        assert_eq!(parse_hex_bytes("3E 20"), Some(vec![0x3E, 0x20]));
+        assert_eq!(parse_hex_bytes("$3e,$20"), Some(vec![0x3E, 0x20]));
+        assert_eq!(parse_hex_bytes("3E2"), None);
+        assert_eq!(parse_hex_bytes("zz"), None);
+
+        let data = [1, 2, 3, 1, 2, 3];
+        assert_eq!(find_bytes(&data, &[1, 2], 0), Some(0));
+        assert_eq!(find_bytes(&data, &[1, 2], 1), Some(3));
+        assert_eq!(find_bytes(&data, &[1, 2], 4), Some(0), "busca recomeça do início");
+        assert_eq!(find_bytes(&data, &[9], 0), None);
+    }
+}
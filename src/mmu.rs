@@ -1,15 +1,7 @@
 use crate::eeprom::Eeprom93C46;
-use crate::platform::Platform;
-
-/// Jogos GG que usam EEPROM 93C46 em vez de SRAM (identificados por CRC32 do ROM).
-/// Fonte: Gearsystem game_db.h
-const EEPROM_CRCS: &[u32] = &[
-    0x36EBCD6D, // Majors Pro Baseball
-    0x2DA8E943, // Pro Yakyuu GG League
-    0x3D8D0DD6, // World Series Baseball [v0]
-    0xBB38CFD7, // World Series Baseball [v1]
-    0x578A8A38, // World Series Baseball '95
-];
+use crate::gamedb::{GameInfo, SaveMemory};
+use crate::mapper::{self, Mapped, Mapper, MapperKind};
+use crate::platform::{MediaSlot, Platform};
 
 /// Calcula o CRC32 (IEEE 802.3 / standard) dos dados do ROM.
 /// Exportado para testes.
@@ -29,38 +21,86 @@ fn crc32(data: &[u8]) -> u32 {
     !crc
 }
 
+// Memory control register (port $3E): a set bit *disables* the device.
+const MC_EXPANSION: u8 = 0x80;
+const MC_CARTRIDGE: u8 = 0x40;
+const MC_CARD:      u8 = 0x20;
+const MC_WORK_RAM:  u8 = 0x10;
+const MC_BIOS:      u8 = 0x08;
+const MC_IO:        u8 = 0x04;
+
+/// Power-on value with a BIOS: only the BIOS, work RAM and I/O enabled.
+const MC_BOOT_BIOS: u8 = MC_EXPANSION | MC_CARTRIDGE | MC_CARD | 0x03;
+/// Value the BIOS leaves behind when it starts a game in `media`.
+fn mc_boot_game(media: MediaSlot) -> u8 {
+    (MC_EXPANSION | MC_CARTRIDGE | MC_CARD | MC_BIOS | 0x03) & !mc_slot_bit(media)
+}
+
+fn mc_slot_bit(media: MediaSlot) -> u8 {
+    match media {
+        MediaSlot::Cartridge => MC_CARTRIDGE,
+        MediaSlot::Card      => MC_CARD,
+        MediaSlot::Expansion => MC_EXPANSION,
+    }
+}
+
+/// The device answering reads and writes in $0000–$BFFF.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Slot {
+    Bios,
+    /// The game image, in whichever slot it was inserted.
+    Game,
+    /// Nothing enabled, or only empty slots: open bus.
+    Empty,
+}
+
+/// Console boot ROM with its own mapper (the SMS2 BIOS with Alex Kidd is 128KB).
+pub(crate) struct Bios {
+    pub(crate) rom:    Vec<u8>,
+    pub(crate) mapper: Box<dyn Mapper>,
+}
+
 pub(crate) struct Mmu {
     pub(crate) ram: [u8; 8192],       // 8KB Work RAM ($C000–$DFFF) — SG/SC only uses 1–2KB
     pub(crate) rom: Vec<u8>,          // O Cartucho de Jogo
     pub(crate) cart_ram: [u8; 16384], // Até 16KB de RAM no Cartucho (SRAM) — SMS/GG only
+    /// Cart RAM actually fitted; accesses wrap at this size.
+    pub(crate) cart_ram_size: usize,
     pub(crate) sram_dirty: bool,
 
     // EEPROM 93C46 (apenas para jogos GG que a utilizam)
     pub(crate) eeprom: Option<Eeprom93C46>,
 
-    // Registradores do Sega Mapper (SMS/GG only — unused for SG/SC)
-    pub(crate) ram_control: u8,    // $FFFC
-    pub(crate) rom_bank_0: usize,  // $FFFD
-    pub(crate) rom_bank_1: usize,  // $FFFE
-    pub(crate) rom_bank_2: usize,  // $FFFF
+    /// Bank switching for $0000–$BFFF.
+    pub(crate) mapper: Box<dyn Mapper>,
+
+    pub(crate) bios: Option<Bios>,
+    /// Slot the game image sits in.
+    pub(crate) media: MediaSlot,
+    /// Port $3E; only honoured when a BIOS is installed.
+    pub(crate) memory_control: u8,
 
     pub(crate) platform: Platform,
+    /// CRC32 of the ROM image as loaded (before padding).
+    pub(crate) rom_crc: u32,
+    /// Length of the ROM image as loaded; `rom` may be padded past it.
+    pub(crate) rom_size: usize,
+    /// Enabled Game Genie patches, checked on every ROM read.
+    pub(crate) rom_patches: Vec<RomPatch>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct RomPatch {
+    pub(crate) addr:    u16,
+    pub(crate) value:   u8,
+    pub(crate) compare: Option<u8>,
 }
 
 impl Mmu {
     pub(crate) fn new(mut rom: Vec<u8>, platform: Platform) -> Self {
-        // EEPROM detection: only for Game Gear
-        let eeprom = if platform.is_gg() {
-            let rom_crc = crc32(&rom);
-            if EEPROM_CRCS.contains(&rom_crc) {
-                println!("EEPROM 93C46 detectada (CRC32: {:#010X})", rom_crc);
-                Some(Eeprom93C46::new())
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+        let rom_crc = crc32(&rom);
+        let rom_size = rom.len();
+        let mapper_kind = mapper::detect(&rom, platform);
 
         // SMS/GG: pad to minimum 3 banks (48KB) for mapper safety.
         // SG/SC: no mapper — ROM accessed flat; no minimum padding needed.
@@ -70,135 +110,191 @@ impl Mmu {
 
         Self {
             ram: [0; 8192],
+            mapper: mapper::new_mapper(mapper_kind, rom.len()),
             rom,
             cart_ram: [0; 16384],
+            cart_ram_size: 16384,
             sram_dirty: false,
-            eeprom,
-            ram_control: 0,
-            rom_bank_0: 0,
-            rom_bank_1: 1,
-            rom_bank_2: 2,
+            // Fitted from the game database by `Emulator::new` (see `apply_game`)
+            eeprom: None,
+            bios: None,
+            media: MediaSlot::Cartridge,
+            memory_control: mc_boot_game(MediaSlot::Cartridge),
             platform,
+            rom_crc,
+            rom_size,
+            rom_patches: Vec::new(),
         }
     }
 
+    /// Swaps in the mapper and save memory a database entry asks for.
+    pub(crate) fn apply_game(&mut self, game: &GameInfo) {
+        if let Some(kind) = game.mapper {
+            self.mapper = mapper::new_mapper(kind, self.rom.len());
+        }
+        match game.save {
+            Some(SaveMemory::Sram(size)) => {
+                self.cart_ram_size = size.clamp(1, self.cart_ram.len());
+                self.eeprom = None;
+            }
+            Some(SaveMemory::Eeprom) => { self.eeprom.get_or_insert_with(Eeprom93C46::new); }
+            None => {}
+        }
+    }
+
+    /// Installs a boot ROM and selects it, as at power-on.
+    pub(crate) fn set_bios(&mut self, rom: Vec<u8>) {
+        let mapper = mapper::new_mapper(MapperKind::Sega, rom.len());
+        self.bios = Some(Bios { rom, mapper });
+        self.memory_control = MC_BOOT_BIOS;
+    }
+
+    /// Moves the game image to another slot. Cards and expansion media
+    /// have no mapper.
+    pub(crate) fn set_media(&mut self, media: MediaSlot) {
+        self.media = media;
+        if media != MediaSlot::Cartridge {
+            self.mapper = mapper::new_mapper(MapperKind::None, self.rom.len());
+        }
+        if self.bios.is_none() {
+            self.memory_control = mc_boot_game(media);
+        }
+    }
+
+    /// Port $3E write. Without a BIOS the cartridge stays mapped whatever
+    /// the game writes, as before the register was emulated.
+    pub(crate) fn write_memory_control(&mut self, value: u8) {
+        if self.bios.is_some() {
+            self.memory_control = value;
+        }
+    }
+
+    fn enabled(&self, device: u8) -> bool {
+        self.memory_control & device == 0
+    }
+
+    /// False while port $3E has the I/O chip (controller ports) disabled.
+    pub(crate) fn io_enabled(&self) -> bool {
+        self.enabled(MC_IO)
+    }
+
+    /// Device selected for `addr` in $0000–$BFFF. The Game Gear BIOS is
+    /// 1KB overlaid on the cartridge at $0000–$03FF; on the Master System
+    /// the BIOS takes priority over the game, and the other slots are empty.
+    pub(crate) fn slot_at(&self, addr: u16) -> Slot {
+        if self.bios.is_none() {
+            Slot::Game
+        } else if self.platform.is_gg() {
+            if self.enabled(MC_BIOS) && addr < 0x0400 { Slot::Bios } else { Slot::Game }
+        } else if self.enabled(MC_BIOS) {
+            Slot::Bios
+        } else if self.enabled(mc_slot_bit(self.media)) {
+            Slot::Game
+        } else {
+            Slot::Empty
+        }
+    }
+
+    /// Work RAM actually fitted: 1KB on SG-1000, 2KB on SC-3000, 8KB otherwise.
+    pub(crate) fn ram_size(&self) -> usize {
+        match self.platform {
+            Platform::Sg1000 => 1024,
+            Platform::Sc3000 => 2048,
+            _ => 8192,
+        }
+    }
+
+    /// Writes work RAM at `addr` ($C000–$FFFF, mirrored) without touching
+    /// the mapper registers.
+    pub(crate) fn write_ram(&mut self, addr: u16, value: u8) {
+        let size = self.ram_size();
+        self.ram[(addr as usize - 0xC000) % size] = value;
+    }
+
     pub(crate) fn read(&self, addr: u16) -> u8 {
-        // SG-1000/SC-3000: flat ROM
-        if self.platform.is_sg_family() {
+        let value = self.read_mapped(addr);
+        if self.rom_patches.is_empty() || addr >= 0xC000 {
+            return value;
+        }
+        self.rom_patches.iter()
+            .find(|p| p.addr == addr && p.compare.is_none_or(|c| c == value))
+            .filter(|_| self.rom_bank_at(addr).is_some())
+            .map_or(value, |p| p.value)
+    }
+
+    fn read_mapped(&self, addr: u16) -> u8 {
+        if addr >= 0xC000 {
+            if !self.enabled(MC_WORK_RAM) { return 0xFF; }
+            // RAM espelhada até $FFFF
+            return self.ram[(addr as usize - 0xC000) % self.ram_size()];
+        }
+
+        match (self.slot_at(addr), &self.bios) {
+            (Slot::Game, _) => {}
+            (Slot::Bios, Some(bios)) => {
+                return match bios.mapper.map(addr) {
+                    Mapped::Rom(offset) | Mapped::RomFlipped(offset) => bios.rom[offset % bios.rom.len()],
+                    Mapped::CartRam(_) => 0xFF,
+                };
+            }
+            _ => return 0xFF,
+        }
+
+        // EEPROM 93C46 (acesso serial e direto)
+        if let (Some(eeprom), 0x8000..=0xBFFF) = (&self.eeprom, addr) {
             return match addr {
-                0x0000..=0xBFFF => {
-                    let off = addr as usize;
-                    if off < self.rom.len() { self.rom[off] } else { 0xFF }
-                }
-                0xC000..=0xFFFF => {
-                    let ram_size = if self.platform == Platform::Sc3000 { 2048 } else { 1024 };
-                    self.ram[(addr - 0xC000) as usize % ram_size]
-                }
+                0x8000 => eeprom.read_control(),
+                0x8008..=0x8087 => eeprom.direct_read((addr - 0x8008) as u8),
+                _ => 0xFF,
             };
         }
 
-        // SMS/GG: Sega mapper
-        match addr {
-            0x0000..=0x03FF => {
-                // Primeiros 1KB são FIXOS no Banco 0
-                self.rom[addr as usize]
-            }
-            0x0400..=0x3FFF => {
-                let rom_banks = (self.rom.len() / 0x4000).max(1);
-                let actual_bank = self.rom_bank_0 % rom_banks;
-                let offset = (actual_bank * 0x4000) + (addr as usize);
-                if offset < self.rom.len() { self.rom[offset] } else { 0xFF }
-            }
-            0x4000..=0x7FFF => {
-                let rom_banks = (self.rom.len() / 0x4000).max(1);
-                let actual_bank = self.rom_bank_1 % rom_banks;
-                let offset = (actual_bank * 0x4000) + (addr as usize - 0x4000);
-                if offset < self.rom.len() { self.rom[offset] } else { 0xFF }
-            }
-            0x8000..=0xBFFF => {
-                // EEPROM 93C46 (acesso serial e direto)
-                if let Some(ref eeprom) = self.eeprom {
-                    return match addr {
-                        0x8000 => eeprom.read_control(),
-                        0x8008..=0x8087 => eeprom.direct_read((addr - 0x8008) as u8),
-                        _ => 0xFF,
-                    };
-                }
-
-                // SRAM do Cartucho (Sega Mapper padrão)
-                if (self.ram_control & 0x08) != 0 {
-                    let ram_page = if (self.ram_control & 0x04) != 0 { 1 } else { 0 };
-                    let offset = (ram_page * 0x2000) + (addr as usize - 0x8000);
-                    return self.cart_ram[offset];
-                }
-
-                // ROM no Slot 2
-                let rom_banks = (self.rom.len() / 0x4000).max(1);
-                let actual_bank = self.rom_bank_2 % rom_banks;
-                let offset = (actual_bank * 0x4000) + (addr as usize - 0x8000);
-                if offset < self.rom.len() { self.rom[offset] } else { 0xFF }
-            }
-            0xC000..=0xDFFF => {
-                self.ram[(addr - 0xC000) as usize]
-            }
-            0xE000..=0xFFFF => {
-                self.ram[(addr - 0xE000) as usize]
-            }
+        match self.mapper.map(addr) {
+            Mapped::Rom(offset) => self.rom.get(offset).copied().unwrap_or(0xFF),
+            Mapped::RomFlipped(offset) => self.rom.get(offset).map_or(0xFF, |b| b.reverse_bits()),
+            Mapped::CartRam(offset) => self.cart_ram[offset % self.cart_ram_size],
+        }
+    }
+
+    /// ROM bank currently visible at `addr`, or `None` when RAM, cart RAM or
+    /// EEPROM is mapped there.
+    pub(crate) fn rom_bank_at(&self, addr: u16) -> Option<u16> {
+        if addr >= 0xC000 || self.slot_at(addr) != Slot::Game || (self.eeprom.is_some() && addr >= 0x8000) {
+            return None;
+        }
+        match self.mapper.map(addr) {
+            Mapped::Rom(offset) | Mapped::RomFlipped(offset) => Some((offset / 0x4000) as u16),
+            Mapped::CartRam(_) => None,
         }
     }
 
+    pub(crate) fn mapper_kind(&self) -> MapperKind {
+        self.mapper.kind()
+    }
+
     pub(crate) fn write(&mut self, addr: u16, value: u8) {
-        // SG-1000/SC-3000: flat RAM
-        if self.platform.is_sg_family() {
-            if addr >= 0xC000 {
-                let ram_size = if self.platform == Platform::Sc3000 { 2048 } else { 1024 };
-                self.ram[(addr - 0xC000) as usize % ram_size] = value;
+        let slot = self.slot_at(addr);
+        if addr >= 0xC000 {
+            if self.enabled(MC_WORK_RAM) { self.write_ram(addr, value); }
+            // Mapper registers at $FFFC–$FFFF: every mapper on the bus latches them
+            if let Some(ref mut bios) = self.bios { bios.mapper.write(addr, value); }
+        } else if slot == Slot::Bios {
+            if let Some(ref mut bios) = self.bios { bios.mapper.write(addr, value); }
+            return;
+        } else if slot == Slot::Empty {
+            return;
+        } else if let (Some(eeprom), 0x8000..=0xBFFF) = (&mut self.eeprom, addr) {
+            match addr {
+                0x8000 => eeprom.write_control(value),
+                0x8008..=0x8087 => eeprom.direct_write((addr - 0x8008) as u8, value),
+                _ => {}
             }
-            // Writes to ROM area are silently ignored
             return;
         }
 
-        // SMS/GG: Sega mapper
-        match addr {
-            0x8000..=0xBFFF => {
-                // EEPROM 93C46 (acesso serial e direto)
-                if let Some(ref mut eeprom) = self.eeprom {
-                    match addr {
-                        0x8000 => eeprom.write_control(value),
-                        0x8008..=0x8087 => eeprom.direct_write((addr - 0x8008) as u8, value),
-                        _ => {}
-                    }
-                    return;
-                }
-
-                // SRAM do Cartucho (Sega Mapper padrão)
-                // Bit 3 ($08): habilita cart RAM; Bit 0 ($01): write-protect (1 = protegido)
-                if (self.ram_control & 0x08) != 0 && (self.ram_control & 0x01) == 0 {
-                    let ram_page = if (self.ram_control & 0x04) != 0 { 1 } else { 0 };
-                    let offset = (ram_page * 0x2000) + (addr as usize - 0x8000);
-                    self.cart_ram[offset] = value;
-                    self.sram_dirty = true;
-                }
-            }
-            0xC000..=0xDFFF => {
-                self.ram[(addr - 0xC000) as usize] = value;
-            }
-            0xE000..=0xFFFF => {
-                // RAM Mirror
-                self.ram[(addr - 0xE000) as usize] = value;
-
-                // Mappers só recebem escritas, independentes da RAM física espelhada
-                match addr {
-                    0xFFFC => self.ram_control = value,
-                    0xFFFD => self.rom_bank_0 = value as usize,
-                    0xFFFE => self.rom_bank_1 = value as usize,
-                    0xFFFF => self.rom_bank_2 = value as usize,
-                    _ => {}
-                }
-            }
-            _ => {
-                // Descartar outras escritas na ROM
-            }
+        if let Some(offset) = self.mapper.write(addr, value) {
+            self.cart_ram[offset % self.cart_ram_size] = value;
+            self.sram_dirty = true;
         }
     }
 }
@@ -219,13 +315,28 @@ mod tests {
         rom
     }
 
+    #[test]
+    fn rom_bank_at_follows_mapper_slots() {
+        let mut mmu = Mmu::new(make_rom(8), Platform::MasterSystem);
+        mmu.write(0xFFFD, 3);
+        mmu.write(0xFFFE, 5);
+        mmu.write(0xFFFF, 9); // 9 % 8 = 1
+        assert_eq!(mmu.rom_bank_at(0x0100), Some(0), "primeiro KB é fixo");
+        assert_eq!(mmu.rom_bank_at(0x1000), Some(3));
+        assert_eq!(mmu.rom_bank_at(0x4000), Some(5));
+        assert_eq!(mmu.rom_bank_at(0x8000), Some(1));
+        assert_eq!(mmu.rom_bank_at(0xC000), None);
+        mmu.write(0xFFFC, 0x08);
+        assert_eq!(mmu.rom_bank_at(0x8000), None, "RAM do cartucho mapeada no slot 2");
+    }
+
     // Fixed bank ($0000–$03FF)
 
     #[test]
     fn first_1kb_is_always_bank0() {
         let mut rom = make_rom(4);
         // Marca o primeiro KB do banco 0 com valor especial
-        for i in 0..0x400 { rom[i] = 0xAA; }
+        rom[..0x400].fill(0xAA);
         let mut mmu = Mmu::new(rom, Platform::MasterSystem);
         // Troca o banco 0 para o banco 2
         mmu.write(0xFFFD, 2);
@@ -391,9 +502,128 @@ mod tests {
 
     #[test]
     fn sms_rom_never_has_eeprom() {
-        // Mesmo que o CRC bata por acaso, is_gg=false impede EEPROM
+        // EEPROM só vem do banco de dados de jogos
         let rom = make_rom(3);
         let mmu = Mmu::new(rom, Platform::MasterSystem);
         assert!(mmu.eeprom.is_none());
     }
+
+    // Codemasters
+
+    #[test]
+    fn codemasters_cart_switches_banks_and_keeps_ram() {
+        let mut rom = make_rom(8);
+        rom[0x7FE6..0x7FEA].copy_from_slice(&[0x00, 0x80, 0x00, 0x80]); // 0x8000 + 0x8000
+        let mut mmu = Mmu::new(rom, Platform::GameGear);
+        assert_eq!(mmu.mapper_kind(), MapperKind::Codemasters);
+
+        mmu.write(0x8000, 6);
+        assert_eq!(mmu.read(0x8000), 6);
+        mmu.write(0xFFFF, 3);
+        assert_eq!(mmu.read(0x8000), 6, "$FFFF é só RAM neste mapper");
+        assert_eq!(mmu.read(0xFFFF), 3);
+
+        mmu.write(0x4000, 0x81);
+        mmu.write(0xA123, 0x5A);
+        assert_eq!(mmu.read(0xA123), 0x5A);
+        assert_eq!(mmu.cart_ram[0x0123], 0x5A);
+        assert!(mmu.sram_dirty, "RAM do cartucho é salva como SRAM");
+        assert_eq!(mmu.rom_bank_at(0xA123), None);
+        assert_eq!(mmu.rom_bank_at(0x4000), Some(1));
+    }
+
+    // BIOS e porta $3E
+
+    #[test]
+    fn memory_control_ignored_without_bios() {
+        let mut mmu = Mmu::new(make_rom(4), Platform::MasterSystem);
+        mmu.write_memory_control(0xFF);
+        assert_eq!(mmu.read(0x4000), 1, "cartucho continua mapeado");
+        mmu.write(0xC000, 0x12);
+        assert_eq!(mmu.read(0xC000), 0x12);
+        assert!(mmu.io_enabled());
+    }
+
+    #[test]
+    fn bios_boots_then_hands_over_to_cartridge() {
+        let mut mmu = Mmu::new(make_rom(4), Platform::MasterSystem);
+        let mut bios = vec![0xB0; 0x2000];
+        bios[0] = 0xF3;
+        mmu.set_bios(bios);
+        assert_eq!(mmu.read(0x0000), 0xF3, "BIOS em $0000 ao ligar");
+        assert_eq!(mmu.read(0x2000), 0xF3, "BIOS de 8KB espelhada");
+        assert_eq!(mmu.rom_bank_at(0x0000), None);
+
+        // Como a BIOS do SMS: habilita o cartucho, desabilita a si mesma
+        mmu.write_memory_control(0xAB);
+        assert_eq!(mmu.read(0x4000), 1);
+        assert_eq!(mmu.rom_bank_at(0x4000), Some(1));
+
+        mmu.write_memory_control(0xEB);
+        assert_eq!(mmu.read(0x4000), 0xFF, "nenhum slot habilitado");
+    }
+
+    #[test]
+    fn bios_mapper_and_cart_mapper_both_latch_ffff() {
+        let mut mmu = Mmu::new(make_rom(8), Platform::MasterSystem);
+        let bios: Vec<u8> = (0..8).flat_map(|b| vec![0x80 | b as u8; 0x4000]).collect();
+        mmu.set_bios(bios);
+        mmu.write(0xFFFF, 5);
+        assert_eq!(mmu.read(0x8000), 0x85, "banco 5 da BIOS");
+        mmu.write_memory_control(0xAB);
+        assert_eq!(mmu.read(0x8000), 5, "banco 5 do cartucho");
+    }
+
+    #[test]
+    fn work_ram_and_io_can_be_disabled() {
+        let mut mmu = Mmu::new(make_rom(4), Platform::MasterSystem);
+        mmu.set_bios(vec![0; 0x2000]);
+        mmu.write(0xC000, 0x12);
+        mmu.write_memory_control(0xAB | 0x10 | 0x04);
+        assert_eq!(mmu.read(0xC000), 0xFF);
+        mmu.write(0xC000, 0x34);
+        assert!(!mmu.io_enabled());
+        mmu.write_memory_control(0xAB);
+        assert_eq!(mmu.read(0xC000), 0x12, "escrita com RAM desabilitada é ignorada");
+    }
+
+    #[test]
+    fn game_gear_bios_overlays_first_kilobyte() {
+        let mut mmu = Mmu::new(make_rom(4), Platform::GameGear);
+        mmu.set_bios(vec![0xB1; 0x400]);
+        assert_eq!(mmu.read(0x03FF), 0xB1);
+        assert_eq!(mmu.read(0x0400), 0, "cartucho visível fora do 1KB da BIOS");
+        assert_eq!(mmu.read(0x4000), 1);
+        mmu.write_memory_control(0x08);
+        assert_eq!(mmu.read(0x0000), 0);
+    }
+
+    // Sega Card / slot de expansão
+
+    #[test]
+    fn card_has_no_mapper_and_runs_without_bios() {
+        let mut mmu = Mmu::new(make_rom(2), Platform::MasterSystem);
+        mmu.set_media(MediaSlot::Card);
+        assert_eq!(mmu.mapper_kind(), MapperKind::None);
+        mmu.write(0xFFFF, 0);
+        assert_eq!(mmu.read(0x4000), 1, "sem troca de banco");
+        assert_eq!(mmu.memory_control & 0x20, 0, "slot de cartão habilitado");
+    }
+
+    #[test]
+    fn bios_selects_the_slot_holding_the_game() {
+        let mut mmu = Mmu::new(make_rom(2), Platform::MasterSystem);
+        mmu.set_media(MediaSlot::Card);
+        mmu.set_bios(vec![0xB0; 0x2000]);
+        mmu.write_memory_control(0xAB); // cartucho: vazio
+        assert_eq!(mmu.read(0x4000), 0xFF);
+        mmu.write_memory_control(0xCB); // cartão
+        assert_eq!(mmu.read(0x4000), 1);
+
+        let mut mmu = Mmu::new(make_rom(2), Platform::MasterSystem);
+        mmu.set_media(MediaSlot::Expansion);
+        mmu.set_bios(vec![0xB0; 0x2000]);
+        mmu.write_memory_control(0x6B); // expansão
+        assert_eq!(mmu.read(0x4000), 1);
+    }
 }
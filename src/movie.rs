@@ -0,0 +1,521 @@
+//! Input movies: per-frame input recorded from power-on or from an embedded
+//! save state, replayed frame-exactly.
+//!
+//! Movie binary format  (magic "VSMV", version 1, little-endian)
+//!
+//! ```text
+//! header      magic, version, ROM CRC32, platform, region, video standard
+//! start       0 = power-on, 1 = save state (u32 length + SaveState bytes)
+//! frames      u32 count, then per frame: u16 button bits, u8 gun X, u8 gun Y
+//! checkpoints u32 count, then (u32 frame, u32 CRC32 of the machine state)
+//! ```
+//!
+//! Both recording and playback start from a freshly built machine (blank
+//! battery RAM, or the embedded state), so the same inputs always produce the
+//! same frames. The player's battery RAM is set aside while a movie runs and
+//! put back by `restore_battery`, so the movie's saves never reach disk.
+//! Checkpoints taken every [`CHECKPOINT_INTERVAL`] frames while
+//! recording let playback report the first frame where emulation diverged.
+
+use crate::mmu::crc32;
+use crate::savestate::{De, Ser};
+use crate::{ConsoleRegion, Emulator, Platform, SaveState, VideoStandard};
+
+const MAGIC: &[u8; 4] = b"VSMV";
+const VERSION: u8 = 1;
+
+/// Frames between two desync checkpoints.
+pub const CHECKPOINT_INTERVAL: usize = 60;
+
+/// Everything the player can do to the machine during one frame.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct FrameInput {
+    /// Player 1: up, down, left, right, button 1, button 2.
+    pub p1: [bool; 6],
+    /// Player 2: up, down, left, right, button 1, button 2.
+    pub p2: [bool; 6],
+    /// SMS Pause / Game Gear Start.
+    pub start: bool,
+    /// Light Phaser trigger pulled at screen pixel (x, y).
+    pub gun: Option<(u16, u16)>,
+    /// Power-cycle before this frame (the frontend's Reset).
+    pub reset: bool,
+}
+
+impl FrameInput {
+    fn to_bits(self) -> u16 {
+        let mut bits = 0u16;
+        for i in 0..6 {
+            bits |= (self.p1[i] as u16) << i;
+            bits |= (self.p2[i] as u16) << (i + 6);
+        }
+        bits |= (self.start as u16) << 12;
+        bits |= (self.gun.is_some() as u16) << 13;
+        bits |= (self.reset as u16) << 14;
+        bits
+    }
+
+    fn from_bits(bits: u16, x: u8, y: u8) -> Self {
+        let bit = |n: u16| bits & (1 << n) != 0;
+        FrameInput {
+            p1: core::array::from_fn(|i| bit(i as u16)),
+            p2: core::array::from_fn(|i| bit(i as u16 + 6)),
+            start: bit(12),
+            gun: bit(13).then_some((x as u16, y as u16)),
+            reset: bit(14),
+        }
+    }
+}
+
+fn platform_id(p: Platform) -> u8 {
+    match p {
+        Platform::MasterSystem => 0,
+        Platform::GameGear     => 1,
+        Platform::Sg1000       => 2,
+        Platform::Sc3000       => 3,
+    }
+}
+
+fn platform_from_id(id: u8) -> Option<Platform> {
+    Some(match id {
+        0 => Platform::MasterSystem,
+        1 => Platform::GameGear,
+        2 => Platform::Sg1000,
+        3 => Platform::Sc3000,
+        _ => return None,
+    })
+}
+
+/// Fingerprint of the machine state used for desync detection.
+fn state_crc(emu: &Emulator) -> u32 {
+    crc32(&emu.save_state().serialize())
+}
+
+/// A recorded input movie.
+pub struct Movie {
+    pub rom_crc:  u32,
+    pub platform: Platform,
+    pub region:   ConsoleRegion,
+    pub video:    VideoStandard,
+    /// Serialized [`SaveState`] the movie starts from; `None` = power-on.
+    start_state: Option<Vec<u8>>,
+    frames:      Vec<FrameInput>,
+    /// (frame index, state CRC after that frame).
+    checkpoints: Vec<(u32, u32)>,
+}
+
+impl Movie {
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn starts_from_savestate(&self) -> bool {
+        self.start_state.is_some()
+    }
+
+    pub fn frames(&self) -> &[FrameInput] {
+        &self.frames
+    }
+
+    /// Puts `emu` in the movie's starting state, refusing a different game or
+    /// machine. Returns the battery RAM it had before.
+    fn prepare(&self, emu: &mut Emulator) -> Result<BatteryRam, String> {
+        if emu.rom_crc32() != self.rom_crc {
+            return Err(format!(
+                "movie was recorded with ROM CRC32 {:08X}, loaded ROM is {:08X}",
+                self.rom_crc, emu.rom_crc32()));
+        }
+        if emu.platform() != self.platform {
+            return Err(format!("movie was recorded on {:?}, not {:?}", self.platform, emu.platform()));
+        }
+        if emu.video_standard() != self.video {
+            return Err(format!("movie was recorded with {:?} timing", self.video));
+        }
+        let battery = BatteryRam::take(emu);
+        emu.set_console_region(self.region);
+        emu.rebuild(false);
+        if let Some(ref bytes) = self.start_state {
+            let state = SaveState::deserialize(bytes).ok_or("movie save state is invalid")?;
+            emu.load_state(state);
+        }
+        // Clear latched inputs so the first recorded frame sees the same button edges.
+        emu.apply_input(&FrameInput::default());
+        Ok(battery)
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut s = Ser::new();
+        s.bytes(MAGIC);
+        s.u8(VERSION);
+        s.u32(self.rom_crc);
+        s.u8(platform_id(self.platform));
+        s.u8(matches!(self.region, ConsoleRegion::Japan) as u8);
+        s.u8(matches!(self.video, VideoStandard::Pal) as u8);
+
+        match self.start_state {
+            Some(ref state) => {
+                s.u8(1);
+                s.u32(state.len() as u32);
+                s.bytes(state);
+            }
+            None => s.u8(0),
+        }
+
+        s.u32(self.frames.len() as u32);
+        for f in &self.frames {
+            let (x, y) = f.gun.unwrap_or((0, 0));
+            s.u16(f.to_bits());
+            s.u8(x.min(255) as u8);
+            s.u8(y.min(255) as u8);
+        }
+
+        s.u32(self.checkpoints.len() as u32);
+        for &(frame, crc) in &self.checkpoints {
+            s.u32(frame);
+            s.u32(crc);
+        }
+        s.0
+    }
+
+    pub fn deserialize(data: &[u8]) -> Option<Self> {
+        let mut d = De::new(data);
+        if &d.bytes::<4>()? != MAGIC || d.u8()? != VERSION { return None; }
+
+        let rom_crc  = d.u32()?;
+        let platform = platform_from_id(d.u8()?)?;
+        let region   = if d.bool()? { ConsoleRegion::Japan } else { ConsoleRegion::Export };
+        let video    = if d.bool()? { VideoStandard::Pal } else { VideoStandard::Ntsc };
+
+        let start_state = if d.bool()? {
+            let len = d.u32()? as usize;
+            Some(d.slice(len)?.to_vec())
+        } else {
+            None
+        };
+
+        let count = d.u32()? as usize;
+        let mut frames = Vec::with_capacity(count.min(data.len() / 4));
+        for _ in 0..count {
+            let bits = d.u16()?;
+            let (x, y) = (d.u8()?, d.u8()?);
+            frames.push(FrameInput::from_bits(bits, x, y));
+        }
+
+        let count = d.u32()? as usize;
+        let mut checkpoints = Vec::with_capacity(count.min(data.len() / 8));
+        for _ in 0..count {
+            checkpoints.push((d.u32()?, d.u32()?));
+        }
+
+        Some(Movie { rom_crc, platform, region, video, start_state, frames, checkpoints })
+    }
+}
+
+/// Cart RAM and EEPROM contents set aside while a movie runs on blank memory.
+struct BatteryRam {
+    cart_ram: Vec<u8>,
+    eeprom:   Option<Vec<u8>>,
+}
+
+impl BatteryRam {
+    fn take(emu: &Emulator) -> Self {
+        Self { cart_ram: emu.get_cart_ram(), eeprom: emu.get_eeprom_data() }
+    }
+
+    /// Writes the contents back and clears both dirty flags.
+    fn restore(&self, emu: &Emulator) {
+        emu.load_cart_ram(&self.cart_ram);
+        if let Some(ref data) = self.eeprom {
+            emu.load_eeprom_data(data);
+        }
+    }
+}
+
+/// Records the inputs fed to an emulator, one frame at a time.
+pub struct MovieRecorder {
+    movie:   Movie,
+    battery: BatteryRam,
+}
+
+impl MovieRecorder {
+    /// Starts recording on `emu`. From power-on the machine is rebuilt with
+    /// blank battery RAM; otherwise its current state is embedded in the movie.
+    pub fn start(emu: &mut Emulator, from_power_on: bool) -> Self {
+        let movie = Movie {
+            rom_crc:     emu.rom_crc32(),
+            platform:    emu.platform(),
+            region:      emu.console_region(),
+            video:       emu.video_standard(),
+            start_state: (!from_power_on).then(|| emu.save_state().serialize()),
+            frames:      Vec::new(),
+            checkpoints: Vec::new(),
+        };
+        // Same ROM and machine by construction, so this cannot fail.
+        let battery = movie.prepare(emu).unwrap_or_else(|_| BatteryRam::take(emu));
+        Self { movie, battery }
+    }
+
+    /// Puts back the battery RAM `emu` had before recording, with nothing
+    /// left dirty. Call when recording stops, before saves are written.
+    pub fn restore_battery(&self, emu: &Emulator) {
+        self.battery.restore(emu);
+    }
+
+    /// Applies `input`, runs one frame and records it. Returns `step_frame`'s result.
+    pub fn step(&mut self, emu: &mut Emulator, input: FrameInput) -> (bool, Vec<f32>) {
+        emu.apply_input(&input);
+        let out = emu.step_frame();
+        self.movie.frames.push(input);
+        let n = self.movie.frames.len();
+        if n.is_multiple_of(CHECKPOINT_INTERVAL) {
+            self.movie.checkpoints.push((n as u32 - 1, state_crc(emu)));
+        }
+        out
+    }
+
+    pub fn len(&self) -> usize {
+        self.movie.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.movie.frames.is_empty()
+    }
+
+    pub fn finish(self) -> Movie {
+        self.movie
+    }
+}
+
+/// Replays a movie, checking the recorded state checkpoints as it goes.
+pub struct MoviePlayer {
+    movie: Movie,
+    battery: BatteryRam,
+    frame: usize,
+    next_checkpoint: usize,
+    desync: Option<u32>,
+}
+
+impl MoviePlayer {
+    /// Validates `movie` against `emu` and rewinds the machine to the movie's start.
+    pub fn start(movie: Movie, emu: &mut Emulator) -> Result<Self, String> {
+        let battery = movie.prepare(emu)?;
+        Ok(Self { movie, battery, frame: 0, next_checkpoint: 0, desync: None })
+    }
+
+    /// Puts back the battery RAM `emu` had before playback, with nothing
+    /// left dirty. Call when playback ends or is stopped.
+    pub fn restore_battery(&self, emu: &Emulator) {
+        self.battery.restore(emu);
+    }
+
+    /// Runs the next recorded frame; `None` once the movie has ended.
+    pub fn step(&mut self, emu: &mut Emulator) -> Option<(bool, Vec<f32>)> {
+        let input = *self.movie.frames.get(self.frame)?;
+        emu.apply_input(&input);
+        let out = emu.step_frame();
+
+        if let Some(&(frame, crc)) = self.movie.checkpoints.get(self.next_checkpoint) {
+            if frame as usize == self.frame {
+                self.next_checkpoint += 1;
+                if self.desync.is_none() && state_crc(emu) != crc {
+                    self.desync = Some(frame);
+                }
+            }
+        }
+        self.frame += 1;
+        Some(out)
+    }
+
+    /// Frames played so far.
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    pub fn len(&self) -> usize {
+        self.movie.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.movie.is_empty()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.movie.len()
+    }
+
+    /// First checkpoint frame whose machine state differed from the recording.
+    pub fn desync_frame(&self) -> Option<u32> {
+        self.desync
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::with_large_stack;
+
+    /// ROM que soma as leituras da porta $DC na RAM: o estado depende da entrada.
+    fn input_rom() -> Vec<u8> {
+        let mut rom = vec![0u8; 0xC000];
+        // 0000: DI; LD HL,$C000; loop: IN A,($DC); ADD A,(HL); LD (HL),A; JR loop
+        rom[..11].copy_from_slice(&[0xF3, 0x21, 0x00, 0xC0, 0xDB, 0xDC, 0x86, 0x77, 0x18, 0xFA, 0x00]);
+        rom
+    }
+
+    fn emu() -> Emulator {
+        Emulator::new(input_rom(), Platform::MasterSystem, VideoStandard::Ntsc, 44100.0)
+    }
+
+    fn pressing(frame: usize) -> FrameInput {
+        FrameInput { p1: [frame.is_multiple_of(3), false, false, frame.is_multiple_of(5), frame.is_multiple_of(7), false], ..Default::default() }
+    }
+
+    #[test]
+    fn frame_input_bits_roundtrip() {
+        let f = FrameInput {
+            p1: [true, false, true, false, true, false],
+            p2: [false, true, false, true, false, true],
+            start: true, gun: Some((200, 150)), reset: true,
+        };
+        assert_eq!(FrameInput::from_bits(f.to_bits(), 200, 150), f);
+    }
+
+    #[test]
+    fn serialize_roundtrip_keeps_header_and_frames() {
+        with_large_stack(|| {
+            let mut e = emu();
+            let mut rec = MovieRecorder::start(&mut e, false);
+            for i in 0..130 { rec.step(&mut e, pressing(i)); }
+            let movie = rec.finish();
+            let r = Movie::deserialize(&movie.serialize()).expect("filme válido");
+            assert_eq!(r.rom_crc, movie.rom_crc);
+            assert_eq!(r.platform, Platform::MasterSystem);
+            assert!(r.starts_from_savestate());
+            assert_eq!(r.frames(), movie.frames());
+            assert_eq!(r.checkpoints, movie.checkpoints);
+            assert_eq!(r.checkpoints.len(), 2, "checkpoints nos frames 59 e 119");
+        });
+    }
+
+    #[test]
+    fn deserialize_rejects_bad_magic_and_truncation() {
+        assert!(Movie::deserialize(b"XXXX\x01").is_none());
+        assert!(Movie::deserialize(&b"VSMV\x01\x00\x00"[..]).is_none());
+    }
+
+    #[test]
+    fn playback_reproduces_recording() {
+        with_large_stack(|| {
+            let mut e = emu();
+            for _ in 0..10 { e.step_frame(); } // estado anterior é descartado (power-on)
+            let mut rec = MovieRecorder::start(&mut e, true);
+            for i in 0..180 { rec.step(&mut e, pressing(i)); }
+            let recorded = e.get_framebuffer();
+            let recorded_ram = e.save_state().mmu.ram;
+            let movie = Movie::deserialize(&rec.finish().serialize()).unwrap();
+
+            let mut e2 = emu();
+            let mut player = MoviePlayer::start(movie, &mut e2).unwrap();
+            while player.step(&mut e2).is_some() {}
+            assert!(player.is_finished());
+            assert_eq!(player.desync_frame(), None);
+            assert_eq!(e2.save_state().mmu.ram, recorded_ram);
+            assert_eq!(e2.get_framebuffer(), recorded);
+        });
+    }
+
+    #[test]
+    fn playback_from_embedded_state_reproduces_recording() {
+        with_large_stack(|| {
+            let mut e = emu();
+            for i in 0..30 { e.apply_input(&pressing(i)); e.step_frame(); }
+            let mut rec = MovieRecorder::start(&mut e, false);
+            for i in 0..120 { rec.step(&mut e, pressing(i + 1)); }
+            let recorded_ram = e.save_state().mmu.ram;
+
+            let mut e2 = emu();
+            let mut player = MoviePlayer::start(rec.finish(), &mut e2).unwrap();
+            while player.step(&mut e2).is_some() {}
+            assert_eq!(player.desync_frame(), None);
+            assert_eq!(e2.save_state().mmu.ram, recorded_ram);
+        });
+    }
+
+    #[test]
+    fn playback_detects_desync() {
+        with_large_stack(|| {
+            let mut e = emu();
+            let mut rec = MovieRecorder::start(&mut e, true);
+            for i in 0..120 { rec.step(&mut e, pressing(i)); }
+            let mut movie = rec.finish();
+            movie.frames[70].p1[0] = !movie.frames[70].p1[0]; // adultera uma entrada
+
+            let mut e2 = emu();
+            let mut player = MoviePlayer::start(movie, &mut e2).unwrap();
+            while player.step(&mut e2).is_some() {}
+            assert_eq!(player.desync_frame(), Some(119));
+        });
+    }
+
+    /// ROM que liga a RAM do cartucho e grava $5A em $8000.
+    fn sram_rom() -> Vec<u8> {
+        let mut rom = vec![0u8; 0xC000];
+        // DI; LD A,$08; LD ($FFFC),A; LD A,$5A; LD ($8000),A; JR $
+        rom[..13].copy_from_slice(&[0xF3, 0x3E, 0x08, 0x32, 0xFC, 0xFF, 0x3E, 0x5A, 0x32, 0x00, 0x80, 0x18, 0xFE]);
+        rom
+    }
+
+    #[test]
+    fn ending_a_movie_restores_the_players_battery_ram() {
+        with_large_stack(|| {
+            let save = vec![0x11u8; 0x4000];
+            let mut e = Emulator::new(sram_rom(), Platform::MasterSystem, VideoStandard::Ntsc, 44100.0);
+            e.load_cart_ram(&save);
+
+            let mut rec = MovieRecorder::start(&mut e, true);
+            for _ in 0..5 { rec.step(&mut e, FrameInput::default()); }
+            assert_eq!(e.get_cart_ram()[0], 0x5A, "o filme roda sobre RAM em branco");
+            assert!(e.is_sram_dirty());
+            rec.restore_battery(&e);
+            let movie = rec.finish();
+            assert_eq!(e.get_cart_ram(), save, "o .sav do jogador não muda");
+            assert!(!e.is_sram_dirty(), "nada a gravar depois do filme");
+
+            let mut player = MoviePlayer::start(movie, &mut e).unwrap();
+            while player.step(&mut e).is_some() {}
+            assert!(e.is_sram_dirty());
+            player.restore_battery(&e);
+            assert_eq!(e.get_cart_ram(), save);
+            assert!(!e.is_sram_dirty());
+        });
+    }
+
+    #[test]
+    fn playback_refuses_other_rom() {
+        with_large_stack(|| {
+            let mut e = emu();
+            let movie = MovieRecorder::start(&mut e, true).finish();
+            let mut other_rom = input_rom();
+            other_rom[0x100] = 0x42;
+            let mut e2 = Emulator::new(other_rom, Platform::MasterSystem, VideoStandard::Ntsc, 44100.0);
+            let err = MoviePlayer::start(movie, &mut e2).err().expect("ROM diferente");
+            assert!(err.contains("CRC32"), "{err}");
+        });
+    }
+
+    #[test]
+    fn reset_input_power_cycles() {
+        with_large_stack(|| {
+            let mut e = emu();
+            for _ in 0..5 { e.apply_input(&pressing(0)); e.step_frame(); }
+            assert_ne!(e.save_state().mmu.ram[0], 0);
+            e.apply_input(&FrameInput { reset: true, ..Default::default() });
+            assert_eq!(e.save_state().mmu.ram[0], 0, "power cycle limpa a RAM");
+            assert_eq!(e.cpu.pc, 0);
+        });
+    }
+}
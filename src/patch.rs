@@ -0,0 +1,291 @@
+//! Soft-patching: IPS, BPS and UPS patches applied to the ROM image in memory,
+//! so fan translations and hacks run without touching the original dump.
+//!
+//! BPS and UPS carry CRC32s of the source, the target and the patch itself;
+//! all three are checked. IPS has no checksums and applies to any ROM.
+
+use std::path::{Path, PathBuf};
+
+use crate::mmu::crc32;
+
+const EXTENSIONS: [&str; 3] = ["ips", "bps", "ups"];
+
+/// A same-named `.ips`, `.bps` or `.ups` beside the ROM, if there is one.
+pub fn find_patch(rom_path: &Path) -> Option<PathBuf> {
+    EXTENSIONS.iter()
+        .flat_map(|ext| [ext.to_string(), ext.to_ascii_uppercase()])
+        .map(|ext| rom_path.with_extension(ext))
+        .find(|p| p.is_file())
+}
+
+/// Applies an IPS, BPS or UPS patch (recognised by its magic) to `rom`.
+pub fn apply_patch(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    if patch.starts_with(b"PATCH") {
+        apply_ips(rom, patch)
+    } else if patch.starts_with(b"BPS1") {
+        apply_bps(rom, patch)
+    } else if patch.starts_with(b"UPS1") {
+        apply_ups(rom, patch)
+    } else {
+        Err("not an IPS, BPS or UPS patch".into())
+    }
+}
+
+/// Bounds-checked cursor over the patch data.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos:  usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let out = self.data.get(self.pos..self.pos + n).ok_or("patch is truncated")?;
+        self.pos += n;
+        Ok(out)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn be(&mut self, n: usize) -> Result<usize, String> {
+        Ok(self.bytes(n)?.iter().fold(0, |acc, &b| acc << 8 | b as usize))
+    }
+
+    /// BPS/UPS variable-length number: 7 bits per byte, last byte has bit 7 set.
+    fn number(&mut self) -> Result<usize, String> {
+        let (mut value, mut shift) = (0usize, 1usize);
+        loop {
+            let b = self.byte()?;
+            value = value.checked_add((b & 0x7F) as usize * shift).ok_or("patch number overflows")?;
+            if b & 0x80 != 0 { return Ok(value); }
+            shift = shift.checked_mul(128).ok_or("patch number overflows")?;
+            value = value.checked_add(shift).ok_or("patch number overflows")?;
+        }
+    }
+}
+
+/// Patches larger than this (16MB) are rejected rather than allocated.
+const MAX_SIZE: usize = 0x100_0000;
+
+fn apply_ips(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    let mut r = Reader { data: patch, pos: 5 };
+    let mut out = rom.to_vec();
+    loop {
+        if r.data.get(r.pos..r.pos + 3) == Some(b"EOF") {
+            r.pos += 3;
+            break;
+        }
+        let offset = r.be(3)?;
+        let (len, fill) = match r.be(2)? {
+            0 => (r.be(2)?, Some(r.byte()?)),
+            len => (len, None),
+        };
+        if offset + len > out.len() { out.resize(offset + len, 0); }
+        match fill {
+            Some(value) => out[offset..offset + len].fill(value),
+            None => out[offset..offset + len].copy_from_slice(r.bytes(len)?),
+        }
+    }
+    // Optional truncation extension: the final size follows "EOF".
+    if let Ok(size) = r.be(3) {
+        out.truncate(size);
+    }
+    Ok(out)
+}
+
+/// Splits off and checks the 12-byte CRC32 footer shared by BPS and UPS.
+/// Returns the length of the patch body and the expected target CRC.
+fn footer(rom: &[u8], patch: &[u8]) -> Result<(usize, u32), String> {
+    if patch.len() < 16 { return Err("patch is truncated".into()); }
+    let body = patch.len() - 12;
+    let word = |at: usize| u32::from_le_bytes(patch[at..at + 4].try_into().unwrap());
+    let (source, target) = (word(body), word(body + 4));
+    if crc32(&patch[..body + 8]) != word(body + 8) {
+        return Err("patch file is corrupt (CRC32 mismatch)".into());
+    }
+    let actual = crc32(rom);
+    if actual != source {
+        return Err(format!("patch is for a different ROM (CRC32 {actual:08X}, expected {source:08X})"));
+    }
+    Ok((body, target))
+}
+
+fn check_target(out: &[u8], target: u32) -> Result<(), String> {
+    let actual = crc32(out);
+    if actual != target {
+        return Err(format!("patched ROM has CRC32 {actual:08X}, expected {target:08X}"));
+    }
+    Ok(())
+}
+
+fn apply_bps(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    let (body, target_crc) = footer(rom, patch)?;
+    let mut r = Reader { data: &patch[..body], pos: 4 };
+    let source_size = r.number()?;
+    let target_size = r.number()?;
+    if source_size != rom.len() {
+        return Err(format!("patch expects a {source_size}-byte ROM, got {} bytes", rom.len()));
+    }
+    if target_size > MAX_SIZE { return Err("patched ROM would be too large".into()); }
+    let metadata = r.number()?;
+    r.bytes(metadata)?;
+
+    let mut out = Vec::with_capacity(target_size);
+    let (mut source_rel, mut target_rel) = (0usize, 0usize);
+    let relative = |base: usize, r: &mut Reader| -> Result<usize, String> {
+        let d = r.number()?;
+        let moved = if d & 1 != 0 { base.checked_sub(d >> 1) } else { base.checked_add(d >> 1) };
+        moved.ok_or_else(|| "patch copy offset is out of range".to_string())
+    };
+    while r.pos < r.data.len() {
+        let action = r.number()?;
+        let len = (action >> 2) + 1;
+        if out.len() + len > target_size { return Err("patch writes past the target size".into()); }
+        match action & 3 {
+            0 => {
+                let at = out.len();
+                out.extend_from_slice(rom.get(at..at + len).ok_or("patch reads past the source ROM")?);
+            }
+            1 => out.extend_from_slice(r.bytes(len)?),
+            2 => {
+                source_rel = relative(source_rel, &mut r)?;
+                out.extend_from_slice(rom.get(source_rel..source_rel + len).ok_or("patch reads past the source ROM")?);
+                source_rel += len;
+            }
+            _ => {
+                target_rel = relative(target_rel, &mut r)?;
+                // May overlap the bytes being written, so copy one at a time.
+                for _ in 0..len {
+                    let b = *out.get(target_rel).ok_or("patch copy offset is out of range")?;
+                    out.push(b);
+                    target_rel += 1;
+                }
+            }
+        }
+    }
+    if out.len() != target_size { return Err("patch ends before the target size".into()); }
+    check_target(&out, target_crc)?;
+    Ok(out)
+}
+
+fn apply_ups(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    let (body, target_crc) = footer(rom, patch)?;
+    let mut r = Reader { data: &patch[..body], pos: 4 };
+    let source_size = r.number()?;
+    let target_size = r.number()?;
+    if source_size != rom.len() {
+        return Err(format!("patch expects a {source_size}-byte ROM, got {} bytes", rom.len()));
+    }
+    if target_size > MAX_SIZE { return Err("patched ROM would be too large".into()); }
+
+    let mut out = rom.to_vec();
+    out.resize(target_size, 0);
+    let mut at = 0usize;
+    while r.pos < r.data.len() {
+        at += r.number()?;
+        loop {
+            let x = r.byte()?;
+            if x == 0 { at += 1; break; }
+            *out.get_mut(at).ok_or("patch writes past the target size")? ^= x;
+            at += 1;
+        }
+    }
+    check_target(&out, target_crc)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(mut n: usize) -> Vec<u8> {
+        let mut out = Vec::new();
+        loop {
+            let x = (n & 0x7F) as u8;
+            n >>= 7;
+            if n == 0 { out.push(x | 0x80); return out; }
+            out.push(x);
+            n -= 1;
+        }
+    }
+
+    /// Acrescenta o rodapé de CRC32 (origem, destino, patch).
+    fn with_footer(mut patch: Vec<u8>, source: &[u8], target: &[u8]) -> Vec<u8> {
+        patch.extend_from_slice(&crc32(source).to_le_bytes());
+        patch.extend_from_slice(&crc32(target).to_le_bytes());
+        let crc = crc32(&patch);
+        patch.extend_from_slice(&crc.to_le_bytes());
+        patch
+    }
+
+    #[test]
+    fn ips_writes_rle_grows_and_truncates() {
+        let rom = vec![0u8; 16];
+        let mut patch = b"PATCH".to_vec();
+        patch.extend_from_slice(&[0, 0, 2, 0, 2, 0xAA, 0xBB]);     // 2 bytes em $0002
+        patch.extend_from_slice(&[0, 0, 14, 0, 0, 0, 4, 0x55]);    // RLE de 4 bytes, cresce
+        patch.extend_from_slice(b"EOF");
+        let out = apply_patch(&rom, &patch).unwrap();
+        assert_eq!(out.len(), 18);
+        assert_eq!(&out[..4], &[0, 0, 0xAA, 0xBB]);
+        assert_eq!(&out[14..], &[0x55; 4]);
+
+        patch.extend_from_slice(&[0, 0, 8]);
+        assert_eq!(apply_patch(&rom, &patch).unwrap().len(), 8, "extensão de truncamento");
+        assert_eq!(apply_patch(&rom, b"PATCH\x00\x00\x01\x00\x05\x01").err().as_deref(), Some("patch is truncated"));
+    }
+
+    #[test]
+    fn ups_xors_and_checks_crcs() {
+        let rom: Vec<u8> = (0..32).collect();
+        let mut target = rom.clone();
+        target[3] = 0xFF;
+        target[4] = 0xEE;
+        target.push(0x77);
+        let mut patch = b"UPS1".to_vec();
+        patch.extend(number(rom.len()));
+        patch.extend(number(target.len()));
+        patch.extend(number(3));
+        patch.extend_from_slice(&[3 ^ 0xFF, 4 ^ 0xEE, 0]);
+        patch.extend(number(32 - 6));
+        patch.extend_from_slice(&[0x77, 0]);
+        let patch = with_footer(patch, &rom, &target);
+        assert_eq!(apply_patch(&rom, &patch).unwrap(), target);
+
+        let other = vec![1u8; 32];
+        let err = apply_patch(&other, &patch).err().unwrap();
+        assert!(err.starts_with("patch is for a different ROM"), "{err}");
+    }
+
+    #[test]
+    fn bps_runs_all_four_actions() {
+        let rom = b"ABCDEFGH".to_vec();
+        let target = b"ABxyDEFGxyxyx".to_vec();
+        let mut patch = b"BPS1".to_vec();
+        patch.extend(number(rom.len()));
+        patch.extend(number(target.len()));
+        patch.extend(number(3));
+        patch.extend_from_slice(b"meu");
+        patch.extend(number(1 << 2));                         // SourceRead "AB"
+        patch.extend(number((1 << 2) | 1));                   // TargetRead "xy"
+        patch.extend_from_slice(b"xy");
+        patch.extend(number((3 << 2) | 2));                   // SourceCopy "DEFG" de +3
+        patch.extend(number(3 << 1));
+        patch.extend(number((1 << 2) | 1));                   // TargetRead "xy"
+        patch.extend_from_slice(b"xy");
+        patch.extend(number((2 << 2) | 3));                   // TargetCopy "xyx" de +8 (sobreposto)
+        patch.extend(number(8 << 1));
+        let patch = with_footer(patch, &rom, &target);
+        assert_eq!(apply_patch(&rom, &patch).unwrap(), target);
+
+        let mut corrupt = patch.clone();
+        corrupt[10] ^= 1;
+        assert_eq!(apply_patch(&rom, &corrupt).err().as_deref(), Some("patch file is corrupt (CRC32 mismatch)"));
+    }
+
+    #[test]
+    fn rejects_unknown_format() {
+        assert!(apply_patch(&[0; 4], b"NOTAPATCH").is_err());
+    }
+}
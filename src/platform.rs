@@ -1,12 +1,14 @@
+use crate::rom_header::RomHeader;
+
 // Screen dimensions
-pub(crate) const SMS_W: usize = 256;
-pub(crate) const SMS_H: usize = 192;
-pub(crate) const GG_W: usize = 160;
-pub(crate) const GG_H: usize = 144;
+pub const SMS_W: usize = 256;
+pub const SMS_H: usize = 192;
+pub const GG_W: usize = 160;
+pub const GG_H: usize = 144;
 
 /// Hardware platform being emulated.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
-pub(crate) enum Platform {
+pub enum Platform {
     MasterSystem,
     GameGear,
     Sg1000,   // SG-1000 — 1 KB RAM, TMS9918A VDP, no Sega mapper
@@ -14,13 +16,157 @@ pub(crate) enum Platform {
 }
 
 impl Platform {
-    pub(crate) fn is_gg(self) -> bool {
+    pub fn is_gg(self) -> bool {
         self == Platform::GameGear
     }
     /// True for SG-1000 and SC-3000.
-    pub(crate) fn is_sg_family(self) -> bool {
+    pub fn is_sg_family(self) -> bool {
         matches!(self, Platform::Sg1000 | Platform::Sc3000)
     }
+
+    /// Picks the platform from a ROM file extension (`.gg`, `.sg`, `.sc`);
+    /// anything else is treated as a Master System cartridge.
+    pub fn from_extension(ext: &str) -> Platform {
+        match ext.to_ascii_lowercase().as_str() {
+            "gg" => Platform::GameGear,
+            "sg" => Platform::Sg1000,
+            "sc" => Platform::Sc3000,
+            _    => Platform::MasterSystem,
+        }
+    }
+
+    /// Name used by `--platform` and the game database: `sms`, `gg`, `sg` or `sc`.
+    pub fn from_name(name: &str) -> Option<Platform> {
+        match name.to_ascii_lowercase().as_str() {
+            "sms" => Some(Platform::MasterSystem),
+            "gg"  => Some(Platform::GameGear),
+            "sg"  => Some(Platform::Sg1000),
+            "sc"  => Some(Platform::Sc3000),
+            _     => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Platform::MasterSystem => "Master System",
+            Platform::GameGear     => "Game Gear",
+            Platform::Sg1000       => "SG-1000",
+            Platform::Sc3000       => "SC-3000",
+        }
+    }
+
+    /// Guesses the platform of a ROM image. An extension naming a console
+    /// wins; for others (`.bin`, `.rom`, …) the region code of the
+    /// `TMR SEGA` header decides. Failing that, a small image with no header
+    /// that never touches the Sega mapper registers is an SG-1000 cartridge.
+    pub fn detect(ext: &str, rom: &[u8]) -> Platform {
+        if let Some(platform) = Platform::from_name(ext) {
+            return platform;
+        }
+        match RomHeader::parse(rom) {
+            Some(header) => header.region.platform().unwrap_or(Platform::MasterSystem),
+            None if rom.len() <= 0xC000 && !writes_mapper_registers(rom) => Platform::Sg1000,
+            None => Platform::MasterSystem,
+        }
+    }
+}
+
+/// Looks for `LD ($FFFC–$FFFF),A`, `LD ($FFFC–$FFFF),HL` or `LD HL,$FFFC–$FFFF`,
+/// the usual ways games reach the Sega mapper's control registers.
+fn writes_mapper_registers(rom: &[u8]) -> bool {
+    rom.windows(3).any(|w| matches!(w, [0x32 | 0x22 | 0x21, 0xFC..=0xFF, 0xFF]))
+}
+
+/// Master System slot the game image is plugged into. The BIOS picks
+/// between them through port $3E; without a BIOS the game always runs.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum MediaSlot {
+    #[default]
+    Cartridge,
+    /// Sega Card / My Card (32KB, no mapper); also takes SG-1000 cards.
+    Card,
+    Expansion,
+}
+
+/// Television standard the console is timed for.
+///
+/// NTSC machines run 262 lines per frame from a 3.579545 MHz master clock
+/// (~59.92 Hz); PAL machines run 313 lines from 3.546893 MHz (~49.70 Hz).
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum VideoStandard {
+    #[default]
+    Ntsc,
+    Pal,
+}
+
+impl VideoStandard {
+    /// Z80 / PSG / YM2413 master clock in Hz.
+    pub fn master_clock(self) -> u32 {
+        match self {
+            VideoStandard::Ntsc => 3_579_545,
+            VideoStandard::Pal  => 3_546_893,
+        }
+    }
+
+    /// Total scanlines per frame, including blanking.
+    pub fn lines_per_frame(self) -> u16 {
+        match self {
+            VideoStandard::Ntsc => 262,
+            VideoStandard::Pal  => 313,
+        }
+    }
+
+    /// Maps an internal line number (0..lines_per_frame) to the value the
+    /// VDP V counter reports in 192-line mode.
+    ///
+    /// NTSC counts $00–$DA then jumps back to $D5–$FF;
+    /// PAL counts $00–$F2 then jumps back to $BA–$FF.
+    pub(crate) fn hw_vcounter(self, line: u16) -> u8 {
+        let (last, jump_to) = match self {
+            VideoStandard::Ntsc => (0xDA, 0xD5),
+            VideoStandard::Pal  => (0xF2, 0xBA),
+        };
+        if line <= last {
+            line as u8
+        } else {
+            (line - (last + 1) + jump_to) as u8
+        }
+    }
+
+    /// Real frame duration in microseconds (228 cycles × lines / clock).
+    pub fn frame_us(self) -> i64 {
+        match self {
+            VideoStandard::Ntsc => 16_688,
+            VideoStandard::Pal  => 20_120,
+        }
+    }
+}
+
+/// Console nationalization, as seen by software through the I/O ports.
+///
+/// Export consoles echo the TH/TR output levels programmed via port $3F
+/// back on ports $DC/$DD; Japanese consoles do not. Games use this to pick
+/// a language or to refuse to boot on the "wrong" machine. The Japanese
+/// Master System also ships with the YM2413 FM chip built in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ConsoleRegion {
+    Japan,
+    #[default]
+    Export,
+}
+
+impl ConsoleRegion {
+    /// True when the console has the FM sound unit on board by default.
+    pub fn has_builtin_fm(self) -> bool {
+        self == ConsoleRegion::Japan
+    }
+
+    /// Whether a game on `platform` runs without the FM unit. Only the Master
+    /// System takes one; it is fitted by default on the Japanese model, and
+    /// `user` (an explicit FM on/off choice) overrides that default.
+    pub fn fm_disabled(self, platform: Platform, user: Option<bool>) -> bool {
+        platform != Platform::MasterSystem || !user.unwrap_or(self.has_builtin_fm())
+    }
 }
 
 #[cfg(test)]
@@ -42,4 +188,93 @@ mod tests {
         assert!(!Platform::MasterSystem.is_sg_family());
         assert!(!Platform::GameGear.is_sg_family());
     }
+
+    #[test]
+    fn from_extension_is_case_insensitive() {
+        assert_eq!(Platform::from_extension("GG"), Platform::GameGear);
+        assert_eq!(Platform::from_extension("sg"), Platform::Sg1000);
+        assert_eq!(Platform::from_extension("Sc"), Platform::Sc3000);
+        assert_eq!(Platform::from_extension("sms"), Platform::MasterSystem);
+        assert_eq!(Platform::from_extension("bin"), Platform::MasterSystem);
+    }
+
+    #[test]
+    fn detect_uses_header_for_ambiguous_extensions() {
+        let mut gg = vec![0u8; 0x8000];
+        gg[0x7FF0..0x7FF8].copy_from_slice(b"TMR SEGA");
+        gg[0x7FFF] = 0x6C;
+        assert_eq!(Platform::detect("bin", &gg), Platform::GameGear);
+        assert_eq!(Platform::detect("sms", &gg), Platform::MasterSystem, "extensão explícita vence");
+    }
+
+    #[test]
+    fn detect_falls_back_to_code_heuristics() {
+        let mut rom = vec![0u8; 0x8000];
+        assert_eq!(Platform::detect("bin", &rom), Platform::Sg1000, "sem cabeçalho nem mapper");
+        rom[0x100..0x103].copy_from_slice(&[0x32, 0xFF, 0xFF]); // LD ($FFFF),A
+        assert_eq!(Platform::detect("bin", &rom), Platform::MasterSystem, "escreve no mapper");
+        assert_eq!(Platform::detect("rom", &vec![0u8; 0x20000]), Platform::MasterSystem, "grande demais para SG-1000");
+        assert_eq!(Platform::detect("SG", &rom), Platform::Sg1000);
+    }
+
+    #[test]
+    fn platform_names_round_trip() {
+        for p in [Platform::MasterSystem, Platform::GameGear, Platform::Sg1000, Platform::Sc3000] {
+            let short = match p {
+                Platform::MasterSystem => "sms",
+                Platform::GameGear => "gg",
+                Platform::Sg1000 => "sg",
+                Platform::Sc3000 => "sc",
+            };
+            assert_eq!(Platform::from_name(short), Some(p));
+        }
+        assert_eq!(Platform::from_name("nes"), None);
+    }
+
+    #[test]
+    fn only_japanese_console_has_builtin_fm() {
+        assert!(ConsoleRegion::Japan.has_builtin_fm());
+        assert!(!ConsoleRegion::Export.has_builtin_fm());
+    }
+
+    #[test]
+    fn fm_default_follows_region_unless_user_chooses() {
+        let sms = Platform::MasterSystem;
+        assert!(!ConsoleRegion::Japan.fm_disabled(sms, None));
+        assert!(ConsoleRegion::Export.fm_disabled(sms, None));
+        assert!(!ConsoleRegion::Export.fm_disabled(sms, Some(true)), "unidade FM avulsa");
+        assert!(ConsoleRegion::Japan.fm_disabled(sms, Some(false)));
+        assert!(ConsoleRegion::Japan.fm_disabled(Platform::GameGear, Some(true)), "só o SMS tem FM");
+    }
+
+    #[test]
+    fn ntsc_vcounter_jumps_from_da_to_d5() {
+        let v = VideoStandard::Ntsc;
+        assert_eq!(v.hw_vcounter(0), 0x00);
+        assert_eq!(v.hw_vcounter(218), 0xDA);
+        assert_eq!(v.hw_vcounter(219), 0xD5);
+        assert_eq!(v.hw_vcounter(v.lines_per_frame() - 1), 0xFF);
+    }
+
+    #[test]
+    fn pal_vcounter_jumps_from_f2_to_ba() {
+        let v = VideoStandard::Pal;
+        assert_eq!(v.hw_vcounter(242), 0xF2);
+        assert_eq!(v.hw_vcounter(243), 0xBA);
+        assert_eq!(v.hw_vcounter(v.lines_per_frame() - 1), 0xFF);
+    }
+
+    #[test]
+    fn pal_frame_is_longer_than_ntsc() {
+        assert!(VideoStandard::Pal.frame_us() > VideoStandard::Ntsc.frame_us());
+        assert!(VideoStandard::Pal.master_clock() < VideoStandard::Ntsc.master_clock());
+    }
+
+    #[test]
+    fn frame_us_matches_lines_and_clock() {
+        for v in [VideoStandard::Ntsc, VideoStandard::Pal] {
+            let us = 228 * v.lines_per_frame() as i64 * 1_000_000 / v.master_clock() as i64;
+            assert_eq!(v.frame_us(), us, "{v:?}");
+        }
+    }
 }
@@ -0,0 +1,234 @@
+//! RAM search ("cheat finder"): narrows a set of candidate addresses by
+//! comparing successive snapshots of a memory region.
+
+use crate::cheats::Cheat;
+use crate::memory::MemoryRegion;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueSize {
+    Byte,
+    Word,
+}
+
+/// How the bytes at a candidate offset are read as a number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValueFormat {
+    pub size:       ValueSize,
+    pub big_endian: bool,
+    pub signed:     bool,
+}
+
+impl Default for ValueFormat {
+    fn default() -> Self {
+        Self { size: ValueSize::Byte, big_endian: false, signed: false }
+    }
+}
+
+impl ValueFormat {
+    pub fn byte_len(&self) -> usize {
+        match self.size {
+            ValueSize::Byte => 1,
+            ValueSize::Word => 2,
+        }
+    }
+
+    fn modulus(&self) -> i64 {
+        1 << (8 * self.byte_len())
+    }
+
+    /// Value at `offset`, or `None` if it runs past the end of `data`.
+    pub fn read(&self, data: &[u8], offset: usize) -> Option<i64> {
+        let bytes = data.get(offset..offset + self.byte_len())?;
+        let raw = match (self.size, self.big_endian) {
+            (ValueSize::Byte, _)     => bytes[0] as u16,
+            (ValueSize::Word, false) => u16::from_le_bytes([bytes[0], bytes[1]]),
+            (ValueSize::Word, true)  => u16::from_be_bytes([bytes[0], bytes[1]]),
+        };
+        Some(match (self.size, self.signed) {
+            (_, false)               => raw as i64,
+            (ValueSize::Byte, true)  => raw as u8 as i8 as i64,
+            (ValueSize::Word, true)  => raw as i16 as i64,
+        })
+    }
+
+    /// Bytes of `value` in memory order (wrapped to the value's width).
+    pub fn encode(&self, value: i64) -> Vec<u8> {
+        let raw = value.rem_euclid(self.modulus()) as u16;
+        match (self.size, self.big_endian) {
+            (ValueSize::Byte, _)     => vec![raw as u8],
+            (ValueSize::Word, false) => raw.to_le_bytes().to_vec(),
+            (ValueSize::Word, true)  => raw.to_be_bytes().to_vec(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Greater,
+    Less,
+    /// Current minus previous equals N (modulo the value's width, so a
+    /// counter that wraps from 0 to 255 has changed by -1).
+    ChangedBy(i64),
+}
+
+/// What each candidate is compared against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    /// Its own value at the previous snapshot.
+    Previous,
+    Value(i64),
+}
+
+pub struct RamSearch {
+    region:     MemoryRegion,
+    format:     ValueFormat,
+    previous:   Vec<u8>,
+    candidates: Vec<usize>,
+}
+
+impl RamSearch {
+    /// Starts a search over every offset of `data` (a snapshot of `region`).
+    pub fn new(region: MemoryRegion, format: ValueFormat, data: &[u8]) -> Self {
+        let candidates = (0..(data.len() + 1).saturating_sub(format.byte_len())).collect();
+        Self { region, format, previous: data.to_vec(), candidates }
+    }
+
+    pub fn region(&self) -> MemoryRegion {
+        self.region
+    }
+
+    pub fn format(&self) -> ValueFormat {
+        self.format
+    }
+
+    /// Changes how values are read; candidates are kept.
+    pub fn set_format(&mut self, format: ValueFormat) {
+        self.format = format;
+        let len = self.previous.len();
+        self.candidates.retain(|&o| o + format.byte_len() <= len);
+    }
+
+    pub fn candidates(&self) -> &[usize] {
+        &self.candidates
+    }
+
+    /// Value of a candidate at the last snapshot.
+    pub fn previous(&self, offset: usize) -> Option<i64> {
+        self.format.read(&self.previous, offset)
+    }
+
+    /// Keeps the candidates whose value in `data` passes the comparison,
+    /// then makes `data` the new previous snapshot.
+    pub fn filter(&mut self, data: &[u8], comparison: Comparison, operand: Operand) {
+        let format = self.format;
+        let modulus = format.modulus();
+        let previous = &self.previous;
+        self.candidates.retain(|&offset| {
+            let (Some(cur), Some(prev)) = (format.read(data, offset), format.read(previous, offset)) else {
+                return false;
+            };
+            let other = match operand {
+                Operand::Previous => prev,
+                Operand::Value(v) => v,
+            };
+            match comparison {
+                Comparison::Equal     => cur == other,
+                Comparison::NotEqual  => cur != other,
+                Comparison::Greater   => cur > other,
+                Comparison::Less      => cur < other,
+                Comparison::ChangedBy(n) => (cur - prev).rem_euclid(modulus) == n.rem_euclid(modulus),
+            }
+        });
+        self.previous = data.to_vec();
+    }
+}
+
+/// Action Replay codes that hold `value` at a work-RAM offset (two codes
+/// for a word). Other regions aren't reachable by RAM codes.
+pub fn ram_cheats(region: MemoryRegion, offset: usize, format: ValueFormat, value: i64, name: &str) -> Result<Vec<Cheat>, String> {
+    if region != MemoryRegion::Ram {
+        return Err(format!("Action Replay codes can only write work RAM, not {}", region.name()));
+    }
+    format.encode(value).iter().enumerate()
+        .map(|(i, b)| {
+            let addr = 0xC000 + offset + i;
+            let label = if format.byte_len() > 1 { format!("{name} ({}/{})", i + 1, format.byte_len()) } else { name.to_string() };
+            Cheat::parse(&format!("00{addr:04X}{b:02X}"), &label)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CheatEffect;
+
+    const WORD_LE: ValueFormat = ValueFormat { size: ValueSize::Word, big_endian: false, signed: false };
+
+    #[test]
+    fn reads_values_in_each_format() {
+        let data = [0xFE, 0x01];
+        assert_eq!(ValueFormat::default().read(&data, 0), Some(0xFE));
+        assert_eq!(ValueFormat { signed: true, ..Default::default() }.read(&data, 0), Some(-2));
+        assert_eq!(WORD_LE.read(&data, 0), Some(0x01FE));
+        assert_eq!(ValueFormat { big_endian: true, ..WORD_LE }.read(&data, 0), Some(0xFE01));
+        assert_eq!(ValueFormat { big_endian: true, signed: true, ..WORD_LE }.read(&data, 0), Some(-511));
+        assert_eq!(WORD_LE.read(&data, 1), None, "palavra passaria do fim");
+        assert_eq!(WORD_LE.encode(-1), vec![0xFF, 0xFF]);
+        assert_eq!(ValueFormat { big_endian: true, ..WORD_LE }.encode(0x1234), vec![0x12, 0x34]);
+    }
+
+    #[test]
+    fn narrows_candidates_across_snapshots() {
+        // Contador de vidas em 0x10: 3 → 2 → 2; o resto muda ao acaso
+        let mut data = vec![0u8; 64];
+        data[0x10] = 3;
+        let mut search = RamSearch::new(MemoryRegion::Ram, ValueFormat::default(), &data);
+        assert_eq!(search.candidates().len(), 64);
+
+        search.filter(&data, Comparison::Equal, Operand::Value(3));
+        assert_eq!(search.candidates(), &[0x10]);
+
+        let mut search = RamSearch::new(MemoryRegion::Ram, ValueFormat::default(), &data);
+        data[0x10] = 2;
+        data[0x20] = 9;
+        search.filter(&data, Comparison::Less, Operand::Previous);
+        assert_eq!(search.candidates(), &[0x10]);
+        search.filter(&data, Comparison::Equal, Operand::Previous);
+        assert_eq!(search.candidates(), &[0x10], "sem mudança desde o último filtro");
+        assert_eq!(search.previous(0x10), Some(2));
+    }
+
+    #[test]
+    fn changed_by_wraps_with_the_value_width() {
+        let mut data = vec![0u8; 4];
+        data[1] = 0x00;
+        let mut search = RamSearch::new(MemoryRegion::Ram, ValueFormat::default(), &data);
+        data[1] = 0xFF;
+        data[2] = 0x05;
+        search.filter(&data, Comparison::ChangedBy(-1), Operand::Previous);
+        assert_eq!(search.candidates(), &[1], "0 → 255 conta como -1");
+
+        // Palavra big-endian 0x00FF → 0x0100
+        let mut data = vec![0x00, 0xFF, 0x00];
+        let mut search = RamSearch::new(MemoryRegion::Ram, ValueFormat { big_endian: true, ..WORD_LE }, &data);
+        assert_eq!(search.candidates().len(), 2);
+        data[..2].copy_from_slice(&[0x01, 0x00]);
+        search.filter(&data, Comparison::ChangedBy(1), Operand::Previous);
+        assert_eq!(search.candidates(), &[0]);
+    }
+
+    #[test]
+    fn results_become_action_replay_codes() {
+        let cheats = ram_cheats(MemoryRegion::Ram, 0x0123, WORD_LE, 999, "Dinheiro").unwrap();
+        let effects: Vec<_> = cheats.iter().map(Cheat::effect).collect();
+        assert_eq!(effects, vec![
+            CheatEffect::RamWrite { addr: 0xC123, value: 0xE7 },
+            CheatEffect::RamWrite { addr: 0xC124, value: 0x03 },
+        ]);
+        assert_eq!(cheats[1].name, "Dinheiro (2/2)");
+        assert!(ram_cheats(MemoryRegion::CartRam, 0, ValueFormat::default(), 1, "").is_err());
+    }
+}
@@ -0,0 +1,241 @@
+//! Rewind history: a bounded ring of save states, delta-compressed.
+//!
+//! Only the newest snapshot is kept in full. Each older one is stored as the
+//! XOR of itself with its successor, run-length encoded — between two frames
+//! most of VRAM, RAM and cart RAM is unchanged, so a ~41 KB state usually
+//! shrinks to a few hundred bytes. Stepping back XORs the newest delta into
+//! the full snapshot; the oldest deltas are dropped once the byte budget is hit.
+
+use std::collections::VecDeque;
+
+use crate::{Emulator, SaveState};
+
+pub struct RewindBuffer {
+    /// Newest snapshot, serialized.
+    current: Option<Vec<u8>>,
+    /// `encode(older XOR newer)` for each step back, oldest first.
+    deltas: VecDeque<Vec<u8>>,
+    /// Bytes held by `deltas`.
+    bytes: usize,
+    max_bytes: usize,
+    /// Capture one snapshot every `interval` frames.
+    interval: u32,
+    counter: u32,
+}
+
+impl RewindBuffer {
+    /// History limited to `max_bytes` of deltas, captured every `interval` frames.
+    pub fn new(max_bytes: usize, interval: u32) -> Self {
+        Self {
+            current: None,
+            deltas: VecDeque::new(),
+            bytes: 0,
+            max_bytes,
+            interval: interval.max(1),
+            counter: 0,
+        }
+    }
+
+    /// Call once per emulated frame; snapshots the machine every `interval` frames.
+    pub fn push(&mut self, emu: &Emulator) {
+        self.counter += 1;
+        if self.counter < self.interval { return; }
+        self.counter = 0;
+
+        let state = emu.save_state().serialize();
+        if let Some(prev) = self.current.take() {
+            if prev.len() == state.len() {
+                let delta = encode_xor(&prev, &state);
+                self.bytes += delta.len();
+                self.deltas.push_back(delta);
+                while self.bytes > self.max_bytes {
+                    match self.deltas.pop_front() {
+                        Some(d) => self.bytes -= d.len(),
+                        None => break,
+                    }
+                }
+            } else {
+                self.clear();
+            }
+        }
+        self.current = Some(state);
+    }
+
+    /// Steps one snapshot back and loads it into `emu`.
+    /// Returns `false` when the history is exhausted.
+    pub fn rewind(&mut self, emu: &mut Emulator) -> bool {
+        let (Some(current), Some(delta)) = (self.current.as_mut(), self.deltas.pop_back()) else {
+            return false;
+        };
+        self.bytes -= delta.len();
+        apply_xor(current, &delta);
+        self.counter = 0;
+        match SaveState::deserialize(current) {
+            Some(state) => { emu.load_state(state); true }
+            None => { self.clear(); false }
+        }
+    }
+
+    /// Number of steps that can be rewound.
+    pub fn len(&self) -> usize {
+        self.deltas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deltas.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.current = None;
+        self.deltas.clear();
+        self.bytes = 0;
+        self.counter = 0;
+    }
+}
+
+// ── Delta codec ──────────────────────────────────────────────────────────────
+//
+// The XOR of two snapshots is encoded as a sequence of
+// `(zero run, literal length, literal bytes)` records, lengths as LEB128.
+
+fn put_len(out: &mut Vec<u8>, mut v: usize) {
+    loop {
+        let byte = (v & 0x7F) as u8;
+        v >>= 7;
+        if v == 0 { out.push(byte); return; }
+        out.push(byte | 0x80);
+    }
+}
+
+fn get_len(data: &[u8], pos: &mut usize) -> usize {
+    let mut v = 0usize;
+    let mut shift = 0;
+    while let Some(&byte) = data.get(*pos) {
+        *pos += 1;
+        v |= ((byte & 0x7F) as usize) << shift;
+        if byte & 0x80 == 0 { break; }
+        shift += 7;
+    }
+    v
+}
+
+/// Run-length encodes `a XOR b` (equal lengths).
+fn encode_xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < a.len() {
+        let zero_start = i;
+        while i < a.len() && a[i] == b[i] { i += 1; }
+        let lit_start = i;
+        // A literal ends at the first run of 4+ equal bytes (cheaper to restart).
+        while i < a.len() && a[i..(i + 4).min(a.len())].iter().zip(&b[i..]).any(|(x, y)| x != y) {
+            i += 1;
+        }
+        put_len(&mut out, lit_start - zero_start);
+        put_len(&mut out, i - lit_start);
+        out.extend(a[lit_start..i].iter().zip(&b[lit_start..i]).map(|(x, y)| x ^ y));
+    }
+    out
+}
+
+/// XORs an `encode_xor` delta into `buf` in place.
+fn apply_xor(buf: &mut [u8], delta: &[u8]) {
+    let mut pos = 0;
+    let mut i = 0;
+    while pos < delta.len() {
+        i += get_len(delta, &mut pos);
+        let lit = get_len(delta, &mut pos);
+        let end = (i + lit).min(buf.len());
+        for (dst, x) in buf[i..end].iter_mut().zip(&delta[pos..]) {
+            *dst ^= x;
+        }
+        pos += lit;
+        i += lit;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Platform, VideoStandard};
+    use crate::test_util::with_large_stack;
+
+    /// ROM que incrementa um contador em $C000 sem parar.
+    fn counter_rom() -> Vec<u8> {
+        let mut rom = vec![0u8; 0xC000];
+        // DI; LD HL,$C000; loop: INC (HL); JR loop
+        rom[..7].copy_from_slice(&[0xF3, 0x21, 0x00, 0xC0, 0x34, 0x18, 0xFD]);
+        rom
+    }
+
+    fn ram(emu: &Emulator) -> [u8; 8192] {
+        emu.save_state().mmu.ram
+    }
+
+    #[test]
+    fn xor_codec_roundtrip() {
+        let a: Vec<u8> = (0..5000u32).map(|i| (i * 7) as u8).collect();
+        let mut b = a.clone();
+        b[10] ^= 0xFF;
+        b[11] ^= 0x01;
+        b[4000..4100].iter_mut().for_each(|x| *x = x.wrapping_add(3));
+        let delta = encode_xor(&a, &b);
+        assert!(delta.len() < 200, "delta deve ser pequeno, veio {}", delta.len());
+        let mut restored = b.clone();
+        apply_xor(&mut restored, &delta);
+        assert_eq!(restored, a);
+    }
+
+    #[test]
+    fn identical_snapshots_encode_to_almost_nothing() {
+        let a = vec![0x55u8; 40_000];
+        assert!(encode_xor(&a, &a).len() <= 4);
+    }
+
+    #[test]
+    fn rewind_restores_previous_frames_in_order() {
+        with_large_stack(|| {
+            let mut emu = Emulator::new(counter_rom(), Platform::MasterSystem, VideoStandard::Ntsc, 44100.0);
+            let mut rw = RewindBuffer::new(1 << 20, 1);
+            let mut history = Vec::new();
+            for _ in 0..10 {
+                emu.step_frame();
+                rw.push(&emu);
+                history.push(ram(&emu));
+            }
+            assert_eq!(rw.len(), 9);
+            for expected in history.iter().rev().skip(1) {
+                assert!(rw.rewind(&mut emu));
+                assert_eq!(&ram(&emu), expected);
+            }
+            assert!(!rw.rewind(&mut emu), "histórico esgotado");
+        });
+    }
+
+    #[test]
+    fn budget_drops_oldest_snapshots() {
+        with_large_stack(|| {
+            let mut emu = Emulator::new(counter_rom(), Platform::MasterSystem, VideoStandard::Ntsc, 44100.0);
+            let mut rw = RewindBuffer::new(200, 1);
+            for _ in 0..50 {
+                emu.step_frame();
+                rw.push(&emu);
+            }
+            assert!(rw.bytes <= 200);
+            assert!(rw.len() < 49, "entradas antigas descartadas");
+        });
+    }
+
+    #[test]
+    fn interval_skips_frames() {
+        with_large_stack(|| {
+            let mut emu = Emulator::new(counter_rom(), Platform::MasterSystem, VideoStandard::Ntsc, 44100.0);
+            let mut rw = RewindBuffer::new(1 << 20, 4);
+            for _ in 0..20 {
+                emu.step_frame();
+                rw.push(&emu);
+            }
+            assert_eq!(rw.len(), 4, "5 snapshots → 4 passos");
+        });
+    }
+}
@@ -0,0 +1,249 @@
+//! Cartridge headers: the `TMR SEGA` block the export BIOS checks before
+//! running a game, and the build stamp Codemasters put at $7FE0.
+//!
+//! The Sega header is 16 bytes at $7FF0 (some small games use $3FF0 or $1FF0):
+//!
+//! | offset | contents                                                   |
+//! |--------|------------------------------------------------------------|
+//! | +$0    | `TMR SEGA`                                                 |
+//! | +$A    | checksum, little-endian                                    |
+//! | +$C    | product code, BCD, low digits first; high digit in +$E bits 7–4 |
+//! | +$E    | bits 3–0: version                                          |
+//! | +$F    | bits 7–4: region code, bits 3–0: ROM size code             |
+
+use crate::mapper::has_codemasters_header;
+use crate::mmu::crc32;
+use crate::platform::Platform;
+
+const SIGNATURE: &[u8] = b"TMR SEGA";
+const OFFSETS: [usize; 3] = [0x7FF0, 0x3FF0, 0x1FF0];
+
+/// Region code from the Sega header; it also tells SMS and GG games apart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeaderRegion {
+    SmsJapan,
+    SmsExport,
+    GgJapan,
+    GgExport,
+    GgInternational,
+    Unknown(u8),
+}
+
+impl HeaderRegion {
+    fn from_code(code: u8) -> Self {
+        match code {
+            3 => HeaderRegion::SmsJapan,
+            4 => HeaderRegion::SmsExport,
+            5 => HeaderRegion::GgJapan,
+            6 => HeaderRegion::GgExport,
+            7 => HeaderRegion::GgInternational,
+            _ => HeaderRegion::Unknown(code),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            HeaderRegion::SmsJapan        => "SMS Japan".to_string(),
+            HeaderRegion::SmsExport       => "SMS Export".to_string(),
+            HeaderRegion::GgJapan         => "GG Japan".to_string(),
+            HeaderRegion::GgExport        => "GG Export".to_string(),
+            HeaderRegion::GgInternational => "GG International".to_string(),
+            HeaderRegion::Unknown(code)   => format!("Unknown (${code:X})"),
+        }
+    }
+
+    /// Console the region code was assigned for, if it is a known one.
+    pub fn platform(&self) -> Option<Platform> {
+        match self {
+            HeaderRegion::SmsJapan | HeaderRegion::SmsExport => Some(Platform::MasterSystem),
+            HeaderRegion::GgJapan | HeaderRegion::GgExport | HeaderRegion::GgInternational => Some(Platform::GameGear),
+            HeaderRegion::Unknown(_) => None,
+        }
+    }
+}
+
+/// The `TMR SEGA` header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RomHeader {
+    /// Where the header was found ($7FF0, $3FF0 or $1FF0).
+    pub offset:       usize,
+    pub checksum:     u16,
+    pub product_code: u32,
+    pub version:      u8,
+    pub region:       HeaderRegion,
+    /// Declared ROM size in bytes; `None` for an unassigned size code.
+    pub rom_size:     Option<usize>,
+}
+
+impl RomHeader {
+    pub fn parse(rom: &[u8]) -> Option<Self> {
+        let offset = OFFSETS.into_iter()
+            .find(|&at| rom.get(at..at + SIGNATURE.len()) == Some(SIGNATURE))?;
+        let h = &rom[offset..offset + 16];
+        let bcd = |b: u8| (b >> 4) as u32 * 10 + (b & 0x0F) as u32;
+        Some(Self {
+            offset,
+            checksum:     u16::from_le_bytes([h[0xA], h[0xB]]),
+            product_code: bcd(h[0xC]) + bcd(h[0xD]) * 100 + (h[0xE] >> 4) as u32 * 10_000,
+            version:      h[0xE] & 0x0F,
+            region:       HeaderRegion::from_code(h[0xF] >> 4),
+            rom_size:     size_from_code(h[0xF] & 0x0F),
+        })
+    }
+
+    /// Sum of the bytes the BIOS checks: the declared size, minus the header
+    /// at $7FF0–$7FFF. `None` if the size code is unknown or the image is
+    /// shorter than declared.
+    pub fn computed_checksum(&self, rom: &[u8]) -> Option<u16> {
+        let size = self.rom_size?;
+        if rom.len() < size { return None; }
+        let sum = |range: &[u8]| range.iter().fold(0u16, |acc, &b| acc.wrapping_add(b as u16));
+        let low = sum(&rom[..size.min(0x8000) - 16]);
+        let high = if size > 0x8000 { sum(&rom[0x8000..size]) } else { 0 };
+        Some(low.wrapping_add(high))
+    }
+
+    pub fn checksum_ok(&self, rom: &[u8]) -> Option<bool> {
+        self.computed_checksum(rom).map(|sum| sum == self.checksum)
+    }
+}
+
+fn size_from_code(code: u8) -> Option<usize> {
+    Some(match code {
+        0xA => 0x2000,
+        0xB => 0x4000,
+        0xC => 0x8000,
+        0xD => 0xC000,
+        0xE => 0x10000,
+        0xF => 0x20000,
+        0x0 => 0x40000,
+        0x1 => 0x80000,
+        0x2 => 0x100000,
+        _ => return None,
+    })
+}
+
+/// Codemasters build stamp at $7FE0. Only the stored checksum's complement
+/// at $7FE8 is verified, which is also how the mapper is recognised.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CodemastersHeader {
+    pub banks:    u8,
+    /// Build date and time, decoded from BCD: (year, month, day, hour, minute).
+    pub built:    (u16, u8, u8, u8, u8),
+    pub checksum: u16,
+}
+
+impl CodemastersHeader {
+    pub fn parse(rom: &[u8]) -> Option<Self> {
+        if !has_codemasters_header(rom) { return None; }
+        let h = &rom[0x7FE0..0x7FF0];
+        let bcd = |b: u8| (b >> 4) * 10 + (b & 0x0F);
+        Some(Self {
+            banks:    h[0],
+            built:    (1900 + bcd(h[3]) as u16, bcd(h[2]), bcd(h[1]), bcd(h[4]), bcd(h[5])),
+            checksum: u16::from_le_bytes([h[6], h[7]]),
+        })
+    }
+
+    pub fn date(&self) -> String {
+        let (y, mo, d, h, mi) = self.built;
+        format!("{y:04}-{mo:02}-{d:02} {h:02}:{mi:02}")
+    }
+}
+
+/// Everything the ROM image says about itself.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RomInfo {
+    pub size:         usize,
+    pub crc32:        u32,
+    pub header:       Option<RomHeader>,
+    /// Checksum computed over the declared size, to compare with the header's.
+    pub computed_checksum: Option<u16>,
+    pub codemasters:  Option<CodemastersHeader>,
+}
+
+impl RomInfo {
+    pub fn new(rom: &[u8]) -> Self {
+        let header = RomHeader::parse(rom);
+        Self {
+            size: rom.len(),
+            crc32: crc32(rom),
+            header,
+            computed_checksum: header.and_then(|h| h.computed_checksum(rom)),
+            codemasters: CodemastersHeader::parse(rom),
+        }
+    }
+
+    /// `None` when there is no header or its size can't be checked.
+    pub fn checksum_ok(&self) -> Option<bool> {
+        Some(self.computed_checksum? == self.header?.checksum)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ROM de `size` bytes com cabeçalho em `at` e checksum correto.
+    fn rom_with_header(size: usize, at: usize, region_size: u8) -> Vec<u8> {
+        let mut rom: Vec<u8> = (0..size).map(|i| (i * 7) as u8).collect();
+        rom[at..at + 8].copy_from_slice(SIGNATURE);
+        rom[at + 0xC] = 0x26;
+        rom[at + 0xD] = 0x70;
+        rom[at + 0xE] = 0x12;
+        rom[at + 0xF] = region_size;
+        let sum = RomHeader::parse(&rom).unwrap().computed_checksum(&rom).unwrap();
+        rom[at + 0xA..at + 0xC].copy_from_slice(&sum.to_le_bytes());
+        rom
+    }
+
+    #[test]
+    fn parses_sega_header_fields() {
+        let rom = rom_with_header(0x20000, 0x7FF0, 0x4F);
+        let h = RomHeader::parse(&rom).unwrap();
+        assert_eq!(h.offset, 0x7FF0);
+        assert_eq!(h.product_code, 17026, "BCD 26, 70 e dígito alto 1");
+        assert_eq!(h.version, 2);
+        assert_eq!(h.region, HeaderRegion::SmsExport);
+        assert_eq!(h.rom_size, Some(0x20000));
+        assert_eq!(h.checksum_ok(&rom), Some(true));
+    }
+
+    #[test]
+    fn finds_header_in_small_roms() {
+        let rom = rom_with_header(0x2000, 0x1FF0, 0x5A);
+        let h = RomHeader::parse(&rom).unwrap();
+        assert_eq!(h.offset, 0x1FF0);
+        assert_eq!(h.region.platform(), Some(Platform::GameGear));
+        assert_eq!(h.checksum_ok(&rom), Some(true));
+        assert!(RomHeader::parse(&vec![0u8; 0x8000]).is_none(), "sem assinatura");
+    }
+
+    #[test]
+    fn checksum_covers_declared_size_only() {
+        let mut rom = rom_with_header(0x10000, 0x7FF0, 0x6C);
+        let h = RomHeader::parse(&rom).unwrap();
+        rom[0xC000] ^= 0xFF;
+        assert_eq!(h.checksum_ok(&rom), Some(true), "fora dos 32KB declarados");
+        rom[0x7FF0 + 0xD] ^= 0xFF;
+        assert_eq!(h.checksum_ok(&rom), Some(true), "o próprio cabeçalho não entra");
+        rom[0x100] ^= 0xFF;
+        assert_eq!(h.checksum_ok(&rom), Some(false));
+        assert_eq!(h.checksum_ok(&rom[..0x4000]), None, "imagem menor que o declarado");
+    }
+
+    #[test]
+    fn parses_codemasters_header() {
+        let mut rom = vec![0u8; 0x20000];
+        rom[0x7FE0..0x7FE6].copy_from_slice(&[8, 0x15, 0x03, 0x93, 0x14, 0x30]);
+        rom[0x7FE6..0x7FE8].copy_from_slice(&0x1234u16.to_le_bytes());
+        rom[0x7FE8..0x7FEA].copy_from_slice(&(0x10000u32 - 0x1234).to_le_bytes()[..2]);
+        let info = RomInfo::new(&rom);
+        let cm = info.codemasters.unwrap();
+        assert_eq!(cm.banks, 8);
+        assert_eq!(cm.checksum, 0x1234);
+        assert_eq!(cm.date(), "1993-03-15 14:30");
+        assert!(info.header.is_none());
+        assert_eq!(info.checksum_ok(), None);
+    }
+}
@@ -1,4 +1,4 @@
-/// Save-state binary format  (magic "VSMS", version 1)
+/// Save-state binary format  (magic "VSMS", version 4)
 ///
 /// All integers are little-endian. booleans are 1 byte (0/1).
 /// f64 is stored as its IEEE-754 bit pattern (u64 LE).
@@ -7,7 +7,7 @@
 /// `SaveState::deserialize()`.  Both functions fail fast on any mismatch
 /// so a truncated or wrong-version file is simply ignored.
 const MAGIC: &[u8; 4] = b"VSMS";
-const VERSION: u8 = 2;
+const VERSION: u8 = 4;
 
 pub(crate) struct CpuState {
     pub(crate) af: u16, pub bc: u16, pub de: u16, pub hl: u16,
@@ -22,10 +22,12 @@ pub(crate) struct CpuState {
 pub(crate) struct MmuState {
     pub(crate) ram: [u8; 8192],
     pub(crate) cart_ram: [u8; 16384],
-    pub(crate) ram_control: u8,
-    pub(crate) rom_bank_0: usize,
-    pub(crate) rom_bank_1: usize,
-    pub(crate) rom_bank_2: usize,
+    /// Mapper bank registers; layout depends on the mapper.
+    pub(crate) mapper: Vec<u8>,
+    /// Port $3E memory control.
+    pub(crate) memory_control: u8,
+    /// BIOS mapper registers (empty without a BIOS).
+    pub(crate) bios_mapper: Vec<u8>,
 }
 
 pub(crate) struct VdpState {
@@ -66,7 +68,7 @@ pub(crate) struct EmuTimingState {
     pub(crate) frame_cycles: u32,
 }
 
-pub(crate) struct SaveState {
+pub struct SaveState {
     pub(crate) cpu:    CpuState,
     pub(crate) mmu:    MmuState,
     pub(crate) vdp:    VdpState,
@@ -74,54 +76,60 @@ pub(crate) struct SaveState {
     pub(crate) timing: EmuTimingState,
 }
 
-struct Ser(Vec<u8>);
+/// Little-endian writer shared by the binary file formats (save states, movies).
+pub(crate) struct Ser(pub(crate) Vec<u8>);
 
 impl Ser {
-    fn new() -> Self { Ser(Vec::with_capacity(64 * 1024)) }
-    fn u8(&mut self, v: u8)   { self.0.push(v); }
-    fn u16(&mut self, v: u16) { self.0.extend_from_slice(&v.to_le_bytes()); }
-    fn u32(&mut self, v: u32) { self.0.extend_from_slice(&v.to_le_bytes()); }
-    fn i8(&mut self, v: i8)   { self.0.push(v as u8); }
-    fn i32(&mut self, v: i32) { self.0.extend_from_slice(&v.to_le_bytes()); }
-    fn f64(&mut self, v: f64) { self.0.extend_from_slice(&v.to_bits().to_le_bytes()); }
-    fn bool(&mut self, v: bool) { self.0.push(v as u8); }
-    fn bytes(&mut self, v: &[u8]) { self.0.extend_from_slice(v); }
+    pub(crate) fn new() -> Self { Ser(Vec::with_capacity(64 * 1024)) }
+    pub(crate) fn u8(&mut self, v: u8)   { self.0.push(v); }
+    pub(crate) fn u16(&mut self, v: u16) { self.0.extend_from_slice(&v.to_le_bytes()); }
+    pub(crate) fn u32(&mut self, v: u32) { self.0.extend_from_slice(&v.to_le_bytes()); }
+    pub(crate) fn i8(&mut self, v: i8)   { self.0.push(v as u8); }
+    pub(crate) fn i32(&mut self, v: i32) { self.0.extend_from_slice(&v.to_le_bytes()); }
+    pub(crate) fn f64(&mut self, v: f64) { self.0.extend_from_slice(&v.to_bits().to_le_bytes()); }
+    pub(crate) fn bool(&mut self, v: bool) { self.0.push(v as u8); }
+    pub(crate) fn bytes(&mut self, v: &[u8]) { self.0.extend_from_slice(v); }
 }
 
-struct De<'a> { data: &'a [u8], pos: usize }
+pub(crate) struct De<'a> { data: &'a [u8], pos: usize }
 
 impl<'a> De<'a> {
-    fn new(data: &'a [u8]) -> Self { De { data, pos: 0 } }
+    pub(crate) fn new(data: &'a [u8]) -> Self { De { data, pos: 0 } }
 
-    fn u8(&mut self) -> Option<u8> {
+    pub(crate) fn u8(&mut self) -> Option<u8> {
         let v = *self.data.get(self.pos)?;
         self.pos += 1;
         Some(v)
     }
-    fn u16(&mut self) -> Option<u16> {
+    pub(crate) fn u16(&mut self) -> Option<u16> {
         let b = self.data.get(self.pos..self.pos + 2)?;
         self.pos += 2;
         Some(u16::from_le_bytes([b[0], b[1]]))
     }
-    fn u32(&mut self) -> Option<u32> {
+    pub(crate) fn u32(&mut self) -> Option<u32> {
         let b = self.data.get(self.pos..self.pos + 4)?;
         self.pos += 4;
         Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
     }
-    fn i8(&mut self) -> Option<i8>  { Some(self.u8()? as i8) }
-    fn i32(&mut self) -> Option<i32> {
+    pub(crate) fn i8(&mut self) -> Option<i8>  { Some(self.u8()? as i8) }
+    pub(crate) fn i32(&mut self) -> Option<i32> {
         let b = self.data.get(self.pos..self.pos + 4)?;
         self.pos += 4;
         Some(i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
     }
-    fn f64(&mut self) -> Option<f64> {
+    pub(crate) fn f64(&mut self) -> Option<f64> {
         let b = self.data.get(self.pos..self.pos + 8)?;
         self.pos += 8;
         let bits = u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]);
         Some(f64::from_bits(bits))
     }
-    fn bool(&mut self) -> Option<bool> { Some(self.u8()? != 0) }
-    fn bytes<const N: usize>(&mut self) -> Option<[u8; N]> {
+    pub(crate) fn bool(&mut self) -> Option<bool> { Some(self.u8()? != 0) }
+    pub(crate) fn slice(&mut self, len: usize) -> Option<&'a [u8]> {
+        let slice = self.data.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(slice)
+    }
+    pub(crate) fn bytes<const N: usize>(&mut self) -> Option<[u8; N]> {
         let slice = self.data.get(self.pos..self.pos + N)?;
         self.pos += N;
         let mut arr = [0u8; N];
@@ -131,7 +139,7 @@ impl<'a> De<'a> {
 }
 
 impl SaveState {
-    pub(crate) fn serialize(&self) -> Vec<u8> {
+    pub fn serialize(&self) -> Vec<u8> {
         let mut s = Ser::new();
 
         s.bytes(MAGIC);
@@ -151,10 +159,11 @@ impl SaveState {
         let m = &self.mmu;
         s.bytes(&m.ram);
         s.bytes(&m.cart_ram);
-        s.u8(m.ram_control);
-        s.u32(m.rom_bank_0 as u32);
-        s.u32(m.rom_bank_1 as u32);
-        s.u32(m.rom_bank_2 as u32);
+        s.u8(m.mapper.len() as u8);
+        s.bytes(&m.mapper);
+        s.u8(m.memory_control);
+        s.u8(m.bios_mapper.len() as u8);
+        s.bytes(&m.bios_mapper);
 
         // VDP
         let v = &self.vdp;
@@ -193,7 +202,7 @@ impl SaveState {
         s.0
     }
 
-    pub(crate) fn deserialize(data: &[u8]) -> Option<Self> {
+    pub fn deserialize(data: &[u8]) -> Option<Self> {
         let mut d = De::new(data);
 
         // Header
@@ -216,10 +225,15 @@ impl SaveState {
         let mmu = MmuState {
             ram:       d.bytes::<8192>()?,
             cart_ram:  d.bytes::<16384>()?,
-            ram_control: d.u8()?,
-            rom_bank_0: d.u32()? as usize,
-            rom_bank_1: d.u32()? as usize,
-            rom_bank_2: d.u32()? as usize,
+            mapper: {
+                let len = d.u8()? as usize;
+                d.slice(len)?.to_vec()
+            },
+            memory_control: d.u8()?,
+            bios_mapper: {
+                let len = d.u8()? as usize;
+                d.slice(len)?.to_vec()
+            },
         };
 
         // VDP
@@ -292,8 +306,9 @@ mod tests {
             mmu: MmuState {
                 ram: [0xAB; 8192],
                 cart_ram: [0xCD; 16384],
-                ram_control: 0x08,
-                rom_bank_0: 0, rom_bank_1: 1, rom_bank_2: 2,
+                mapper: vec![0x08, 0, 1, 2],
+                memory_control: 0xAB,
+                bios_mapper: vec![0, 0, 1, 2],
             },
             vdp: VdpState {
                 vram: {
@@ -368,10 +383,9 @@ mod tests {
         assert_eq!(r.mmu.ram[8191], 0xAB);
         assert_eq!(r.mmu.cart_ram[0], 0xCD);
         assert_eq!(r.mmu.cart_ram[16383], 0xCD);
-        assert_eq!(r.mmu.ram_control, 0x08);
-        assert_eq!(r.mmu.rom_bank_0, 0);
-        assert_eq!(r.mmu.rom_bank_1, 1);
-        assert_eq!(r.mmu.rom_bank_2, 2);
+        assert_eq!(r.mmu.mapper, vec![0x08, 0, 1, 2]);
+        assert_eq!(r.mmu.memory_control, 0xAB);
+        assert_eq!(r.mmu.bios_mapper, vec![0, 0, 1, 2]);
     }
 
     #[test]
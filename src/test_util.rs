@@ -0,0 +1,16 @@
+//! Helpers shared by the unit tests.
+
+/// Runs `f` on a thread with a 32 MB stack to avoid overflow in debug builds.
+/// `step_frame` triggers deep Z80 dispatch which exceeds the default 2 MB test stack.
+pub(crate) fn with_large_stack<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    std::thread::Builder::new()
+        .stack_size(32 * 1024 * 1024)
+        .spawn(f)
+        .unwrap()
+        .join()
+        .unwrap()
+}
@@ -0,0 +1,348 @@
+//! Execution trace: one line per instruction, for diffing against other emulators.
+//!
+//! Each line is written before the instruction executes:
+//!
+//! ```text
+//! 02:8A20  3A 00 C0     LD A,($C000)     AF=1234 BC=0000 DE=0000 HL=C000 IX=0000 IY=0000 SP=DFF0 CYC=123456 V=C1 H=2A
+//! ```
+//!
+//! Optional event lines start with `--` (VDP register writes, interrupts) and
+//! each traced frame begins with a `== frame N ==` marker, so both are easy to
+//! strip before comparing with another emulator's log.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use crate::core::Emulator;
+
+/// What to trace and where to write it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceOptions {
+    pub path:       PathBuf,
+    /// Inclusive PC range; instructions outside it are not logged.
+    pub pc_range:   Option<(u16, u16)>,
+    /// Only log instructions fetched from this ROM bank.
+    pub bank:       Option<u16>,
+    /// Inclusive frame range (frames count from 0 when tracing starts).
+    pub frames:     Option<(u64, u64)>,
+    /// Also log VDP register writes.
+    pub vdp_writes: bool,
+    /// Also log accepted IRQs and NMIs.
+    pub interrupts: bool,
+}
+
+fn parse_hex(s: &str) -> Option<u16> {
+    let s = s.trim().trim_start_matches('$');
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    u16::from_str_radix(s, 16).ok()
+}
+
+fn parse_pc_range(s: &str) -> Option<(u16, u16)> {
+    let (a, b) = s.split_once('-')?;
+    let (a, b) = (parse_hex(a)?, parse_hex(b)?);
+    (a <= b).then_some((a, b))
+}
+
+fn parse_frame_range(s: &str) -> Option<(u64, u64)> {
+    let (a, b) = s.split_once('-')?;
+    let a = a.trim().parse().ok()?;
+    let b = if b.trim().is_empty() { u64::MAX } else { b.trim().parse().ok()? };
+    (a <= b).then_some((a, b))
+}
+
+impl TraceOptions {
+    /// For arguments handled by [`TraceOptions::from_args`], whether the next
+    /// argument is their value; `None` for anything else.
+    pub fn is_trace_arg(arg: &str) -> Option<bool> {
+        match arg {
+            "--trace" | "--trace-pc" | "--trace-bank" | "--trace-frames" => Some(true),
+            "--trace-vdp" | "--trace-irq" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Picks the `--trace*` options out of `args`. Returns `None` without `--trace`.
+    pub fn from_args(args: &[String]) -> Result<Option<Self>, String> {
+        let mut path = None;
+        let mut opts = TraceOptions {
+            path: PathBuf::new(), pc_range: None, bank: None, frames: None,
+            vdp_writes: false, interrupts: false,
+        };
+        let mut filtered = false;
+
+        let mut it = args.iter();
+        while let Some(arg) = it.next() {
+            let mut value = || it.next().cloned().ok_or_else(|| format!("{arg} needs a value"));
+            match arg.as_str() {
+                "--trace" => path = Some(PathBuf::from(value()?)),
+                "--trace-pc" => {
+                    let v = value()?;
+                    opts.pc_range = Some(parse_pc_range(&v).ok_or_else(|| format!("invalid PC range '{v}'"))?);
+                }
+                "--trace-bank" => {
+                    let v = value()?;
+                    opts.bank = Some(parse_hex(&v).ok_or_else(|| format!("invalid bank '{v}'"))?);
+                }
+                "--trace-frames" => {
+                    let v = value()?;
+                    opts.frames = Some(parse_frame_range(&v).ok_or_else(|| format!("invalid frame range '{v}'"))?);
+                }
+                "--trace-vdp" => opts.vdp_writes = true,
+                "--trace-irq" => opts.interrupts = true,
+                _ => continue,
+            }
+            filtered |= arg != "--trace";
+        }
+
+        match path {
+            Some(p) => { opts.path = p; Ok(Some(opts)) }
+            None if filtered => Err("--trace-* options need --trace FILE".into()),
+            None => Ok(None),
+        }
+    }
+}
+
+/// CPU interrupt state sampled before an instruction, to tell afterwards
+/// whether the Z80 accepted an interrupt at the end of it.
+#[derive(Clone, Copy)]
+pub(crate) struct InterruptProbe {
+    irq_pending: bool,
+    nmi_pulse:   bool,
+    iff1:        bool,
+    opcode:      u8,
+}
+
+impl InterruptProbe {
+    pub(crate) fn capture(emu: &Emulator) -> Self {
+        let c = &emu.cpu;
+        Self {
+            irq_pending: c.irq_pending != 0,
+            nmi_pulse: c.nmi_pending & 1 != 0,
+            iff1: c.iff1,
+            opcode: emu.peek(c.pc),
+        }
+    }
+}
+
+/// Writes the trace for an [`Emulator`]; attach with [`Emulator::set_tracer`].
+pub struct Tracer {
+    opts:   TraceOptions,
+    out:    Box<dyn Write + Send>,
+    /// Frames completed since tracing started.
+    frame:  u64,
+    /// CPU cycles run since tracing started.
+    cycles: u64,
+    frame_marked: bool,
+    error:  Option<String>,
+}
+
+impl Tracer {
+    /// Creates (truncating) the trace file.
+    pub fn create(opts: TraceOptions) -> Result<Self, String> {
+        let file = File::create(&opts.path)
+            .map_err(|e| format!("failed to create trace {}: {e}", opts.path.display()))?;
+        Ok(Self::with_writer(opts, Box::new(BufWriter::new(file))))
+    }
+
+    pub(crate) fn with_writer(opts: TraceOptions, out: Box<dyn Write + Send>) -> Self {
+        Self { opts, out, frame: 0, cycles: 0, frame_marked: false, error: None }
+    }
+
+    pub fn options(&self) -> &TraceOptions { &self.opts }
+
+    /// Flushes buffered output and reports the first write error, if any.
+    pub fn finish(&mut self) -> Result<(), String> {
+        if let Err(e) = self.out.flush() { self.fail(e); }
+        match self.error.take() {
+            Some(e) => Err(format!("failed to write trace {}: {e}", self.opts.path.display())),
+            None => Ok(()),
+        }
+    }
+
+    fn fail(&mut self, e: std::io::Error) {
+        self.error.get_or_insert_with(|| e.to_string());
+    }
+
+    fn frame_in_range(&self) -> bool {
+        self.error.is_none() && self.opts.frames.is_none_or(|(a, b)| (a..=b).contains(&self.frame))
+    }
+
+    fn line(&mut self, text: std::fmt::Arguments) {
+        if !self.frame_marked {
+            self.frame_marked = true;
+            let frame = self.frame;
+            if let Err(e) = writeln!(self.out, "== frame {frame} ==") { self.fail(e); }
+        }
+        if let Err(e) = self.out.write_fmt(text).and_then(|_| self.out.write_all(b"\n")) {
+            self.fail(e);
+        }
+    }
+
+    /// Logs the instruction at PC, with registers as they are before it runs.
+    pub(crate) fn before_instruction(&mut self, emu: &Emulator) {
+        if !self.frame_in_range() { return; }
+        let pc = emu.cpu.pc;
+        if self.opts.pc_range.is_some_and(|(a, b)| !(a..=b).contains(&pc)) { return; }
+        let ins = emu.disassemble(pc);
+        if self.opts.bank.is_some() && ins.bank != self.opts.bank { return; }
+
+        let r = emu.registers();
+        let (v, h) = {
+            let bus = emu.cpu.io.bus.borrow();
+            (bus.vdp.v_counter, bus.vdp.h_counter)
+        };
+        let cycles = self.cycles;
+        self.line(format_args!(
+            "{:<48} AF={:04X} BC={:04X} DE={:04X} HL={:04X} IX={:04X} IY={:04X} SP={:04X} CYC={cycles} V={v:02X} H={h:02X}",
+            ins.to_string(), r.af, r.bc, r.de, r.hl, r.ix, r.iy, r.sp,
+        ));
+    }
+
+    /// Accounts the instruction's cycles and logs the events it caused.
+    pub(crate) fn after_instruction(&mut self, emu: &Emulator, cycles: u32, probe: InterruptProbe) {
+        self.cycles += cycles as u64;
+        let vdp_writes = emu.cpu.io.bus.borrow_mut().vdp.reg_write_log.as_mut().map(std::mem::take);
+        if !self.frame_in_range() { return; }
+
+        for (reg, value) in vdp_writes.unwrap_or_default() {
+            self.line(format_args!("-- VDP R{reg:X} = {value:02X}"));
+        }
+        if self.opts.interrupts {
+            let c = &emu.cpu;
+            if probe.nmi_pulse && c.nmi_pending & 1 == 0 {
+                self.line(format_args!("-- NMI -> {:04X}", c.pc));
+            } else if probe.irq_pending && probe.iff1 && !c.iff1 && probe.opcode != 0xF3 {
+                // IFF1 dropped without a DI: the interrupt was accepted.
+                self.line(format_args!("-- IRQ (IM {}) -> {:04X}", c.interrupt_mode, c.pc));
+            }
+        }
+    }
+
+    pub(crate) fn end_frame(&mut self) {
+        self.frame += 1;
+        self.frame_marked = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::{Platform, VideoStandard};
+    use crate::test_util::with_large_stack;
+    use std::sync::{Arc, Mutex};
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// Writer that keeps what was written so the test can read it back.
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+    }
+
+    impl Shared {
+        fn text(&self) -> String { String::from_utf8(self.0.lock().unwrap().clone()).unwrap() }
+    }
+
+    fn opts() -> TraceOptions {
+        TraceOptions::from_args(&args(&["--trace", "t.log"])).unwrap().unwrap()
+    }
+
+    /// Runs `frames` frames of `program` (at $0000) with a trace attached.
+    fn trace(program: Vec<u8>, opts: TraceOptions, frames: usize) -> String {
+        with_large_stack(move || {
+            let mut rom = vec![0u8; 0x8000];
+            rom[..program.len()].copy_from_slice(&program);
+            let mut emu = Emulator::new(rom, Platform::MasterSystem, VideoStandard::Ntsc, 44100.0);
+            let out = Shared::default();
+            emu.set_tracer(Some(Box::new(Tracer::with_writer(opts, Box::new(out.clone())))));
+            for _ in 0..frames { emu.step_frame(); }
+            emu.take_tracer().unwrap().finish().unwrap();
+            out.text()
+        })
+    }
+
+    // DI; LD SP,$DFF0; LD A,$20; OUT ($BF),A; LD A,$81; OUT ($BF),A; loop: JR loop
+    const PROGRAM: [u8; 16] = [
+        0xF3, 0x31, 0xF0, 0xDF, 0x3E, 0x20, 0xD3, 0xBF, 0x3E, 0x81, 0xD3, 0xBF, 0x18, 0xFE, 0, 0,
+    ];
+
+    // ── Options ──────────────────────────────────────────────────────────────
+
+    #[test]
+    fn options_parse_filters() {
+        let o = TraceOptions::from_args(&args(&[
+            "game.sms", "--trace", "out.log", "--trace-pc", "$8000-BFFF", "--trace-bank", "1F",
+            "--trace-frames", "10-", "--trace-vdp", "--trace-irq",
+        ])).unwrap().unwrap();
+        assert_eq!(o.path, PathBuf::from("out.log"));
+        assert_eq!(o.pc_range, Some((0x8000, 0xBFFF)));
+        assert_eq!(o.bank, Some(0x1F));
+        assert_eq!(o.frames, Some((10, u64::MAX)));
+        assert!(o.vdp_writes && o.interrupts);
+    }
+
+    #[test]
+    fn options_absent_without_trace() {
+        assert_eq!(TraceOptions::from_args(&args(&["game.sms", "--pal"])).unwrap(), None);
+        assert!(TraceOptions::from_args(&args(&["--trace-vdp"])).is_err(), "filtro sem --trace");
+        assert!(TraceOptions::from_args(&args(&["--trace", "t", "--trace-pc", "9000-8000"])).is_err());
+    }
+
+    // ── Output ───────────────────────────────────────────────────────────────
+
+    #[test]
+    fn line_has_disassembly_registers_and_cycles() {
+        let text = trace(PROGRAM.to_vec(), opts(), 2);
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some("== frame 0 =="));
+        let first = lines.next().unwrap();
+        assert!(first.starts_with("00:0000  F3"), "{first}");
+        assert!(first.contains("DI"), "{first}");
+        assert!(first.contains("CYC=0 "), "{first}");
+        let second = lines.next().unwrap();
+        assert!(second.contains("LD SP,$DFF0") && second.contains("CYC=4 "), "{second}");
+        assert!(text.contains("== frame 1 =="), "marcador do segundo frame");
+    }
+
+    #[test]
+    fn pc_range_filters_instructions() {
+        let o = TraceOptions { pc_range: Some((0x000C, 0x000C)), ..opts() };
+        let text = trace(PROGRAM.to_vec(), o, 1);
+        assert!(text.lines().skip(1).all(|l| l.starts_with("00:000C")), "só o JR");
+    }
+
+    #[test]
+    fn frame_range_skips_earlier_frames() {
+        let o = TraceOptions { frames: Some((1, 1)), ..opts() };
+        let text = trace(PROGRAM.to_vec(), o, 3);
+        assert!(text.starts_with("== frame 1 =="), "frames anteriores ignorados");
+        assert!(!text.contains("frame 2"));
+    }
+
+    #[test]
+    fn vdp_register_writes_are_logged() {
+        let o = TraceOptions { vdp_writes: true, ..opts() };
+        let text = trace(PROGRAM.to_vec(), o, 1);
+        assert!(text.contains("-- VDP R1 = 20"), "{text}");
+    }
+
+    #[test]
+    fn accepted_irq_is_logged() {
+        // IM 1; enable the vblank IRQ; EI; loop: JR loop. The handler at $38 just spins.
+        let mut program = vec![0xED, 0x56, 0x3E, 0x20, 0xD3, 0xBF, 0x3E, 0x81, 0xD3, 0xBF, 0xFB, 0x18, 0xFE];
+        program.resize(0x38, 0);
+        program.extend_from_slice(&[0x18, 0xFE]);
+        let o = TraceOptions { interrupts: true, ..opts() };
+        let text = trace(program, o, 2);
+        assert!(text.contains("-- IRQ (IM 1) -> 0038"), "IRQ de vblank aceita");
+    }
+}
@@ -9,7 +9,7 @@ enum VdpMode {
 
 /// Fixed 16-colour hardware palette of the TMS9918A.
 /// Indexed by colour code 0–15. Colour 0 is "Transparent" (rendered as backdrop).
-const TMS_PALETTE: [u32; 16] = [
+pub(crate) const TMS_PALETTE: [u32; 16] = [
     0xFF000000, // 0  Transparent
     0xFF000000, // 1  Black
     0xFF21C842, // 2  Medium Green
@@ -43,6 +43,9 @@ pub(crate) struct Vdp {
     pub(crate) line_interrupt_flag: bool,
     pub(crate) sprite_collision: bool,
     pub(crate) sprite_overflow: bool,
+    /// Drop sprites past the per-line limit (4 in TMS modes, 8 in Mode 4).
+    /// The overflow flag is set either way.
+    pub(crate) sprite_limit: bool,
     pub(crate) v_counter: u8,
     pub(crate) h_counter: u8,
     pub(crate) h_latched: bool,
@@ -50,6 +53,47 @@ pub(crate) struct Vdp {
     pub(crate) latched_v_counter: u8,
     pub(crate) platform: Platform,
     pub(crate) cram_latch: u8,
+    /// Register writes `(index, value)` collected for the trace log, when enabled.
+    pub(crate) reg_write_log: Option<Vec<(u8, u8)>>,
+}
+
+/// ARGB colour of palette entry `index` (0–31) from CRAM.
+pub(crate) fn cram_color(cram: &[u8; 64], is_gg: bool, index: usize) -> u32 {
+    if is_gg {
+        // Game Gear Palette: 12-bit xxxxbbbbggggrrrr (Words at even addresses)
+        let base_addr = (index & 0x1F) * 2;
+        let lo = cram[base_addr] as u16;
+        let hi = cram[base_addr + 1] as u16;
+        let color = lo | (hi << 8);
+
+        let r = (color & 0x0F) * 17;
+        let g = ((color >> 4) & 0x0F) * 17;
+        let b = ((color >> 8) & 0x0F) * 17;
+        0xFF000000 | ((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
+    } else {
+        // Master System Palette: 6-bit ..bbggrr
+        let color_byte = cram[index & 0x1F];
+        let r = (color_byte & 0x03) * 85;
+        let g = ((color_byte >> 2) & 0x03) * 85;
+        let b = ((color_byte >> 4) & 0x03) * 85;
+        0xFF000000 | ((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
+    }
+}
+
+/// Determine TMS9918A rendering mode from register bits M1, M2, M3, M4.
+pub(crate) fn tms_mode(registers: &[u8; 16]) -> u8 {
+    let m4 = (registers[0] >> 2) & 1; // SMS extension — if set, use Mode 4
+    if m4 != 0 { return 4; }
+    let m1 = (registers[1] >> 4) & 1; // Text
+    let m3 = (registers[1] >> 3) & 1; // Multicolor (SMS calls this M2)
+    let m2 = (registers[0] >> 1) & 1; // Graphics II (SMS calls this M3)
+    match (m1, m2, m3) {
+        (0, 0, 0) => 0, // Graphics I
+        (1, 0, 0) => 1, // Text
+        (0, 1, 0) => 2, // Graphics II
+        (0, 0, 1) => 3, // Multicolor
+        _         => 0, // Undefined — fall back to Graphics I
+    }
 }
 
 impl Vdp {
@@ -68,6 +112,7 @@ impl Vdp {
             line_interrupt_flag: false,
             sprite_collision: false,
             sprite_overflow: false,
+            sprite_limit: true,
             v_counter: 0,
             h_counter: 0,
             h_latched: false,
@@ -75,6 +120,7 @@ impl Vdp {
             latched_v_counter: 0,
             platform,
             cram_latch: 0,
+            reg_write_log: None,
         }
     }
 
@@ -138,41 +184,17 @@ impl Vdp {
     }
 
     fn get_color(&self, cram_address: usize) -> u32 {
-        if self.platform.is_gg() {
-            // Game Gear Palette: 12-bit xxxxbbbbggggrrrr (Words at even addresses)
-            let base_addr = (cram_address & 0x1F) * 2;
-            let lo = self.cram[base_addr] as u16;
-            let hi = self.cram[base_addr + 1] as u16;
-            let color = lo | (hi << 8);
-            
-            let r = (color & 0x0F) * 17;
-            let g = ((color >> 4) & 0x0F) * 17;
-            let b = ((color >> 8) & 0x0F) * 17;
-            0xFF000000 | ((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
-        } else {
-            // Master System Palette: 6-bit ..bbggrr
-            let color_byte = self.cram[cram_address & 0x1F];
-            let r = (color_byte & 0x03) * 85;
-            let g = ((color_byte >> 2) & 0x03) * 85;
-            let b = ((color_byte >> 4) & 0x03) * 85;
-            0xFF000000 | ((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
-        }
+        cram_color(&self.cram, self.platform.is_gg(), cram_address)
     }
 
-    /// Determine TMS9918A rendering mode from register bits M1, M2, M3, M4.
     fn tms_mode(&self) -> u8 {
-        let m4 = (self.registers[0] >> 2) & 1; // SMS extension — if set, use Mode 4
-        if m4 != 0 { return 4; }
-        let m1 = (self.registers[1] >> 4) & 1; // Text
-        let m3 = (self.registers[1] >> 3) & 1; // Multicolor (SMS calls this M2)
-        let m2 = (self.registers[0] >> 1) & 1; // Graphics II (SMS calls this M3)
-        match (m1, m2, m3) {
-            (0, 0, 0) => 0, // Graphics I
-            (1, 0, 0) => 1, // Text
-            (0, 1, 0) => 2, // Graphics II
-            (0, 0, 1) => 3, // Multicolor
-            _         => 0, // Undefined — fall back to Graphics I
-        }
+        tms_mode(&self.registers)
+    }
+
+    /// Whether lines are drawn in a TMS9918 mode: always on SG-1000/SC-3000,
+    /// and on the Master System while Mode 4 is off (SG-1000 games on a card).
+    pub(crate) fn in_tms_mode(&self) -> bool {
+        self.platform.is_sg_family() || (self.platform == Platform::MasterSystem && self.tms_mode() != 4)
     }
 
     /// TMS9918A Mode 0 — Graphics I (most common in SG-1000 games).
@@ -356,7 +378,7 @@ impl Vdp {
             sprites_on_line += 1;
             if sprites_on_line > 4 {
                 self.sprite_overflow = true;
-                break;
+                if self.sprite_limit { break; }
             }
 
             let x_byte = self.vram[(base + 1) & 0x3FFF];
@@ -411,7 +433,7 @@ impl Vdp {
 
     pub(crate) fn render_scanline(&mut self, screen_y: usize) {
         // SG-1000 / SC-3000 use TMS9918A modes (not SMS Mode 4)
-        if self.platform.is_sg_family() {
+        if self.in_tms_mode() {
             match self.tms_mode() {
                 1 => self.render_tms_mode1(screen_y),
                 2 => self.render_tms_mode2(screen_y),
@@ -564,7 +586,7 @@ impl Vdp {
                     // Master System só desenha os primeiros 8 sprites que encontrar na linha!
                     if sprites_on_this_line > 8 {
                         self.sprite_overflow = true;
-                        continue; 
+                        if self.sprite_limit { continue; }
                     }
                     
                     let y_in_sprite = screen_y - *actual_y;
@@ -721,6 +743,7 @@ impl Vdp {
                     let reg_data = (self.control_word & 0x00FF) as u8;
                     if reg_index <= 10 {
                         self.registers[reg_index as usize] = reg_data;
+                        if let Some(log) = self.reg_write_log.as_mut() { log.push((reg_index, reg_data)); }
                         // TODO: Tratar side-effects (ex: reg 1 habilitar vídeo mode/vblank)
                     }
                     self.mode = VdpMode::VramRead; // Comandos param modo write
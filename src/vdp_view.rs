@@ -0,0 +1,461 @@
+//! Read-only decoding of VRAM and CRAM for the tile, tilemap, sprite and
+//! palette viewers. Works on a [`VdpSnapshot`] so the emulator keeps running.
+
+use crate::vdp::{cram_color, tms_mode, Vdp, TMS_PALETTE};
+
+/// An ARGB image, row-major.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    pub width:  usize,
+    pub height: usize,
+    pub pixels: Vec<u32>,
+}
+
+impl Image {
+    fn new(width: usize, height: usize) -> Self {
+        Self { width, height, pixels: vec![0xFF000000; width * height] }
+    }
+
+    fn set(&mut self, x: usize, y: usize, argb: u32) {
+        if x < self.width && y < self.height { self.pixels[y * self.width + x] = argb; }
+    }
+}
+
+/// One Mode 4 name-table entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TileEntry {
+    pub tile:     u16,
+    pub h_flip:   bool,
+    pub v_flip:   bool,
+    /// Uses the sprite palette (CRAM 16–31).
+    pub sprite_palette: bool,
+    pub priority: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpriteInfo {
+    pub index:  usize,
+    /// Screen position of the top-left pixel (early clock / shift applied).
+    pub x:      i32,
+    pub y:      i32,
+    /// Pattern number (Mode 4: 0–511 including the R6 base).
+    pub tile:   u16,
+    pub width:  usize,
+    pub height: usize,
+    /// TMS colour (0 = transparent); `None` in Mode 4.
+    pub color:  Option<u8>,
+    /// False at and after the Y = $D0 terminator.
+    pub active: bool,
+}
+
+/// A copy of the VDP's memories and registers.
+pub struct VdpSnapshot {
+    vram: Box<[u8; 16384]>,
+    cram: [u8; 64],
+    pub registers: [u8; 16],
+    is_gg: bool,
+    is_tms: bool,
+}
+
+impl VdpSnapshot {
+    pub(crate) fn capture(vdp: &Vdp) -> Self {
+        Self {
+            vram: Box::new(vdp.vram),
+            cram: vdp.cram,
+            registers: vdp.registers,
+            is_gg: vdp.platform.is_gg(),
+            is_tms: vdp.in_tms_mode(),
+        }
+    }
+
+    pub fn vram(&self) -> &[u8] { &self.vram[..] }
+
+    pub fn is_gg(&self) -> bool { self.is_gg }
+
+    /// TMS9918 mode 0–3 on SG-1000/SC-3000, or on the SMS with Mode 4 off;
+    /// `None` for Mode 4.
+    pub fn tms_mode(&self) -> Option<u8> {
+        self.is_tms.then(|| match tms_mode(&self.registers) { 4 => 0, m => m })
+    }
+
+    // ── Palette ──────────────────────────────────────────────────────────────
+
+    /// Colours on screen: 32 CRAM entries in Mode 4, the fixed 16 TMS colours otherwise.
+    pub fn palette(&self) -> Vec<u32> {
+        if self.is_tms { return TMS_PALETTE.to_vec(); }
+        (0..32).map(|i| cram_color(&self.cram, self.is_gg, i)).collect()
+    }
+
+    /// Raw CRAM entry: 6-bit `--BBGGRR` (SMS) or 12-bit `----BBBBGGGGRRRR` (GG).
+    pub fn cram_value(&self, index: usize) -> u16 {
+        let i = index & 0x1F;
+        if self.is_gg {
+            u16::from_le_bytes([self.cram[i * 2], self.cram[i * 2 + 1]]) & 0x0FFF
+        } else {
+            (self.cram[i] & 0x3F) as u16
+        }
+    }
+
+    // ── Mode 4 ───────────────────────────────────────────────────────────────
+
+    /// Colour index (0–15) of pixel (x, y) of a 4bpp tile.
+    fn tile_pixel(&self, tile: usize, x: usize, y: usize) -> usize {
+        let row = (tile & 0x1FF) * 32 + y * 4;
+        let bit = 7 - x;
+        (0..4).map(|p| ((self.vram[row + p] >> bit) as usize & 1) << p).sum()
+    }
+
+    /// All 512 tiles, 16 per row (128×256), drawn with palette `bank` (0 = background, 1 = sprites).
+    pub fn tiles(&self, bank: usize) -> Image {
+        let palette = self.palette();
+        let mut img = Image::new(128, 256);
+        for tile in 0..512 {
+            let (tx, ty) = (tile % 16 * 8, tile / 16 * 8);
+            for y in 0..8 {
+                for x in 0..8 {
+                    img.set(tx + x, ty + y, palette[(bank & 1) * 16 + self.tile_pixel(tile, x, y)]);
+                }
+            }
+        }
+        img
+    }
+
+    pub fn name_table_base(&self) -> usize {
+        ((self.registers[2] & 0x0E) as usize) << 10
+    }
+
+    pub fn tile_entry(&self, col: usize, row: usize) -> TileEntry {
+        let addr = self.name_table_base() + ((row % 28) * 32 + col % 32) * 2;
+        let word = u16::from_le_bytes([self.vram[addr], self.vram[addr + 1]]);
+        TileEntry {
+            tile: word & 0x01FF,
+            h_flip: word & 0x0200 != 0,
+            v_flip: word & 0x0400 != 0,
+            sprite_palette: word & 0x0800 != 0,
+            priority: word & 0x1000 != 0,
+        }
+    }
+
+    /// The whole 32×28 name table (256×224), unscrolled.
+    pub fn name_table(&self) -> Image {
+        let palette = self.palette();
+        let mut img = Image::new(256, 224);
+        for row in 0..28 {
+            for col in 0..32 {
+                let e = self.tile_entry(col, row);
+                let bank = if e.sprite_palette { 16 } else { 0 };
+                for y in 0..8 {
+                    for x in 0..8 {
+                        let sx = if e.h_flip { 7 - x } else { x };
+                        let sy = if e.v_flip { 7 - y } else { y };
+                        let c = self.tile_pixel(e.tile as usize, sx, sy);
+                        img.set(col * 8 + x, row * 8 + y, palette[bank + c]);
+                    }
+                }
+            }
+        }
+        img
+    }
+
+    /// The visible 256×192 area within the name table as `(x, y, w, h)`
+    /// rectangles — up to four, since the window wraps around both edges.
+    pub fn scroll_window(&self) -> Vec<(usize, usize, usize, usize)> {
+        let x0 = (256 - self.registers[8] as usize) % 256;
+        let y0 = self.registers[9] as usize % 224;
+        let split = |start: usize, len: usize, size: usize| -> Vec<(usize, usize)> {
+            if start + len <= size { vec![(start, len)] } else { vec![(start, size - start), (0, start + len - size)] }
+        };
+        let mut rects = Vec::new();
+        for &(y, h) in &split(y0, 192, 224) {
+            for &(x, w) in &split(x0, 256, 256) {
+                rects.push((x, y, w, h));
+            }
+        }
+        rects
+    }
+
+    // ── Sprites ──────────────────────────────────────────────────────────────
+
+    /// The sprite attribute table: 64 entries in Mode 4, 32 in TMS modes.
+    pub fn sprites(&self) -> Vec<SpriteInfo> {
+        let r = &self.registers;
+        let tall = r[1] & 0x02 != 0;
+        let zoom = if r[1] & 0x01 != 0 { 2 } else { 1 };
+        let mut active = true;
+
+        if self.is_tms {
+            let sat = ((r[5] & 0x7F) as usize) << 7;
+            let size = if tall { 16 } else { 8 };
+            return (0..32).map(|i| {
+                let base = sat + i * 4;
+                let [y, x, name, attr] = [0, 1, 2, 3].map(|o| self.vram[(base + o) & 0x3FFF]);
+                active &= y != 0xD0;
+                SpriteInfo {
+                    index: i,
+                    x: x as i32 - if attr & 0x80 != 0 { 32 } else { 0 },
+                    y: y.wrapping_add(1) as i32,
+                    tile: if tall { name & 0xFC } else { name } as u16,
+                    width: size * zoom,
+                    height: size * zoom,
+                    color: Some(attr & 0x0F),
+                    active,
+                }
+            }).collect();
+        }
+
+        let sat = ((r[5] & 0x7E) as usize) << 7;
+        let tile_base = ((r[6] & 0x04) as u16) << 6;
+        (0..64).map(|i| {
+            let y = self.vram[sat + i];
+            let x = self.vram[sat + 0x80 + i * 2];
+            let name = self.vram[sat + 0x81 + i * 2];
+            active &= y != 0xD0;
+            SpriteInfo {
+                index: i,
+                x: x as i32 - if r[0] & 0x08 != 0 { 8 } else { 0 },
+                y: y.wrapping_add(1) as i32,
+                tile: tile_base + if tall { name & 0xFE } else { name } as u16,
+                width: 8 * zoom,
+                height: if tall { 16 } else { 8 } * zoom,
+                color: None,
+                active,
+            }
+        }).collect()
+    }
+
+    /// The sprite's pattern at 1:1 scale; transparent pixels are left at alpha 0.
+    pub fn sprite_image(&self, s: &SpriteInfo) -> Image {
+        let zoom = if self.registers[1] & 0x01 != 0 { 2 } else { 1 };
+        let (w, h) = (s.width / zoom, s.height / zoom);
+        let mut img = Image { width: w, height: h, pixels: vec![0; w * h] };
+        if self.is_tms {
+            let pat_base = ((self.registers[6] & 0x07) as usize) << 11;
+            let color = TMS_PALETTE[s.color.unwrap_or(0) as usize & 0x0F];
+            for y in 0..h {
+                for x in 0..w {
+                    // 16×16 quadrants: N+0 top-left, N+1 bottom-left, N+2 top-right, N+3 bottom-right
+                    let tile = s.tile as usize + (x / 8) * 2 + y / 8;
+                    let byte = self.vram[(pat_base + tile * 8 + y % 8) & 0x3FFF];
+                    if (byte >> (7 - x % 8)) & 1 != 0 { img.set(x, y, color); }
+                }
+            }
+        } else {
+            let palette = self.palette();
+            for y in 0..h {
+                for x in 0..w {
+                    let c = self.tile_pixel(s.tile as usize + y / 8, x, y % 8);
+                    if c != 0 { img.set(x, y, palette[16 + c]); }
+                }
+            }
+        }
+        img
+    }
+
+    // ── TMS9918 modes ────────────────────────────────────────────────────────
+
+    fn tms_backdrop(&self) -> u32 {
+        TMS_PALETTE[(self.registers[7] & 0x0F) as usize]
+    }
+
+    fn tms_color(&self, index: u8) -> u32 {
+        if index & 0x0F == 0 { self.tms_backdrop() } else { TMS_PALETTE[(index & 0x0F) as usize] }
+    }
+
+    /// Colour table of Graphics I: foreground/background for each group of 8 patterns.
+    pub fn tms_color_table(&self) -> Vec<(u8, u8)> {
+        let base = (self.registers[3] as usize) << 6;
+        (0..32).map(|i| {
+            let c = self.vram[(base + i) & 0x3FFF];
+            (c >> 4, c & 0x0F)
+        }).collect()
+    }
+
+    /// (pattern table base, colour table base) for the current TMS mode.
+    fn tms_tables(&self) -> (usize, usize) {
+        let r = &self.registers;
+        if self.tms_mode() == Some(2) {
+            (if r[4] & 0x04 != 0 { 0x2000 } else { 0 }, if r[3] & 0x80 != 0 { 0x2000 } else { 0 })
+        } else {
+            (((r[4] & 0x07) as usize) << 11, (r[3] as usize) << 6)
+        }
+    }
+
+    /// Foreground/background colours of row `y` of `pattern` (0–767 in Graphics II).
+    fn tms_pattern_colors(&self, pattern: usize, y: usize) -> (u32, u32) {
+        let (_, color_base) = self.tms_tables();
+        match self.tms_mode() {
+            Some(1) => (self.tms_color(self.registers[7] >> 4), self.tms_backdrop()),
+            Some(2) => {
+                let c = self.vram[(color_base + pattern * 8 + y) & 0x3FFF];
+                (self.tms_color(c >> 4), self.tms_color(c))
+            }
+            Some(3) => (0xFFFFFFFF, 0xFF000000),
+            _ => {
+                let c = self.vram[(color_base + (pattern & 0xFF) / 8) & 0x3FFF];
+                (self.tms_color(c >> 4), self.tms_color(c))
+            }
+        }
+    }
+
+    /// The pattern table, 16 patterns per row: 256 patterns (128×128), or
+    /// 768 in Graphics II (128×384, one 256-pattern block per screen third).
+    pub fn tms_patterns(&self) -> Image {
+        let (pattern_base, _) = self.tms_tables();
+        let count = if self.tms_mode() == Some(2) { 768 } else { 256 };
+        let mut img = Image::new(128, count / 2);
+        for p in 0..count {
+            let (px, py) = (p % 16 * 8, p / 16 * 8);
+            for y in 0..8 {
+                let byte = self.vram[(pattern_base + p * 8 + y) & 0x3FFF];
+                let (fg, bg) = self.tms_pattern_colors(p, y);
+                for x in 0..8 {
+                    img.set(px + x, py + y, if (byte >> (7 - x)) & 1 != 0 { fg } else { bg });
+                }
+            }
+        }
+        img
+    }
+
+    /// The background as displayed (256×192, no sprites). Text mode fills
+    /// 40 columns of 6 pixels between 8-pixel borders.
+    pub fn tms_name_table(&self) -> Image {
+        let mode = self.tms_mode().unwrap_or(0);
+        let name_base = ((self.registers[2] & 0x0F) as usize) << 10;
+        let (pattern_base, _) = self.tms_tables();
+        let mut img = Image::new(256, 192);
+        img.pixels.fill(self.tms_backdrop());
+
+        let (cols, width, left) = if mode == 1 { (40, 6, 8) } else { (32, 8, 0) };
+        for row in 0..24 {
+            for col in 0..cols {
+                let name = self.vram[(name_base + row * cols + col) & 0x3FFF] as usize;
+                let pattern = if mode == 2 { (row / 8) * 256 + name } else { name };
+                for y in 0..8 {
+                    let (px, py) = (left + col * width, row * 8 + y);
+                    if mode == 3 {
+                        // Multicolor: each pattern byte is two 4×4 colour blocks
+                        let byte = self.vram[(pattern_base + name * 8 + ((py / 4) & 1) + (row & 3) * 2) & 0x3FFF];
+                        for x in 0..8 {
+                            img.set(px + x, py, self.tms_color(if x < 4 { byte >> 4 } else { byte }));
+                        }
+                        continue;
+                    }
+                    let byte = self.vram[(pattern_base + pattern * 8 + y) & 0x3FFF];
+                    let (fg, bg) = self.tms_pattern_colors(pattern, y);
+                    for x in 0..width {
+                        img.set(px + x, py, if (byte >> (7 - x)) & 1 != 0 { fg } else { bg });
+                    }
+                }
+            }
+        }
+        img
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Platform;
+
+    fn snapshot(platform: Platform) -> (Vdp, VdpSnapshot) {
+        let mut vdp = Vdp::new(platform);
+        if !platform.is_sg_family() {
+            vdp.registers[0] = 0x04; // Mode 4, as SMS and GG games set it
+        }
+        let snap = VdpSnapshot::capture(&vdp);
+        (vdp, snap)
+    }
+
+    #[test]
+    fn tile_sheet_decodes_planar_pixels() {
+        let (mut vdp, _) = snapshot(Platform::MasterSystem);
+        // Tile 1, row 0: leftmost pixel colour 5 (planes 0 and 2)
+        vdp.vram[32] = 0x80;
+        vdp.vram[34] = 0x80;
+        vdp.cram[5] = 0x03; // red
+        let img = VdpSnapshot::capture(&vdp).tiles(0);
+        assert_eq!((img.width, img.height), (128, 256));
+        assert_eq!(img.pixels[8], 0xFFFF0000, "tile 1 começa em x=8");
+        assert_eq!(img.pixels[9], 0xFF000000);
+    }
+
+    #[test]
+    fn name_table_applies_flip_and_palette() {
+        let (mut vdp, _) = snapshot(Platform::MasterSystem);
+        vdp.registers[2] = 0xFF; // name table at $3800
+        vdp.vram[0x3800] = 0x01;
+        vdp.vram[0x3801] = 0x0A; // tile 1, h-flip, sprite palette
+        vdp.vram[32] = 0x80;     // tile 1 row 0: leftmost pixel colour 1
+        vdp.cram[17] = 0x30;     // blue
+        let snap = VdpSnapshot::capture(&vdp);
+        let e = snap.tile_entry(0, 0);
+        assert_eq!(e, TileEntry { tile: 1, h_flip: true, v_flip: false, sprite_palette: true, priority: false });
+        let img = snap.name_table();
+        assert_eq!(img.pixels[7], 0xFF0000FF, "flip horizontal leva o pixel para x=7");
+    }
+
+    #[test]
+    fn scroll_window_wraps_around_edges() {
+        let (mut vdp, snap) = snapshot(Platform::MasterSystem);
+        assert_eq!(snap.scroll_window(), vec![(0, 0, 256, 192)]);
+        vdp.registers[8] = 16;  // scrolled right 16 px → window starts at x=240
+        vdp.registers[9] = 100;
+        let rects = VdpSnapshot::capture(&vdp).scroll_window();
+        assert_eq!(rects, vec![(240, 100, 16, 124), (0, 100, 240, 124), (240, 0, 16, 68), (0, 0, 240, 68)]);
+    }
+
+    #[test]
+    fn sprite_table_stops_at_terminator() {
+        let (mut vdp, _) = snapshot(Platform::MasterSystem);
+        vdp.registers[5] = 0x7F; // SAT at $3F00
+        vdp.registers[6] = 0x04; // sprite tiles from 256
+        vdp.vram[0x3F00] = 49;
+        vdp.vram[0x3F80] = 100;
+        vdp.vram[0x3F81] = 3;
+        vdp.vram[0x3F01] = 0xD0;
+        let sprites = VdpSnapshot::capture(&vdp).sprites();
+        assert_eq!(sprites.len(), 64);
+        let s = sprites[0];
+        assert_eq!((s.x, s.y, s.tile, s.width, s.height, s.active), (100, 50, 259, 8, 8, true));
+        assert!(!sprites[1].active && !sprites[63].active);
+    }
+
+    #[test]
+    fn game_gear_cram_is_12_bit() {
+        let (mut vdp, _) = snapshot(Platform::GameGear);
+        vdp.cram[2] = 0x0F;
+        vdp.cram[3] = 0x0F; // entry 1: red 15, blue 15
+        let snap = VdpSnapshot::capture(&vdp);
+        assert_eq!(snap.cram_value(1), 0x0F0F);
+        assert_eq!(snap.palette()[1], 0xFFFF00FF);
+    }
+
+    #[test]
+    fn tms_graphics_one_patterns_use_color_table() {
+        let (mut vdp, _) = snapshot(Platform::Sg1000);
+        vdp.registers[3] = 0x80;  // colour table at $2000
+        vdp.registers[4] = 0x00;  // patterns at $0000
+        vdp.vram[0] = 0x80;       // pattern 0, row 0: leftmost pixel set
+        vdp.vram[0x2000] = 0xF4;  // white on dark blue
+        let snap = VdpSnapshot::capture(&vdp);
+        assert_eq!(snap.tms_mode(), Some(0));
+        assert_eq!(snap.tms_color_table()[0], (0x0F, 0x04));
+        let img = snap.tms_patterns();
+        assert_eq!((img.width, img.height), (128, 128));
+        assert_eq!(img.pixels[0], TMS_PALETTE[15]);
+        assert_eq!(img.pixels[1], TMS_PALETTE[4]);
+    }
+
+    #[test]
+    fn tms_name_table_renders_background() {
+        let (mut vdp, _) = snapshot(Platform::Sg1000);
+        vdp.registers[2] = 0x0E;  // names at $3800
+        vdp.registers[3] = 0x80;
+        vdp.vram[0x3800 + 1] = 1; // column 1 uses pattern 1
+        vdp.vram[8] = 0xFF;       // pattern 1, row 0 solid
+        vdp.vram[0x2000] = 0xA1;  // patterns 0–7: dark yellow on black
+        let img = VdpSnapshot::capture(&vdp).tms_name_table();
+        assert_eq!(img.pixels[8], TMS_PALETTE[10]);
+        assert_eq!(img.pixels[0], TMS_PALETTE[1]);
+    }
+}